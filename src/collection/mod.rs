@@ -1,6 +1,13 @@
+use js_sys::{Array, Object, Reflect};
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
+use crate::query::aggregate::Aggregation;
+use crate::query::options::{decode_cursor, encode_cursor, Projection, QueryOptions};
+use crate::query::plan::Explain;
+use crate::query::search::Search;
+use crate::query::Query;
 use crate::schema::Schema;
+use crate::storage::internals::core::CoreStorage;
 use crate::storage::{HookType, Storage};
 
 #[wasm_bindgen(typescript_custom_section)]
@@ -11,22 +18,51 @@ export type InternalsRecord = {
 /**
  * ExtractType is a utility type that maps a string representing a basic data type to the actual TypeScript type.
  *
- * @template T - A string literal type representing the basic data type ('string', 'number', 'boolean', 'object', 'array').
+ * @template T - A string literal type representing the basic data type ('string', 'number', 'integer', 'boolean', 'object', 'array', 'vector', 'date', 'bytes', 'null').
  *
  * @example
  * type StringType = ExtractType<'string'>; // StringType is string
  * type NumberType = ExtractType<'number'>; // NumberType is number
+ * type IntegerType = ExtractType<'integer'>; // IntegerType is number
  * type BooleanType = ExtractType<'boolean'>; // BooleanType is boolean
  * type ObjectType = ExtractType<'object'>; // ObjectType is object
  * type ArrayType = ExtractType<'array'>; // ArrayType is Array<any>
+ * type VectorType = ExtractType<'vector'>; // VectorType is number[]
+ * type DateType = ExtractType<'date'>; // DateType is Date
+ * type BytesType = ExtractType<'bytes'>; // BytesType is Uint8Array
+ * type NullType = ExtractType<'null'>; // NullType is null
  */
 export type ExtractType<T extends string> = T extends 'string' ? string :
     T extends 'number' ? number :
+    T extends 'integer' ? number :
     T extends 'boolean' ? boolean :
     T extends 'object' ? object :
     T extends 'array' ? Array<any> :
+    T extends 'vector' ? number[] :
+    T extends 'date' ? Date :
+    T extends 'bytes' ? Uint8Array :
+    T extends 'null' ? null :
     never;
 
+/**
+ * PropertyTypeName lists the type names of a property definition, each type of a union type.
+ *
+ * @example
+ * type Names = PropertyTypeName<{ type: readonly ['string', 'null'] }>; // Names is 'string' | 'null'
+ */
+export type PropertyTypeName<P extends { type: string | readonly string[] }> = P['type'] extends readonly (infer N)[] ? Extract<N, string> : Extract<P['type'], string>;
+
+/**
+ * PropertyValue maps a property definition to the type of its values: one of its `enum` values when it lists some, its extracted type otherwise, or `null` for nullable properties.
+ *
+ * @example
+ * type Status = PropertyValue<{ type: 'string', enum: readonly ['draft', 'published'] }>; // Status is 'draft' | 'published'
+ * type Score = PropertyValue<{ type: readonly ['number', 'string'], nullable: true }>; // Score is number | string | null
+ */
+export type PropertyValue<P extends { type: string | readonly string[] }> =
+    (P extends { enum: readonly (infer E)[] } ? E : ExtractType<PropertyTypeName<P>>)
+    | (P extends { nullable: true } ? null : never);
+
 /**
  * Doc is a utility type that transforms a schema type into a document type where each property is mapped to its extracted type.
  *
@@ -35,9 +71,67 @@ export type ExtractType<T extends string> = T extends 'string' ? string :
  * type Document = Doc<Schema>; // Document is { name: string; age: number; }
  */
 export type Doc<T extends SchemaType> = {
-    [name in keyof T['properties']]: ExtractType<T['properties'][name]['type']>
+    [name in keyof T['properties']]: PropertyValue<T['properties'][name]>
+} & {__version?: number };
+
+/**
+ * CreateDoc is the document type accepted when creating documents, where properties with a default value may be omitted.
+ */
+export type CreateDoc<T extends SchemaType> = {
+    [name in keyof T['properties'] as T['properties'][name] extends { default: unknown } ? never : name]: PropertyValue<T['properties'][name]>
+} & {
+    [name in keyof T['properties'] as T['properties'][name] extends { default: unknown } ? name : never]?: PropertyValue<T['properties'][name]>
 } & {__version?: number };
 
+/**
+ * Options accepted by `Collection.find`.
+ */
+export type FindOptions<T extends SchemaType> = QueryOptions<T> & {
+    /**
+     * Fields to return, the primary key is always included.
+     */
+    select?: Select<T>;
+};
+
+/**
+ * Options accepted by `Collection.findById`.
+ */
+export type FindByIdOptions<T extends SchemaType> = {
+    /**
+     * Fields to return, the primary key is always included.
+     */
+    select?: Select<T>;
+};
+
+/**
+ * Options for reading a collection one page at a time.
+ */
+export type FindPageOptions<T extends SchemaType> = {
+    /**
+     * Fields to sort by, defaults to the primary key. Ties are broken by the primary key.
+     */
+    sort?: QueryOptions<T>['sort'];
+    /**
+     * Maximum number of documents in the page.
+     */
+    pageSize: number;
+    /**
+     * Cursor returned with the previous page, omit it to read the first page.
+     */
+    after?: string | null;
+};
+
+/**
+ * A page of documents along with the cursor of the next page.
+ */
+export type Page<T extends SchemaType> = {
+    docs: Doc<T>[];
+    /**
+     * Opaque cursor to pass as `after` to read the next page, `null` on the last page.
+     */
+    nextCursor: string | null;
+};
+
 /**
  * Collection is a class that represents a collection of documents in a database.
  * @template T - A schema type defining the structure of the documents in the collection.
@@ -46,24 +140,75 @@ export class Collection<T extends SchemaType> {
     /**
      * Finds all documents in the collection.
      *
+     * @param query - The query documents have to match.
+     * @param options - Optional find options such as the sort order or the fields to return.
      * @returns A promise that resolves to an array of documents.
      */
-    find(query: QueryType<T>): Promise<Doc<T>[]>;
+    find(query: QueryType<T>, options?: FindOptions<T>): Promise<Doc<T>[]>;
+
+    /**
+     * Finds a page of documents, resuming after the cursor of the previous page.
+     *
+     * Pages are delimited by the sort values and primary key of their last document, so writes
+     * made between reads do not shift documents across pages.
+     *
+     * @param query - The query documents have to match.
+     * @param options - The page size, sort order and cursor.
+     * @returns A promise that resolves to the page and the cursor of the next one.
+     */
+    findPage(query: QueryType<T>, options: FindPageOptions<T>): Promise<Page<T>>;
 
     /**
      * count all documents in the collection.
      *
+     * @param query - The query documents have to match.
+     * @param options - Optional `limit` and `skip`, counting the documents of a single page.
      * @returns A promise that resolves to an array of documents.
      */
-    count(query: QueryType<T>): Promise<number>;
+    count(query: QueryType<T>, options?: QueryOptions<T>): Promise<number>;
+
+    /**
+     * Groups the documents matching the query and computes accumulators over each group.
+     *
+     * Storages implementing `aggregate` compute the result themselves, otherwise the matching
+     * documents are aggregated in memory.
+     *
+     * @param query - The query documents have to match.
+     * @param options - The properties to group by and the accumulators to compute.
+     * @returns A promise that resolves to one row per group, ordered by the groupBy values.
+     */
+    aggregate(query: QueryType<T>, options: AggregateOptions<T>): Promise<AggregateResult[]>;
+
+    /**
+     * Reports how the storage answers a query without returning its documents.
+     *
+     * Storages that do not implement `explain` only report the number of returned documents.
+     *
+     * @param query - The query to explain.
+     * @param options - Optional find options, as passed to `find`.
+     * @returns A promise that resolves to the plan chosen and the documents scanned and matched.
+     */
+    explain(query: QueryType<T>, options?: FindOptions<T>): Promise<ExplainResult>;
+
+    /**
+     * Searches the string properties of the documents, tolerating typos.
+     *
+     * Every document is scored, so searches read the whole collection.
+     *
+     * @param text - The searched terms.
+     * @param options - Optional properties to search, edits tolerated per term and maximum number of hits.
+     * @returns A promise that resolves to the matching documents along with their score, best first.
+     */
+    search(text: string, options?: SearchOptions<T>): Promise<SearchHit<T>[]>;
 
     /**
      * Finds a single document in the collection by its ID.
      *
      * @param id - The ID of the document to find.
+     * @param options - Optional fields to return.
      * @returns A promise that resolves to the found document.
      */
-    findById(id: string): Promise<Doc<T>>;
+    findById(id: string, options?: FindByIdOptions<T>): Promise<Doc<T>>;
 
     /**
      * Updates a document in the collection by its ID.
@@ -80,7 +225,7 @@ export class Collection<T extends SchemaType> {
      * @param document - The document to create.
      * @returns A promise that resolves to the created document.
      */
-    create(document: Doc<T>): Promise<Doc<T>>;
+    create(document: CreateDoc<T>): Promise<Doc<T>>;
 
     /**
      * Deletes a document in the collection by its ID.
@@ -137,8 +282,9 @@ impl Collection {
     /// This function is asynchronous and returns a `Schema` representing
     /// the documents found in the collection.
     #[wasm_bindgen]
-    pub async fn find(&mut self, query: JsValue) -> Result<JsValue, JsValue> {
-        let result = match self.storage.internal.find(&self.name, query).await {
+    pub async fn find(&mut self, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let (options, projection) = self.split_select(options)?;
+        let result = match self.storage.internal.find(&self.name, query, options).await {
             Ok(docs) => {
                 docs
             },
@@ -150,40 +296,231 @@ impl Collection {
         let processed = if result.is_undefined() || result.is_null() {
             result
         } else {
-            self.storage.call(&self.name, HookType::Recover, result)?
+            self.recover(result, projection.as_ref())?
         };
 
 
         Ok(processed)
     }
 
+    /// Finds a page of documents, resuming after the cursor of the previous page.
+    ///
+    /// This function is asynchronous and returns an object with the `docs` of the page and the
+    /// `nextCursor` to read the following one, `null` once there are no more documents.
+    #[wasm_bindgen(js_name="findPage")]
+    pub async fn find_page(&mut self, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let schema = self.storage.get_schema(&self.name)?.clone();
+        if !options.is_object() {
+            return Err(JsValue::from_str("findPage options must be an object"));
+        }
+        let page_size = Reflect::get(&options, &JsValue::from_str("pageSize"))?
+            .as_f64()
+            .filter(|size| *size >= 1.0 && size.fract() == 0.0)
+            .ok_or_else(|| JsValue::from_str("pageSize must be a positive integer"))? as usize;
+
+        // Without an explicit sort, pages follow the primary key
+        let mut sort = Reflect::get(&options, &JsValue::from_str("sort"))?;
+        if sort.is_undefined() || sort.is_null() {
+            let by_primary_key = Object::new();
+            Reflect::set(&by_primary_key, &JsValue::from_str(&schema.primary_key), &JsValue::from_f64(1.0))?;
+            sort = by_primary_key.into();
+        }
+
+        // One extra document tells whether there is a next page
+        let find_options = Object::new();
+        Reflect::set(&find_options, &JsValue::from_str("sort"), &sort)?;
+        Reflect::set(&find_options, &JsValue::from_str("limit"), &JsValue::from_f64((page_size + 1) as f64))?;
+        let after = Reflect::get(&options, &JsValue::from_str("after"))?;
+        if !after.is_undefined() && !after.is_null() {
+            let token = after
+                .as_string()
+                .ok_or_else(|| JsValue::from_str("Invalid page cursor"))?;
+            Reflect::set(&find_options, &JsValue::from_str("after"), &decode_cursor(&token)?)?;
+        }
+        let find_options: JsValue = find_options.into();
+        let query_options = QueryOptions::new(find_options.clone(), &schema)?;
+
+        let result = match self.storage.internal.find(&self.name, query, find_options).await {
+            Ok(docs) => docs,
+            Err(e) => {
+                return Err(js_sys::Error::new(&format!("Failed to find documents: {:?}", e)).into())
+            }
+        };
+
+        let mut docs = Array::from(&result).to_vec();
+        let next_cursor = if docs.len() > page_size {
+            docs.truncate(page_size);
+            let last = docs.last().ok_or_else(|| JsValue::from_str("Empty page"))?;
+            let keyset = CoreStorage {}.keyset(last, &query_options, &schema.primary_key)?;
+            JsValue::from_str(&encode_cursor(&keyset)?)
+        } else {
+            JsValue::NULL
+        };
+
+        let docs: Array = docs.into_iter().collect();
+        let processed = self.storage.call(&self.name, HookType::Recover, docs.into())?;
+
+        let page = Object::new();
+        Reflect::set(&page, &JsValue::from_str("docs"), &processed)?;
+        Reflect::set(&page, &JsValue::from_str("nextCursor"), &next_cursor)?;
+        Ok(page.into())
+    }
+
     /// counts and returns all documents in the collection.
     ///
     /// This function is asynchronous and returns a `Schema` representing
     /// the documents found in the collection.
     #[wasm_bindgen]
-    pub async fn count(&self, query: JsValue) -> Result<JsValue, JsValue> {
-        match self.storage.internal.count(&self.name, query).await {
+    pub async fn count(&self, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        match self.storage.internal.count(&self.name, query, options).await {
             Ok(count) => Ok(count),
             Err(e) => Err(js_sys::Error::new(&format!("Failed to count documents: {:?}", e)).into())
         }
     }
 
+    /// Groups the documents matching the query and computes accumulators over each group.
+    ///
+    /// This function is asynchronous. The aggregation is pushed down to the storage when it
+    /// implements `aggregate`, otherwise matching documents are aggregated by `CoreStorage`.
+    #[wasm_bindgen]
+    pub async fn aggregate(&self, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let schema = self.storage.get_schema(&self.name)?.clone();
+        let aggregation = Aggregation::new(&options, &schema)?;
+
+        let pushdown = Reflect::get(&self.storage.internal, &JsValue::from_str("aggregate"))?;
+        if pushdown.is_function() {
+            Query::new(query.clone(), schema)?.parse()?;
+            return match self.storage.internal.aggregate(&self.name, query, options).await {
+                Ok(rows) => Ok(rows),
+                Err(e) => Err(js_sys::Error::new(&format!("Failed to aggregate documents: {:?}", e)).into())
+            };
+        }
+
+        let documents = match self.storage.internal.find(&self.name, query, JsValue::undefined()).await {
+            Ok(docs) => docs,
+            Err(e) => {
+                return Err(js_sys::Error::new(&format!("Failed to aggregate documents: {:?}", e)).into())
+            }
+        };
+        // Older documents are migrated first, aggregated fields are never encrypted
+        let fields: Array = aggregation
+            .required_fields()
+            .iter()
+            .map(|field| JsValue::from_str(field))
+            .collect();
+        let recovered = self.storage.call_with_fields(&self.name, HookType::Recover, documents, &fields)?;
+        CoreStorage {}.aggregate(&Array::from(&recovered).to_vec(), &aggregation)
+    }
+
+    /// Reports how the storage answers a query: the plan it chose, how many documents it read
+    /// and matched, and how long it took.
+    ///
+    /// This function is asynchronous.
+    #[wasm_bindgen]
+    pub async fn explain(&self, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let (options, _) = self.split_select(options)?;
+        let explain = Reflect::get(&self.storage.internal, &JsValue::from_str("explain"))?;
+        if explain.is_function() {
+            return match self.storage.internal.explain(&self.name, query, options).await {
+                Ok(report) => Ok(report),
+                Err(e) => Err(js_sys::Error::new(&format!("Failed to explain query: {:?}", e)).into())
+            };
+        }
+
+        // Other storages can only be timed from the outside
+        let schema = self.storage.get_schema(&self.name)?.clone();
+        let normalized = Query::new(query.clone(), schema)?.get_query()?;
+        let started_at = js_sys::Date::now();
+        let documents = match self.storage.internal.find(&self.name, query, options).await {
+            Ok(docs) => docs,
+            Err(e) => {
+                return Err(js_sys::Error::new(&format!("Failed to explain query: {:?}", e)).into())
+            }
+        };
+        Explain {
+            query: normalized,
+            plan: None,
+            scanned: None,
+            matched: None,
+            returned: Array::from(&documents).length() as usize,
+            execution_time_ms: js_sys::Date::now() - started_at,
+        }.to_js()
+    }
+
+    /// Searches the string properties of the documents for terms close to the searched ones,
+    /// returning `{ doc, score }` hits, best first.
+    ///
+    /// This function is asynchronous.
+    #[wasm_bindgen]
+    pub async fn search(&self, text: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let schema = self.storage.get_schema(&self.name)?.clone();
+        let search = Search::new(&text, &options, &schema)?;
+
+        let documents = match self.storage.internal.find(&self.name, Object::new().into(), JsValue::undefined()).await {
+            Ok(docs) => docs,
+            Err(e) => {
+                return Err(js_sys::Error::new(&format!("Failed to search documents: {:?}", e)).into())
+            }
+        };
+        // Searched properties may be encrypted, documents are fully recovered
+        let recovered = self.storage.call(&self.name, HookType::Recover, documents)?;
+        CoreStorage {}.search(&Array::from(&recovered).to_vec(), &search, &schema.primary_key)
+    }
+
     /// Finds and returns a single document in the collection by its ID.
     ///
     /// This function is asynchronous.
     #[wasm_bindgen(js_name="findById")]
-    pub async fn find_by_id(&self, primary_key: JsValue) -> Result<JsValue, JsValue>{
+    pub async fn find_by_id(&self, primary_key: JsValue, options: JsValue) -> Result<JsValue, JsValue>{
+        let (_, projection) = self.split_select(options)?;
         let document = match self.storage.internal.find_document_by_id(&self.name, primary_key  ).await {
             Ok(doc) => doc,
             Err(e) => return Err(js_sys::Error::new(&format!("Failed to find document by ID: {:?}", e)).into())
         };
 
-        self.storage.call(
-            &self.name, 
-            HookType::Recover,
-            document
-        )
+        self.recover(document, projection.as_ref())
+    }
+
+    /// Separates the `select` projection from the options handed to the storage.
+    ///
+    /// Storages always return whole documents, projections are applied once plugins are done.
+    fn split_select(&self, options: JsValue) -> Result<(JsValue, Option<Projection>), JsValue> {
+        if !options.is_object() {
+            return Ok((options, None));
+        }
+        let schema = self.storage.get_schema(&self.name)?;
+        let select = Reflect::get(&options, &JsValue::from_str("select"))?;
+        let projection = Projection::new(&select, schema)?;
+        let storage_options = Object::assign(&Object::new(), &Object::from(options));
+        Reflect::delete_property(&storage_options, &JsValue::from_str("select"))?;
+        Ok((storage_options.into(), projection))
+    }
+
+    /// Runs the recover hooks over stored documents and applies the projection, if any.
+    ///
+    /// Hooks are told which fields the projection needs, so that decryption can be skipped
+    /// when no encrypted field is requested.
+    fn recover(&self, documents: JsValue, projection: Option<&Projection>) -> Result<JsValue, JsValue> {
+        let projection = match projection {
+            Some(projection) => projection,
+            None => return self.storage.call(&self.name, HookType::Recover, documents),
+        };
+        let schema = self.storage.get_schema(&self.name)?;
+        let fields: Array = projection
+            .required_fields(schema)
+            .iter()
+            .map(|field| JsValue::from_str(field))
+            .collect();
+        let recovered = self.storage.call_with_fields(&self.name, HookType::Recover, documents, &fields)?;
+        if Array::is_array(&recovered) {
+            let projected = Array::new();
+            for document in Array::from(&recovered).iter() {
+                projected.push(&projection.apply(&document)?);
+            }
+            Ok(projected.into())
+        } else {
+            projection.apply(&recovered)
+        }
     }
 
     /// Updates a document in the collection with the given data.
@@ -195,13 +532,14 @@ impl Collection {
     /// * `document` - A `JsValue` representing the partial document to update.
     #[wasm_bindgen]
     pub async fn update(&mut self, document: JsValue) -> Result<JsValue, JsValue> {
+        let (document, creating) = self.storage.with_defaults(&self.name, document).await?;
         let processed_document = self.storage.call(
             &self.name, 
             HookType::Create,
             document
         )?;
 
-        let res = match self.storage.write(&self.name, processed_document).await {
+        let res = match self.storage.write(&self.name, processed_document, creating).await {
             Ok(result) => result,
             Err(e) => return Err(e)
         };
@@ -222,13 +560,14 @@ impl Collection {
     /// * `document` - A `JsValue` representing the document to create.
     #[wasm_bindgen]
     pub async fn create(&mut self, document: JsValue) -> Result<JsValue, JsValue> {
+        let (document, creating) = self.storage.with_defaults(&self.name, document).await?;
         let processed_document = self.storage.call(
             &self.name, 
             HookType::Create,
             document
         )?;
 
-        let res = match self.storage.write(&self.name, processed_document.clone()).await {
+        let res = match self.storage.write(&self.name, processed_document.clone(), creating).await {
             Ok(result) => result,
             Err(e) => return Err(e)
         };
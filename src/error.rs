@@ -2,8 +2,46 @@ use js_sys::Reflect;
 use serde::{Deserialize, Serialize};
 use serde::de::value::Error;
 use wasm_bindgen::prelude::*;
+use crate::schema::property::Violation;
 use crate::utils::extract_property;
 
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * A document value breaking a rule of its schema.
+ */
+export type SchemaViolation = {
+    /**
+     * JSON pointer to the value in the document, as in `/address/city`.
+     */
+    path: string;
+    /**
+     * The schema keyword the value breaks, as in `type`, `required` or `maxLength`.
+     */
+    keyword: string;
+    /**
+     * What the keyword expects, as in the maximum length.
+     */
+    expected: unknown;
+    /**
+     * What the document holds instead, as in the length of the value.
+     */
+    actual: unknown;
+    /**
+     * What is wrong with the value, as in "should be at most 60 characters long".
+     */
+    message: string;
+};
+
+/**
+ * The error thrown when a document does not match its schema, listing every violation.
+ */
+export type ValidationError = Error & {
+    code: 'ValidationError';
+    violations: SchemaViolation[];
+};
+"#;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Errors {
     Error,
@@ -15,25 +53,42 @@ pub enum Errors {
 pub struct RIDBError {
     pub code: Errors,
     pub message: String,
+    /// Every value of a document breaking its schema, empty for other errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
 }
 
 impl RIDBError {
     pub fn error(err: &str) -> RIDBError {
         RIDBError {
             code: Errors::Error,
-            message:  format!("Serialization Error: {}", err)
+            message: format!("Serialization Error: {}", err),
+            violations: Vec::new(),
         }
     }
     pub fn serialisation(err: &str) -> RIDBError {
         RIDBError {
             code: Errors::SerializationError,
-            message: format!("Error: {}", err)
+            message: format!("Error: {}", err),
+            violations: Vec::new(),
         }
     }
     pub fn validation(err: &str) -> RIDBError {
         RIDBError {
             code: Errors::ValidationError,
-            message: format!("Validation Error: {}", err)
+            message: format!("Validation Error: {}", err),
+            violations: Vec::new(),
+        }
+    }
+    /// A validation error listing every violation of a document, which must not be empty.
+    pub fn invalid(violations: Vec<Violation>) -> RIDBError {
+        let messages: Vec<String> = violations
+            .iter()
+            .map(|violation| format!("Field {} {}", violation.field(), violation.message))
+            .collect();
+        RIDBError {
+            violations,
+            ..RIDBError::validation(&messages.join("; "))
         }
     }
 }
@@ -43,6 +98,7 @@ impl From<serde_wasm_bindgen::Error> for RIDBError {
         RIDBError {
             code: Errors::SerializationError,
             message:format!("Serialization {}", error),
+            violations: Vec::new(),
         }
     }
 }
@@ -51,9 +107,11 @@ impl From<JsValue> for RIDBError {
     fn from(error: JsValue) -> RIDBError {
         let code = extract_property::<Errors>(&error, "code").unwrap_or(Errors::Error);
         let message = extract_property::<String>(&error, "message").expect("Invalid JS Error no message is available");
+        let violations = extract_property::<Vec<Violation>>(&error, "violations").unwrap_or_default();
         RIDBError {
             code,
             message,
+            violations,
         }
     }
 }
@@ -63,6 +121,7 @@ impl From<&str> for RIDBError {
         RIDBError {
             code: Errors::SerializationError,
             message:format!("Serialization {}", error),
+            violations: Vec::new(),
         }
     }
 }
@@ -72,6 +131,7 @@ impl From<String> for RIDBError {
         RIDBError {
             code: Errors::SerializationError,
             message:format!("Serialization {}", error),
+            violations: Vec::new(),
         }
     }
 }
@@ -81,6 +141,7 @@ impl From<Error> for RIDBError {
         RIDBError {
             code: Errors::SerializationError,
             message:format!("Serialization {}", error),
+            violations: Vec::new(),
         }
     }
 }
@@ -89,6 +150,13 @@ impl From<RIDBError> for JsValue {
     fn from(failure: RIDBError) -> Self {
         let error = js_sys::Error::new(&failure.message).into();
         Reflect::set(&error, &"code".into(), &serde_wasm_bindgen::to_value(&failure.code).unwrap()).unwrap();
+        if !failure.violations.is_empty() {
+            // The error still carries the joined violations in its message when they fail to serialize
+            let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+            if let Ok(violations) = failure.violations.serialize(&serializer) {
+                let _ = Reflect::set(&error, &"violations".into(), &violations);
+            }
+        }
         error
     }
 }
@@ -1,7 +1,9 @@
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsValue;
 use crate::plugin::BasePlugin;
-use crate::schema::Schema;
+use crate::schema::{map_typed, Schema};
+use crate::schema::property_type::PropertyType;
+use crate::utils::to_bytes;
 use js_sys::{Object, Reflect};
 use chacha20poly1305::{
     aead::{Aead, KeyInit},
@@ -46,11 +48,9 @@ impl EncryptionPlugin {
             result
         }) as Box<dyn Fn(JsValue, JsValue, JsValue) -> Result<JsValue, JsValue>>);
 
-        let recover_hook = Closure::wrap(Box::new(move |schema, migration, content| {
-            // Add logging for debugging
-            let result = plugin_clone2.clone().decrypt(schema, migration, content);
-            result
-        }) as Box<dyn Fn(JsValue, JsValue, JsValue) -> Result<JsValue, JsValue>>);
+        let recover_hook = Closure::wrap(Box::new(move |schema, migration, content, fields| {
+            plugin_clone2.clone().recover(schema, migration, content, fields)
+        }) as Box<dyn Fn(JsValue, JsValue, JsValue, JsValue) -> Result<JsValue, JsValue>>);
 
         let mut plugin = plugin;
         plugin.base.doc_create_hook = create_hook.into_js_value();
@@ -127,6 +127,10 @@ impl EncryptionPlugin {
 
         // Only perform encryption if there are actual fields to encrypt
         if has_encrypted_fields {
+            // JSON has no binary type, bytes are serialized as base64 strings
+            let encrypted_obj = map_typed(&schema.properties, &encrypted_obj.into(), PropertyType::Bytes, &|value| {
+                to_bytes(value).map(|bytes| JsValue::from_str(&BASE64.encode(bytes)))
+            })?;
             let serialized = js_sys::JSON::stringify(&encrypted_obj)
                 .map_err(|_| JsValue::from("Failed to stringify encrypted data"))?;
             let mut nonce = [0u8; 12];
@@ -155,6 +159,46 @@ impl EncryptionPlugin {
         Ok(JsValue::from(content_obj.clone()))
     }
     
+    /// Recover hook: decrypts documents unless none of the requested `fields` is encrypted.
+    ///
+    /// Documents older than the schema are decrypted regardless, since the migration that runs
+    /// after this hook may read any of their fields.
+    pub(crate) fn recover(&self, schema_js: JsValue, migration: JsValue, content: JsValue, fields: JsValue) -> Result<JsValue, JsValue> {
+        if js_sys::Array::is_array(&fields) {
+            let schema = Schema::create(schema_js.clone())?;
+            let version = schema.version;
+            let encrypted = schema.encrypted.unwrap_or_default();
+            let needs_decryption = js_sys::Array::from(&fields)
+                .iter()
+                .filter_map(|field| field.as_string())
+                .any(|field| encrypted.contains(&field));
+            if !needs_decryption {
+                let outdated = |document: &JsValue| {
+                    Reflect::get(document, &JsValue::from_str("__version"))
+                        .ok()
+                        .and_then(|doc_version| doc_version.as_f64())
+                        .is_some_and(|doc_version| (doc_version as i32) < version)
+                };
+                if !content.is_array() {
+                    if outdated(&content) {
+                        return self.decrypt_single_document(schema_js, migration, content);
+                    }
+                    return Ok(content);
+                }
+                return js_sys::Array::from(&content)
+                    .iter()
+                    .map(|document| if outdated(&document) {
+                        self.decrypt_single_document(schema_js.clone(), migration.clone(), document)
+                    } else {
+                        Ok(document)
+                    })
+                    .collect::<Result<js_sys::Array, JsValue>>()
+                    .map(JsValue::from);
+            }
+        }
+        self.decrypt(schema_js, migration, content)
+    }
+
     pub(crate) fn decrypt(&self, schema_js: JsValue, migration: JsValue, content: JsValue) -> Result<JsValue, JsValue> {
         
         // Add validation for input parameters
@@ -258,6 +302,10 @@ impl EncryptionPlugin {
         // Parse the decrypted JSON string back into a JS object
         let encrypted_obj = js_sys::JSON::parse(&decrypted_str)
             .map_err(|_| JsValue::from("Failed to parse encrypted data"))?;
+        let encrypted_obj = map_typed(&schema.properties, &encrypted_obj, PropertyType::Bytes, &|value| {
+            let bytes = BASE64.decode(value.as_string()?).ok()?;
+            Some(js_sys::Uint8Array::from(bytes.as_slice()).into())
+        })?;
     
         // Remove the encrypted field
         Reflect::delete_property(&content_obj, &JsValue::from_str("__encrypted"))?;
@@ -280,6 +328,7 @@ impl EncryptionPlugin {
 mod tests {
     use super::*;
     use js_sys::JSON;
+    use wasm_bindgen::JsCast;
     use wasm_bindgen_test::*;
 
     #[wasm_bindgen_test]
@@ -449,6 +498,37 @@ mod tests {
         );
     }
 
+    #[wasm_bindgen_test]
+    fn test_encryption_bytes() {
+        let schema_js = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "encrypted": ["key", "signatures"],
+            "properties": {
+                "id": {"type": "string"},
+                "key": {"type": "bytes"},
+                "signatures": {"type": "array", "items": [{"type": "bytes"}]}
+            }
+        }"#;
+        let schema_value = JSON::parse(schema_js).unwrap();
+        let content = Object::new();
+        Reflect::set(&content, &JsValue::from_str("id"), &JsValue::from_str("123")).unwrap();
+        Reflect::set(&content, &JsValue::from_str("key"), &js_sys::Uint8Array::from(&[0u8, 1, 254, 255][..])).unwrap();
+        let signatures = js_sys::Array::of1(&js_sys::Uint8Array::from(&[7u8; 64][..]));
+        Reflect::set(&content, &JsValue::from_str("signatures"), &signatures).unwrap();
+
+        let plugin = EncryptionPlugin::new("test_password".to_string()).unwrap();
+        let encrypted = plugin.encrypt(schema_value.clone(), JsValue::NULL, content.into()).unwrap();
+        let decrypted = plugin.decrypt(schema_value, JsValue::NULL, encrypted).unwrap();
+
+        let key = Reflect::get(&decrypted, &JsValue::from_str("key")).unwrap();
+        assert!(key.is_instance_of::<js_sys::Uint8Array>());
+        assert_eq!(to_bytes(&key).unwrap(), vec![0, 1, 254, 255]);
+        let signatures = js_sys::Array::from(&Reflect::get(&decrypted, &JsValue::from_str("signatures")).unwrap());
+        assert_eq!(to_bytes(&signatures.get(0)).unwrap(), vec![7; 64]);
+    }
+
     #[wasm_bindgen_test]
     fn test_invalid_password_decryption() {
         let schema_js = r#"{
@@ -542,4 +622,68 @@ mod tests {
         let result = plugin.encrypt(schema_value, JsValue::NULL, content);
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[wasm_bindgen_test]
+    fn test_recover_skips_decryption_for_unencrypted_fields() {
+        let schema_js = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "encrypted": ["secret"],
+            "properties": {
+                "id": {"type": "string"},
+                "name": {"type": "string"},
+                "secret": {"type": "string"}
+            }
+        }"#;
+        let schema_value = JSON::parse(schema_js).unwrap();
+        let content = JSON::parse(r#"{"id": "123", "name": "John", "secret": "sensitive data"}"#).unwrap();
+
+        let plugin = EncryptionPlugin::new("test_password".to_string()).unwrap();
+        let encrypted = plugin.encrypt(schema_value.clone(), JsValue::NULL, content).unwrap();
+
+        // Only unencrypted fields requested, the ciphertext is left alone
+        let fields = js_sys::Array::of2(&JsValue::from_str("id"), &JsValue::from_str("name"));
+        let recovered = plugin.recover(schema_value.clone(), JsValue::NULL, encrypted.clone(), fields.into()).unwrap();
+        assert!(Reflect::get(&recovered, &JsValue::from_str("secret")).unwrap().is_undefined());
+        assert!(!Reflect::get(&recovered, &JsValue::from_str("__encrypted")).unwrap().is_undefined());
+
+        let fields = js_sys::Array::of1(&JsValue::from_str("secret"));
+        let recovered = plugin.recover(schema_value, JsValue::NULL, encrypted, fields.into()).unwrap();
+        let secret = Reflect::get(&recovered, &JsValue::from_str("secret")).unwrap();
+        assert_eq!(secret.as_string().unwrap(), "sensitive data");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_recover_decrypts_outdated_documents() {
+        let schema_js = r#"{
+            "version": 2,
+            "primaryKey": "id",
+            "type": "object",
+            "encrypted": ["secret"],
+            "properties": {
+                "id": {"type": "string"},
+                "name": {"type": "string"},
+                "secret": {"type": "string"}
+            }
+        }"#;
+        let schema_value = JSON::parse(schema_js).unwrap();
+        let plugin = EncryptionPlugin::new("test_password".to_string()).unwrap();
+        let outdated = JSON::parse(r#"{"id": "1", "name": "John", "secret": "old", "__version": 1}"#).unwrap();
+        let current = JSON::parse(r#"{"id": "2", "name": "Jane", "secret": "new", "__version": 2}"#).unwrap();
+        let documents = js_sys::Array::of2(
+            &plugin.encrypt(schema_value.clone(), JsValue::NULL, outdated).unwrap(),
+            &plugin.encrypt(schema_value.clone(), JsValue::NULL, current).unwrap(),
+        );
+
+        // The migration of the outdated document may read its secret, the current one is left alone
+        let fields = js_sys::Array::of1(&JsValue::from_str("name"));
+        let recovered = js_sys::Array::from(
+            &plugin.recover(schema_value, JsValue::NULL, documents.into(), fields.into()).unwrap()
+        );
+        let secret = Reflect::get(&recovered.get(0), &JsValue::from_str("secret")).unwrap();
+        assert_eq!(secret.as_string().unwrap(), "old");
+        assert!(Reflect::get(&recovered.get(1), &JsValue::from_str("secret")).unwrap().is_undefined());
+        assert!(!Reflect::get(&recovered.get(1), &JsValue::from_str("__encrypted")).unwrap().is_undefined());
+    }
+}
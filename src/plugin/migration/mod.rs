@@ -2,7 +2,7 @@ use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen::prelude::wasm_bindgen;
 use crate::plugin::BasePlugin;
-use crate::schema::Schema;
+use crate::schema::{apply_defaults, Schema};
 use js_sys::Reflect;
 
 #[wasm_bindgen(typescript_custom_section)]
@@ -182,6 +182,10 @@ impl MigrationPlugin {
 
                 content = upgraded;
             }
+
+            // Generated defaults are left out, as recovered documents are not written back and
+            // would get a new value every time they are read
+            content = apply_defaults(&schema.properties, &content, false)?;
         }
 
         Ok(content)
@@ -735,5 +739,36 @@ mod tests {
                  assert!(result.is_err());
              }
 
+    #[wasm_bindgen_test]
+    fn test_migration_applies_defaults() {
+        let schema_js = r#"{
+            "version": 2,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "status": {"type": "string", "default": "draft"},
+                "createdAt": {"type": "string", "default": {"$generate": "now"}}
+            }
+        }"#;
+        let schema_value = JSON::parse(schema_js).unwrap();
+        let migrations = Object::new().into_js_result().unwrap();
+        let migration_fn = js_sys::Function::new_with_args("doc", "return doc;");
+        Reflect::set(&migrations, &JsValue::from(2), &migration_fn).unwrap();
+
+        let plugin = MigrationPlugin::new().unwrap();
+        let content = JSON::parse(r#"{"id": "123", "__version": 1}"#).unwrap();
+        let migrated = plugin.recover_hook(schema_value.clone(), migrations.clone(), content).unwrap();
+        assert_eq!(
+            Reflect::get(&migrated, &JsValue::from_str("status")).unwrap().as_string().unwrap(),
+            "draft"
+        );
+        // Generated values would change on every read
+        assert!(Reflect::get(&migrated, &JsValue::from_str("createdAt")).unwrap().is_undefined());
 
-}
\ No newline at end of file
+        // Up to date documents are left as they are
+        let content = JSON::parse(r#"{"id": "123", "__version": 2}"#).unwrap();
+        let recovered = plugin.recover_hook(schema_value, migrations, content).unwrap();
+        assert!(Reflect::get(&recovered, &JsValue::from_str("status")).unwrap().is_undefined());
+    }
+}
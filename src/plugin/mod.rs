@@ -10,7 +10,11 @@ const TS_APPEND_CONTENT: &'static str = r#"
 type Hook = (
     schema: Schema<SchemaType>,
     migration: MigrationPathsForSchema<SchemaType>,
-    doc: Doc<SchemaType>
+    doc: Doc<SchemaType>,
+    /**
+     * Top-level fields the caller needs, undefined when the whole document is needed.
+     */
+    fields?: string[]
 ) => Doc<SchemaType>
 
 type BasePluginOptions = {
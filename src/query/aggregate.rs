@@ -0,0 +1,332 @@
+use js_sys::{Array, Object, Reflect, JSON};
+use wasm_bindgen::JsValue;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen_test::wasm_bindgen_test;
+use crate::query::options::SortKey;
+use crate::schema::{resolve_property_path, Schema};
+use crate::schema::property_type::PropertyType;
+use crate::storage::internals::core::CoreStorage;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Property paths accepted by aggregations.
+ */
+export type AggregateField<T extends SchemaType> = Extract<keyof T['properties'], string> | `${string}.${string}`;
+
+/**
+ * A single accumulator: `$count` counts the documents of a group, `$sum` and `$avg` take a number
+ * property, `$min` and `$max` a number, string, boolean or date property, dates comparing as instants.
+ */
+export type Accumulator<T extends SchemaType> =
+    { $count: true } |
+    { $sum: AggregateField<T> } |
+    { $avg: AggregateField<T> } |
+    { $min: AggregateField<T> } |
+    { $max: AggregateField<T> };
+
+/**
+ * Options of an aggregation.
+ */
+export type AggregateOptions<T extends SchemaType> = {
+    /**
+     * Properties to group documents by, all documents form a single group when omitted.
+     */
+    groupBy?: AggregateField<T> | AggregateField<T>[];
+    /**
+     * Accumulators computed for each group, by output name.
+     */
+    accumulators: Record<string, Accumulator<T>>;
+};
+
+/**
+ * One row per group, holding the groupBy values and the accumulator results.
+ */
+export type AggregateResult = Record<string, unknown>;
+"#;
+
+/// The operation performed by an accumulator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccumulatorKind {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// An accumulator, resolved against the schema.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    /// The name of the accumulator in the result rows.
+    pub(crate) name: String,
+    /// The operation performed.
+    pub(crate) kind: AccumulatorKind,
+    /// The accumulated property, `None` for `$count`.
+    pub(crate) field: Option<SortKey>,
+}
+
+#[derive(Debug, Clone)]
+/// Represents the options of an aggregation, validated against the collection schema.
+pub struct Aggregation {
+    /// The properties documents are grouped by.
+    pub(crate) group_by: Vec<SortKey>,
+    /// The accumulators computed for each group.
+    pub(crate) accumulators: Vec<Accumulator>,
+}
+
+impl Aggregation {
+    /// Parses and validates aggregation options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `{ groupBy, accumulators }` object.
+    /// * `schema` - The schema of the aggregated collection.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Aggregation, JsValue>` - The validated aggregation or an error.
+    pub fn new(options: &JsValue, schema: &Schema) -> Result<Aggregation, JsValue> {
+        if !options.is_object() {
+            return Err(JsValue::from_str("Aggregate options must be an object"));
+        }
+        let group_by = Reflect::get(options, &JsValue::from_str("groupBy"))?;
+        let group_paths = if group_by.is_undefined() || group_by.is_null() {
+            Vec::new()
+        } else if let Some(path) = group_by.as_string() {
+            vec![path]
+        } else if Array::is_array(&group_by) {
+            Array::from(&group_by)
+                .iter()
+                .map(|path| path.as_string().ok_or_else(|| JsValue::from_str("groupBy must hold property names")))
+                .collect::<Result<Vec<String>, JsValue>>()?
+        } else {
+            return Err(JsValue::from_str("groupBy must be a property name or an array of property names"));
+        };
+        let group_by = group_paths
+            .into_iter()
+            .map(|path| Self::field(schema, path, "groupBy"))
+            .collect::<Result<Vec<SortKey>, JsValue>>()?;
+
+        let accumulators_js = Reflect::get(options, &JsValue::from_str("accumulators"))?;
+        if !accumulators_js.is_object() || Array::is_array(&accumulators_js) {
+            return Err(JsValue::from_str("accumulators must be an object"));
+        }
+        let names = Object::keys(&Object::from(accumulators_js.clone()));
+        if names.length() == 0 {
+            return Err(JsValue::from_str("At least one accumulator is required"));
+        }
+        let mut accumulators = Vec::new();
+        for i in 0..names.length() {
+            let name = names.get(i).as_string().unwrap_or_default();
+            if group_by.iter().any(|key| key.path == name) {
+                return Err(JsValue::from_str(&format!("Accumulator {} collides with a groupBy property", name)));
+            }
+            let spec = Reflect::get(&accumulators_js, &JsValue::from_str(&name))?;
+            accumulators.push(Self::accumulator(schema, name, &spec)?);
+        }
+        Ok(Aggregation { group_by, accumulators })
+    }
+
+    /// Returns the top-level fields the aggregation reads.
+    pub(crate) fn required_fields(&self) -> Vec<String> {
+        let mut fields: Vec<String> = Vec::new();
+        let paths = self.group_by
+            .iter()
+            .chain(self.accumulators.iter().filter_map(|accumulator| accumulator.field.as_ref()))
+            .map(|key| key.path.split('.').next().unwrap_or_default().to_string());
+        for path in paths {
+            if !fields.contains(&path) {
+                fields.push(path);
+            }
+        }
+        fields
+    }
+
+    fn accumulator(schema: &Schema, name: String, spec: &JsValue) -> Result<Accumulator, JsValue> {
+        let invalid = || JsValue::from_str(&format!("Accumulator {} must hold exactly one of $count, $sum, $avg, $min or $max", name));
+        if !spec.is_object() {
+            return Err(invalid());
+        }
+        let keys = Object::keys(&Object::from(spec.clone()));
+        if keys.length() != 1 {
+            return Err(invalid());
+        }
+        let operator = keys.get(0).as_string().unwrap_or_default();
+        let value = Reflect::get(spec, &JsValue::from_str(&operator))?;
+        let kind = match operator.as_str() {
+            "$count" => AccumulatorKind::Count,
+            "$sum" => AccumulatorKind::Sum,
+            "$avg" => AccumulatorKind::Avg,
+            "$min" => AccumulatorKind::Min,
+            "$max" => AccumulatorKind::Max,
+            _ => return Err(invalid()),
+        };
+        if kind == AccumulatorKind::Count {
+            return Ok(Accumulator { name, kind, field: None });
+        }
+        let path = value
+            .as_string()
+            .ok_or_else(|| JsValue::from_str(&format!("{} accumulator requires a property name", operator)))?;
+        let field = Self::field(schema, path, &operator)?;
+        if matches!(kind, AccumulatorKind::Sum | AccumulatorKind::Avg) && !field.property_type.is_numeric() {
+            return Err(JsValue::from_str(&format!(
+                "{} accumulator requires a number property, {} is {}",
+                operator, field.path, field.property_type
+            )));
+        }
+        Ok(Accumulator { name, kind, field: Some(field) })
+    }
+
+    fn field(schema: &Schema, path: String, usage: &str) -> Result<SortKey, JsValue> {
+        let property = resolve_property_path(&schema.properties, &path)
+            .ok_or_else(|| JsValue::from_str(&format!("Invalid {} property: {}", usage, path)))?;
+        // Encrypted values are stored as ciphertext, storages cannot read them
+        let root = path.split('.').next().unwrap_or_default();
+        if schema.encrypted.as_ref().is_some_and(|encrypted| encrypted.iter().any(|field| field == root)) {
+            return Err(JsValue::from_str(&format!("Cannot aggregate on encrypted property: {}", path)));
+        }
+        match property.property_type {
+            PropertyType::String | PropertyType::Number | PropertyType::Integer | PropertyType::Boolean | PropertyType::Date => {}
+            _ => return Err(JsValue::from_str(&format!(
+                "Cannot aggregate on property {} of type {}",
+                path, property.property_type
+            ))),
+        }
+        Ok(SortKey {
+            path,
+            descending: false,
+            property_type: property.property_type,
+            date: property.is_date(),
+        })
+    }
+}
+
+
+#[wasm_bindgen_test]
+fn test_aggregation_parse() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "category": { "type": "string" },
+            "price": { "type": "number" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let options = JSON::parse(r#"{
+        "groupBy": "category",
+        "accumulators": {
+            "count": { "$count": true },
+            "total": { "$sum": "price" },
+            "cheapest": { "$min": "price" }
+        }
+    }"#).unwrap();
+    let aggregation = Aggregation::new(&options, &schema).unwrap();
+    assert_eq!(aggregation.group_by.len(), 1);
+    assert_eq!(aggregation.accumulators.len(), 3);
+    assert_eq!(aggregation.accumulators[1].kind, AccumulatorKind::Sum);
+    assert_eq!(aggregation.required_fields(), vec!["category", "price"]);
+}
+
+#[wasm_bindgen_test]
+fn test_aggregation_numeric_accumulator_on_string_property() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "category": { "type": "string" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let options = JSON::parse(r#"{
+        "accumulators": { "average": { "$avg": "category" } }
+    }"#).unwrap();
+    let result = Aggregation::new(&options, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "$avg accumulator requires a number property, category is string"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_aggregation_groups_documents() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "category": { "type": "string" },
+            "price": { "type": "number" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let options = JSON::parse(r#"{
+        "groupBy": ["category"],
+        "accumulators": {
+            "count": { "$count": true },
+            "average": { "$avg": "price" },
+            "priciest": { "$max": "price" }
+        }
+    }"#).unwrap();
+    let aggregation = Aggregation::new(&options, &schema).unwrap();
+    let documents: Vec<JsValue> = [
+        r#"{"id": "1", "category": "fruit", "price": 2}"#,
+        r#"{"id": "2", "category": "bread", "price": 3}"#,
+        r#"{"id": "3", "category": "fruit", "price": 4}"#,
+        r#"{"id": "4", "category": "fruit"}"#,
+        r#"{"id": "5", "price": 1}"#,
+    ].iter().map(|document| JSON::parse(document).unwrap()).collect();
+
+    let rows = CoreStorage {}.aggregate(&documents, &aggregation).unwrap();
+    assert_eq!(
+        JSON::stringify(&rows).unwrap().as_string().unwrap(),
+        concat!(
+            r#"[{"category":null,"count":1,"average":1,"priciest":1},"#,
+            r#"{"category":"bread","count":1,"average":3,"priciest":3},"#,
+            r#"{"category":"fruit","count":3,"average":3,"priciest":4}]"#
+        )
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_aggregation_compares_dates_as_instants() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "day": { "type": "string", "format": "date-time" },
+            "createdAt": { "type": "string", "format": "date-time" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let options = JSON::parse(r#"{
+        "groupBy": "day",
+        "accumulators": {
+            "first": { "$min": "createdAt" },
+            "last": { "$max": "createdAt" }
+        }
+    }"#).unwrap();
+    let aggregation = Aggregation::new(&options, &schema).unwrap();
+    let documents: Vec<JsValue> = [
+        r#"{"id": "1", "day": "2024-01-01T00:00:00Z", "createdAt": "2024-01-01T09:00:00Z"}"#,
+        r#"{"id": "2", "day": "2024-01-01T02:00:00+02:00", "createdAt": "2024-01-01T10:00:00+02:00"}"#,
+        r#"{"id": "3", "day": "2024-01-01T00:00:00Z", "createdAt": "2024-01-01T09:30:00Z"}"#,
+    ].iter().map(|document| JSON::parse(document).unwrap()).collect();
+
+    let rows = CoreStorage {}.aggregate(&documents, &aggregation).unwrap();
+    assert_eq!(
+        JSON::stringify(&rows).unwrap().as_string().unwrap(),
+        concat!(
+            r#"[{"day":"2024-01-01T00:00:00.000Z","#,
+            r#""first":"2024-01-01T10:00:00+02:00","last":"2024-01-01T09:30:00Z"}]"#
+        )
+    );
+}
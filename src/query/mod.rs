@@ -1,32 +1,92 @@
-use std::collections::HashMap;
+pub mod aggregate;
+pub mod options;
+pub mod plan;
+pub mod predicate;
+pub mod search;
+
 use js_sys::{Array,  Object, JSON};
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen_test::wasm_bindgen_test;
-use crate::schema::Schema;
+use std::collections::HashMap;
+use crate::schema::{full_text_paths, resolve_property_path, Schema};
+use crate::storage::internals::fulltext::tokenize;
+use crate::storage::internals::vector::{to_vector, Metric};
+use crate::query::predicate::{NearSearch, Predicate};
+use crate::schema::property::Property;
+use crate::schema::property_type::PropertyType;
+use crate::utils::{build_regex, is_bytes, parse_date, to_iso_date};
 use js_sys::Reflect;
 
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT: &'static str = r#"
-export type Operators = {
-    $gte?: number,
-    $gt?: number
-    $lt?: number,
-    $lte?: number
+/**
+ * Range operators, comparing numbers numerically, strings lexicographically and dates, given
+ * as `Date` objects or ISO strings, as instants.
+ */
+export type Operators<T = number> = {
+    $gte?: T,
+    $gt?: T
+    $lt?: T,
+    $lte?: T
+};
+export type InOperator<T> = {  $in?: (T | null)[] };
+export type NInOperator<T> = {  $nin?: (T | null)[] };
+export type NotEqualOperator<T> = { $ne?: T | null };
+export type ExistsOperator = { $exists?: boolean };
+export type StringOperators = {
+    $regex?: string,
+    $startsWith?: string,
+    $contains?: string,
+    $options?: string
+};
+export type ArrayOperators = {
+    $all?: any[],
+    $size?: number,
+    $elemMatch?: any
+};
+export type ElementType<T> = T extends Array<infer U> ? U : never;
+export type FieldOperators<T> = (T extends number ? Operators<T> : {}) & (T extends string ? Operators<T> & StringOperators : {}) & (T extends Date ? Operators<Date | string> : {}) & (T extends any[] ? ArrayOperators : {}) & InOperator<T | ElementType<T>> & NInOperator<T | ElementType<T>> & NotEqualOperator<T | ElementType<T>> & ExistsOperator;
+export type NotOperator<T> = { $not?: FieldOperators<T> };
+export type OperatorOrType<T> = T | ElementType<T> | null | (FieldOperators<T> & NotOperator<T>) | (T extends number[] ? NearOperator : never);
+export type NestedPathQuery = {
+    [path: `${string}.${string}`]: OperatorOrType<any>
 };
-export type InOperator<T> = {  $in?: T[] };
-export type OperatorOrType<T> = T extends number ? T | Operators | InOperator<T> : T | InOperator<T>;
 export type LogicalOperators<T extends SchemaType> = {
     $and?: Partial<QueryType<T>>[];
     $or?: Partial<QueryType<T>>[];
+    $nor?: Partial<QueryType<T>>[];
+};
+/**
+ * Searches the full-text indexed properties for the terms of `$search`. Documents match when
+ * they hold any of the terms, or every one of them with `$operator: 'and'`, and come back most
+ * relevant first unless a sort is given. Only supported at the top level of a query.
+ */
+export type TextOperator = {
+    $text?: {
+        $search: string,
+        $operator?: 'and' | 'or'
+    }
+};
+/**
+ * Keeps the `$k` documents whose vector is nearest to `$vector`, nearest first unless a sort is
+ * given. Distances are measured with `$metric`, `cosine` by default. Only supported on vector
+ * properties, at the top level of a query and once per query.
+ */
+export type NearOperator = {
+    $near?: {
+        $vector: number[],
+        $k: number,
+        $metric?: 'cosine' | 'dot' | 'euclidean'
+    }
 };
 export type QueryType<T extends SchemaType> = Partial<{
     [K in keyof T['properties']]: OperatorOrType<
-        ExtractType<
-            T['properties'][K]['type']
+        PropertyValue<
+            T['properties'][K]
         >
     >
-}> & LogicalOperators<T> | LogicalOperators<T>[];
+}> & NestedPathQuery & LogicalOperators<T> & TextOperator | LogicalOperators<T>[];
 export class Query<T extends SchemaType> {
     readonly query: QueryType<T>
 }
@@ -68,7 +128,7 @@ impl Query {
             let key = keys.get(i).as_string().unwrap_or_default();
             let value = Reflect::get(query, &JsValue::from_str(&key))?;
 
-            if key == "$and" || key == "$or" {
+            if key == "$and" || key == "$or" || key == "$nor" {
                 // Process the logical operator recursively
                 if !Array::is_array(&value) {
                     return Err(JsValue::from_str(&format!("{} must be an array", key)));
@@ -108,48 +168,26 @@ impl Query {
     }
 
     pub fn parse(&self) -> Result<JsValue, JsValue> {
-        self.process_query(&self.query)
+        self.process_query(&self.query, &self.schema.properties, true)
     }
 
-    fn extract_properties(&self, properties_jsvalue: &JsValue) -> Result<HashMap<String, String>, JsValue> {
-        if !properties_jsvalue.is_object() {
-            return Err(JsValue::from_str("Properties is not an object"));
-        }
-        let mut properties = HashMap::new();
-        let keys = Object::keys(&Object::from(properties_jsvalue.clone()));
-
-        for key in keys {
-            let value = Reflect::get(properties_jsvalue, &key)?;
-            if !value.is_object() {
-                return Err(JsValue::from_str(&format!("Property '{}' is not an object", key.as_string().unwrap())));
-            }
-            let prop_type = Reflect::get(&value, &JsValue::from_str("type"))?;
-            if prop_type.is_string() {
-                properties.insert(key.as_string().unwrap(), prop_type.as_string().unwrap());
-            } else {
-                return Err(JsValue::from_str(&format!("Property '{}' does not have a 'type' field", key.as_string().unwrap())));
-            }
-        }
-        Ok(properties)
+    /// Validates the query and compiles it into the predicate tree evaluated by storages.
+    pub(crate) fn compile(&self) -> Result<Predicate, JsValue> {
+        Predicate::compile(&self.parse()?, &self.schema.properties)
     }
 
-    fn process_query(&self, query: &JsValue) -> Result<JsValue, JsValue> {
-        // Get properties from the schema
-        let properties_jsvalue = self.schema.get_properties()?;
-
-        // Extract properties and their types from the properties_jsvalue
-        let properties = self.extract_properties(&properties_jsvalue)?;
-
+    fn process_query(&self, query: &JsValue, properties: &HashMap<String, Property>, top_level: bool) -> Result<JsValue, JsValue> {
         if !query.is_object() {
             return Err(JsValue::from_str("Query must be an object"));
         }
         let result = Object::new();
         let keys = Object::keys(&Object::from(query.clone()));
+        let mut near_searches = 0;
 
         for i in 0..keys.length() {
             let key = keys.get(i).as_string().unwrap_or_default();
             let value = Reflect::get(query, &JsValue::from_str(&key))?;
-            if key == "$and" || key == "$or" {
+            if key == "$and" || key == "$or" || key == "$nor" {
                 // Handle logical operators
                 if !Array::is_array(&value) {
                     return Err(JsValue::from_str(&format!("{} must be an array", key)));
@@ -158,63 +196,161 @@ impl Query {
                 let processed_arr = Array::new();
                 for j in 0..arr.length() {
                     let item = arr.get(j);
-                    let processed_item = self.process_query(&item)?;
+                    let processed_item = self.process_query(&item, properties, false)?;
                     processed_arr.push(&processed_item);
                 }
                 Reflect::set(&result, &JsValue::from_str(&key), &processed_arr)?;
+            } else if key == "$text" {
+                // A search applies to the whole document, so it cannot be negated or nested
+                if !top_level {
+                    return Err(JsValue::from_str("$text is only supported at the top level of a query"));
+                }
+                self.validate_text(&value)?;
+                Reflect::set(&result, &JsValue::from_str(&key), &value)?;
+            } else if NearSearch::is_near(&value) {
+                // Neighbours are chosen among every match, so the search cannot be nested either
+                if !top_level {
+                    return Err(JsValue::from_str("$near is only supported at the top level of a query"));
+                }
+                let property = resolve_property_path(properties, &key)
+                    .ok_or_else(|| JsValue::from_str(&format!("Invalid property: {}", key)))?;
+                self.validate_near(&value, property)?;
+                near_searches += 1;
+                Reflect::set(&result, &JsValue::from_str(&key), &value)?;
             } else {
-                // Check if key is a valid property
-                if let Some(property_type) = properties.get(&key) {
+                // Check if key is a valid property, nested properties are addressed with dot paths
+                if let Some(property) = resolve_property_path(properties, &key) {
                     // Process the value
-                    let processed_value = self.process_value(&value, property_type)?;
+                    let processed_value = self.process_value(&value, property)?;
                     Reflect::set(&result, &JsValue::from_str(&key), &processed_value)?;
                 } else {
                     return Err(JsValue::from_str(&format!("Invalid property: {}", key)));
                 }
             }
         }
+        // Both searches order documents their own way
+        if near_searches > 1 {
+            return Err(JsValue::from_str("A query supports a single $near"));
+        }
+        if near_searches == 1 && Reflect::has(&result, &JsValue::from_str("$text"))? {
+            return Err(JsValue::from_str("$near cannot be combined with $text"));
+        }
         Ok(result.into())
     }
 
-    fn process_value(&self, value: &JsValue, property_type: &str) -> Result<JsValue, JsValue> {
-        if value.is_object() && !Array::is_array(value) {
+    fn process_value(&self, value: &JsValue, property: &Property) -> Result<JsValue, JsValue> {
+        let value = &self.normalize_dates(value, property);
+        // Invalid dates are left as `Date` objects, rejected below rather than read as operators
+        if value.is_object() && !Array::is_array(value) && !value.is_instance_of::<js_sys::Date>() && !is_bytes(value) {
             // Value is an object, process operators
             let result = Object::new();
             let keys = Object::keys(&Object::from(value.clone()));
             for i in 0..keys.length() {
                 let key = keys.get(i).as_string().unwrap_or_default();
                 let val = Reflect::get(value, &JsValue::from_str(&key))?;
-                if ["$gte", "$gt", "$lt", "$lte", "$in"].contains(&key.as_str()) {
+                if key == "$not" {
+                    // Negation wraps another operator object for the same property
+                    if !val.is_object() || Array::is_array(&val) {
+                        return Err(JsValue::from_str("$not operator requires an operator object"));
+                    }
+                    let processed = self.process_value(&val, property)?;
+                    Reflect::set(&result, &JsValue::from_str(&key), &processed)?;
+                } else if key == "$elemMatch" {
+                    let processed = self.process_elem_match(&val, property)?;
+                    Reflect::set(&result, &JsValue::from_str(&key), &processed)?;
+                } else if key == "$options" {
+                    self.validate_options(value, &val)?;
+                    Reflect::set(&result, &JsValue::from_str(&key), &val)?;
+                } else if ["$gte", "$gt", "$lt", "$lte", "$in", "$nin", "$ne", "$regex", "$startsWith", "$contains", "$all", "$size", "$exists"].contains(&key.as_str()) {
                     // Validate operator value
-                    self.validate_operator_value(&key, &val, property_type)?;
+                    let val = self.normalize_dates(&val, property);
+                    self.validate_operator_value(&key, &val, property)?;
                     Reflect::set(&result, &JsValue::from_str(&key), &val)?;
                 } else {
                     return Err(JsValue::from_str(&format!("Invalid operator: {}", key)));
                 }
             }
+            // Regular expressions are compiled here so that invalid patterns fail at query time
+            let regex = Reflect::get(&result, &JsValue::from_str("$regex"))?;
+            if let Some(pattern) = regex.as_string() {
+                let flags = Reflect::get(&result, &JsValue::from_str("$options"))?
+                    .as_string()
+                    .unwrap_or_default();
+                build_regex(&pattern, &flags)?;
+            }
             Ok(result.into())
         } else {
             // Direct value, check that it matches the property type
-            self.validate_value(value, property_type)?;
+            self.validate_value(value, property)?;
             Ok(value.clone())
         }
     }
 
-    fn validate_operator_value(&self, operator: &str, value: &JsValue, property_type: &str) -> Result<(), JsValue> {
+    fn validate_operator_value(&self, operator: &str, value: &JsValue, property: &Property) -> Result<(), JsValue> {
         match operator {
-            "$in" => {
+            "$in" | "$nin" => {
                 if !Array::is_array(value) {
                     return Err(JsValue::from_str(&format!("{} operator requires an array", operator)));
                 }
                 let arr = Array::from(value);
                 for i in 0..arr.length() {
                     let item = arr.get(i);
-                    self.validate_value(&item, property_type)?;
+                    self.validate_value(&item, property)?;
                 }
                 Ok(())
             }
             "$gte" | "$gt" | "$lt" | "$lte" => {
-                self.validate_value(value, property_type)
+                // Ranges compare numbers numerically, strings lexicographically or as dates
+                let element = self.element_property(property)?;
+                if !element.property_type.is_numeric() && !matches!(element.property_type, PropertyType::String | PropertyType::Date) {
+                    return Err(JsValue::from_str(&format!("{} operator is only supported on number, string and date properties", operator)));
+                }
+                if value.is_null() || Array::is_array(value) {
+                    return Err(JsValue::from_str(&format!("{} operator requires a number or a string", operator)));
+                }
+                self.validate_value(value, element)?;
+                if let Some(string) = value.as_string() {
+                    if element.is_date() && parse_date(&string).is_none() {
+                        return Err(JsValue::from_str(&format!("{} operator requires a valid date, got {}", operator, string)));
+                    }
+                }
+                Ok(())
+            }
+            "$ne" => {
+                self.validate_value(value, property)
+            }
+            "$regex" | "$startsWith" | "$contains" => {
+                if self.element_property(property)?.property_type != PropertyType::String {
+                    return Err(JsValue::from_str(&format!("{} operator is only supported on string properties", operator)));
+                }
+                if !value.is_string() {
+                    return Err(JsValue::from_str(&format!("{} operator requires a string", operator)));
+                }
+                Ok(())
+            }
+            "$all" => {
+                let item_property = self.items_property(operator, property)?;
+                if !Array::is_array(value) {
+                    return Err(JsValue::from_str("$all operator requires an array"));
+                }
+                let arr = Array::from(value);
+                for i in 0..arr.length() {
+                    self.validate_value(&arr.get(i), item_property)?;
+                }
+                Ok(())
+            }
+            "$exists" => {
+                if value.as_bool().is_none() {
+                    return Err(JsValue::from_str("$exists operator requires a boolean"));
+                }
+                Ok(())
+            }
+            "$size" => {
+                self.items_property(operator, property)?;
+                match value.as_f64() {
+                    Some(size) if size >= 0.0 && size.fract() == 0.0 => Ok(()),
+                    _ => Err(JsValue::from_str("$size operator requires a non-negative integer")),
+                }
             }
             _ => {
                 Err(JsValue::from_str(&format!("Unsupported operator: {}", operator)))
@@ -222,31 +358,237 @@ impl Query {
         }
     }
 
-    fn validate_value(&self, value: &JsValue, property_type: &str) -> Result<(), JsValue> {
-        match property_type {
-            "number" => {
+    /// Validates an `$elemMatch` condition against the array's item definition.
+    ///
+    /// Object items take a nested query over their properties, scalar items an operator object.
+    fn process_elem_match(&self, value: &JsValue, property: &Property) -> Result<JsValue, JsValue> {
+        let item_property = self.items_property("$elemMatch", property)?;
+        if !value.is_object() || Array::is_array(value) {
+            return Err(JsValue::from_str("$elemMatch operator requires an object"));
+        }
+        if item_property.property_type == PropertyType::Object {
+            let item_properties = item_property
+                .properties
+                .as_ref()
+                .ok_or_else(|| JsValue::from_str("Array items are missing their properties"))?;
+            self.process_query(value, item_properties, false)
+        } else {
+            self.process_value(value, item_property)
+        }
+    }
+
+    /// Returns the item definition of an array property, failing for any other property type.
+    fn items_property<'a>(&self, operator: &str, property: &'a Property) -> Result<&'a Property, JsValue> {
+        if property.property_type != PropertyType::Array {
+            return Err(JsValue::from_str(&format!("{} operator is only supported on array properties", operator)));
+        }
+        self.element_property(property)
+    }
+
+    /// Returns the property that scalar conditions are checked against: the item definition for
+    /// arrays, which match when any of their elements does, and the property itself otherwise.
+    fn element_property<'a>(&self, property: &'a Property) -> Result<&'a Property, JsValue> {
+        if property.property_type == PropertyType::Array {
+            property
+                .items
+                .as_ref()
+                .and_then(|items| items.first())
+                .ok_or_else(|| JsValue::from_str("Array property is missing its items definition"))
+        } else {
+            Ok(property)
+        }
+    }
+
+    /// Validates a `$text` search against the full-text indexed properties of the schema.
+    fn validate_text(&self, value: &JsValue) -> Result<(), JsValue> {
+        if !value.is_object() || Array::is_array(value) {
+            return Err(JsValue::from_str("$text operator requires an object"));
+        }
+        for key in Object::keys(&Object::from(value.clone())).iter() {
+            let key = key.as_string().unwrap_or_default();
+            let option = Reflect::get(value, &JsValue::from_str(&key))?;
+            match key.as_str() {
+                "$search" => {
+                    let search = option
+                        .as_string()
+                        .ok_or_else(|| JsValue::from_str("$text $search must be a string"))?;
+                    if tokenize(&search).is_empty() {
+                        return Err(JsValue::from_str("$text $search must contain at least one term"));
+                    }
+                }
+                "$operator" => {
+                    if !matches!(option.as_string().as_deref(), Some("and") | Some("or")) {
+                        return Err(JsValue::from_str("$text $operator must be 'and' or 'or'"));
+                    }
+                }
+                _ => return Err(JsValue::from_str(&format!("Unsupported $text option: {}", key))),
+            }
+        }
+        if !Reflect::has(value, &JsValue::from_str("$search"))? {
+            return Err(JsValue::from_str("$text requires a $search string"));
+        }
+        if full_text_paths(&self.schema.properties).is_empty() {
+            return Err(JsValue::from_str("$text requires a fullText property in the schema"));
+        }
+        Ok(())
+    }
+
+    /// Validates a `$near` search against the vector property it applies to.
+    fn validate_near(&self, condition: &JsValue, property: &Property) -> Result<(), JsValue> {
+        let dimensions = match (property.property_type, property.dimensions) {
+            (PropertyType::Vector, Some(dimensions)) => dimensions as usize,
+            _ => return Err(JsValue::from_str("$near operator is only supported on vector properties")),
+        };
+        if Object::keys(&Object::from(condition.clone())).length() > 1 {
+            return Err(JsValue::from_str("$near cannot be combined with other operators"));
+        }
+        let near = Reflect::get(condition, &JsValue::from_str("$near"))?;
+        if !near.is_object() || Array::is_array(&near) {
+            return Err(JsValue::from_str("$near operator requires an object"));
+        }
+        for key in Object::keys(&Object::from(near.clone())).iter() {
+            let key = key.as_string().unwrap_or_default();
+            let option = Reflect::get(&near, &JsValue::from_str(&key))?;
+            match key.as_str() {
+                "$vector" => {
+                    if to_vector(&option, dimensions).is_none() {
+                        return Err(JsValue::from_str(&format!("$near $vector must be an array of {} numbers", dimensions)));
+                    }
+                }
+                "$k" => {
+                    if !option.as_f64().is_some_and(|k| k >= 1.0 && k.fract() == 0.0) {
+                        return Err(JsValue::from_str("$near $k must be a positive integer"));
+                    }
+                }
+                "$metric" => {
+                    if option.as_string().and_then(|metric| Metric::parse(&metric)).is_none() {
+                        return Err(JsValue::from_str("$near $metric must be 'cosine', 'dot' or 'euclidean'"));
+                    }
+                }
+                _ => return Err(JsValue::from_str(&format!("Unsupported $near option: {}", key))),
+            }
+        }
+        for required in ["$vector", "$k"] {
+            if !Reflect::has(&near, &JsValue::from_str(required))? {
+                return Err(JsValue::from_str(&format!("$near requires a {}", required)));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_options(&self, condition: &JsValue, options: &JsValue) -> Result<(), JsValue> {
+        let has_string_operator = ["$regex", "$startsWith", "$contains"]
+            .iter()
+            .any(|operator| Reflect::has(condition, &JsValue::from_str(operator)).unwrap_or(false));
+        if !has_string_operator {
+            return Err(JsValue::from_str("$options requires a $regex, $startsWith or $contains operator"));
+        }
+        let flags = options
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("$options must be a string"))?;
+        // Stateful flags (g, y) would make repeated matching depend on previous documents
+        if let Some(flag) = flags.chars().find(|flag| !"imsu".contains(*flag)) {
+            return Err(JsValue::from_str(&format!("Unsupported $options flag: {}", flag)));
+        }
+        Ok(())
+    }
+
+    /// Converts the `Date` objects and date strings compared against a date-type property, or
+    /// its array items, into the ISO strings dates are stored as. Other values are kept as is.
+    fn normalize_dates(&self, value: &JsValue, property: &Property) -> JsValue {
+        let element = match property.property_type {
+            PropertyType::Array => property.items.as_ref().and_then(|items| items.first()),
+            _ => Some(property),
+        };
+        if !element.is_some_and(|element| element.accepts(PropertyType::Date)) {
+            return value.clone();
+        }
+        if Array::is_array(value) {
+            return Array::from(value)
+                .iter()
+                .map(|item| self.normalize_dates(&item, property))
+                .collect::<Array>()
+                .into();
+        }
+        to_iso_date(value).map(JsValue::from).unwrap_or_else(|| value.clone())
+    }
+
+    fn validate_value(&self, value: &JsValue, property: &Property) -> Result<(), JsValue> {
+        if value.is_null() {
+            // null matches null and missing values of any type
+            return Ok(());
+        }
+        if let Some(types) = &property.types {
+            // Values of a union type only have to be valid for one of its types
+            let valid = types.iter().any(|property_type| {
+                let member = Property { property_type: *property_type, types: None, ..property.clone() };
+                self.validate_value(value, &member).is_ok()
+            });
+            return if valid {
+                Ok(())
+            } else {
+                Err(JsValue::from_str(&format!("Expected {}", property.type_names().join(" or ").to_lowercase())))
+            };
+        }
+        match property.property_type {
+            PropertyType::Number | PropertyType::Integer => {
                 if value.as_f64().is_some() {
                     Ok(())
                 } else {
                     Err(JsValue::from_str("Expected a number"))
                 }
             }
-            "string" => {
+            PropertyType::String => {
                 if value.is_string() {
                     Ok(())
                 } else {
                     Err(JsValue::from_str("Expected a string"))
                 }
             }
-            "boolean" => {
-                if value.is_truthy() || value.is_falsy() {
+            PropertyType::Boolean => {
+                if value.as_bool().is_some() {
                     Ok(())
                 } else {
                     Err(JsValue::from_str("Expected a boolean"))
                 }
             }
+            PropertyType::Date => {
+                if value.as_string().is_some_and(|string| parse_date(&string).is_some()) {
+                    Ok(())
+                } else {
+                    Err(JsValue::from_str("Expected a date"))
+                }
+            }
+            PropertyType::Bytes => {
+                if is_bytes(value) {
+                    Ok(())
+                } else {
+                    Err(JsValue::from_str("Expected bytes"))
+                }
+            }
+            PropertyType::Array => {
+                // Arrays are compared whole, single values by membership
+                let item_property = self.element_property(property)?;
+                if Array::is_array(value) {
+                    let arr = Array::from(value);
+                    for i in 0..arr.length() {
+                        self.validate_value(&arr.get(i), item_property)?;
+                    }
+                    Ok(())
+                } else {
+                    self.validate_value(value, item_property)
+                }
+            }
+            PropertyType::Vector => {
+                let dimensions = property.dimensions.unwrap_or_default() as usize;
+                if to_vector(value, dimensions).is_some() {
+                    Ok(())
+                } else {
+                    Err(JsValue::from_str(&format!("Expected a vector of {} numbers", dimensions)))
+                }
+            }
             _ => {
-                Err(JsValue::from_str(&format!("Unsupported property type: {}", property_type)))
+                Err(JsValue::from_str(&format!("Unsupported property type: {}", property.property_type)))
             },
         }
     }
@@ -799,3 +1141,613 @@ fn test_query_parse_empty_logical_operators() {
     let result = query.parse();
     assert!(result.is_ok());
 }
+
+#[wasm_bindgen_test]
+fn test_query_parse_negation_operators() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "status": { "type": "string" },
+            "age": { "type": "number" },
+            "country": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "status": { "$nin": ["archived", "deleted"] },
+        "country": { "$ne": "ES" },
+        "age": { "$not": { "$lt": 18 } },
+        "$nor": [
+            { "status": "banned" }
+        ]
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_nin_operator_wrong_type() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "age": { "type": "number" }
+        }
+    }"#;
+    let query_str = r#"{
+        "age": { "$nin": [18, "twenty"] }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Expected a number"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_not_operator_requires_object() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "age": { "type": "number" }
+        }
+    }"#;
+    let query_str = r#"{
+        "age": { "$not": 18 }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "$not operator requires an operator object"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_get_query_normalization_nor_operator() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "status": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "id": "123",
+        "$nor": [
+            { "status": "archived" },
+            { "status": "deleted" }
+        ]
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+
+    let normalized_query = query.get_query().unwrap();
+    let expected_str = r#"{
+        "$and": [
+            { "id": "123" },
+            {
+                "$nor": [
+                    { "status": "archived" },
+                    { "status": "deleted" }
+                ]
+            }
+        ]
+    }"#;
+    let expected_value = JSON::parse(expected_str).unwrap();
+
+    assert_eq!(
+        JSON::stringify(&normalized_query).unwrap(),
+        JSON::stringify(&expected_value).unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_string_operators() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "email": { "type": "string" },
+            "did": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "name": { "$startsWith": "jo", "$options": "i" },
+        "email": { "$contains": "@example" },
+        "did": { "$regex": "^did:prism:[a-f0-9]+$" }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_string_operator_on_number_property() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "age": { "type": "number" }
+        }
+    }"#;
+    let query_str = r#"{
+        "age": { "$startsWith": "3" }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "$startsWith operator is only supported on string properties"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_invalid_regex() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "name": { "$regex": "([a-z" }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Invalid regular expression: /([a-z/"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_options_without_string_operator() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "name": { "$ne": "john", "$options": "i" }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "$options requires a $regex, $startsWith or $contains operator"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_nested_paths() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "address": {
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" },
+                    "zip": { "type": "number" }
+                }
+            },
+            "tags": {
+                "type": "array",
+                "items": [{
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" }
+                    }
+                }]
+            }
+        }
+    }"#;
+    let query_str = r#"{
+        "address.city": "Madrid",
+        "address.zip": { "$gte": 28000 },
+        "tags.name": { "$in": ["admin", "staff"] },
+        "tags.0.name": { "$startsWith": "ad" }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_invalid_nested_path() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "address": {
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" }
+                }
+            }
+        }
+    }"#;
+    let query_str = r#"{
+        "address.country": "Spain"
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Invalid property: address.country"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_array_operators() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "tags": {
+                "type": "array",
+                "items": [{ "type": "string" }]
+            },
+            "roles": {
+                "type": "array",
+                "items": [{
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "level": { "type": "number" }
+                    }
+                }]
+            },
+            "scores": {
+                "type": "array",
+                "items": [{ "type": "number" }]
+            }
+        }
+    }"#;
+    let query_str = r#"{
+        "tags": "admin",
+        "$or": [
+            { "tags": { "$all": ["admin", "staff"] } },
+            { "tags": { "$size": 0 } }
+        ],
+        "roles": { "$elemMatch": { "name": "owner", "level": { "$gte": 2 } } },
+        "scores": { "$elemMatch": { "$gt": 80, "$lt": 90 } }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_array_operator_on_scalar_property() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "name": { "$size": 2 }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "$size operator is only supported on array properties"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_elem_match_invalid_item_property() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "roles": {
+                "type": "array",
+                "items": [{
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" }
+                    }
+                }]
+            }
+        }
+    }"#;
+    let query_str = r#"{
+        "roles": { "$elemMatch": { "title": "owner" } }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Invalid property: title"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_exists_and_null() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "age": { "type": "number" }
+        }
+    }"#;
+    let query_str = r#"{
+        "name": { "$exists": true, "$ne": null },
+        "age": { "$in": [null, 30] }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_exists_requires_boolean() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" }
+        }
+    }"#;
+    let query_str = r#"{
+        "name": { "$exists": "yes" }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    let result = query.parse();
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "$exists operator requires a boolean"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_string_ranges() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "createdAt": { "type": "string", "format": "date-time" },
+            "active": { "type": "boolean" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+
+    let query_str = r#"{
+        "id": { "$gte": "a", "$lt": "m" },
+        "createdAt": { "$gt": "2024-01-01T00:00:00Z" }
+    }"#;
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema.clone()).unwrap();
+    assert!(query.parse().is_ok());
+
+    let query_str = r#"{ "createdAt": { "$gt": "yesterday" } }"#;
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema.clone()).unwrap();
+    assert_eq!(
+        query.parse().err().unwrap().as_string().unwrap(),
+        "$gt operator requires a valid date, got yesterday"
+    );
+
+    let query_str = r#"{ "active": { "$lt": true } }"#;
+    let query = Query::new(JSON::parse(query_str).unwrap(), schema).unwrap();
+    assert_eq!(
+        query.parse().err().unwrap().as_string().unwrap(),
+        "$lt operator is only supported on number, string and date properties"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_dates() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "createdAt": { "type": "date" },
+            "holidays": { "type": "array", "items": [{ "type": "date" }] }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let date = |iso: &str| JsValue::from(js_sys::Date::new(&JsValue::from_str(iso)));
+    let get = |value: &JsValue, key: &str| Reflect::get(value, &JsValue::from_str(key)).unwrap();
+
+    // Dates and date strings are compared as the ISO strings dates are stored as
+    let query = Object::new();
+    let range = Object::new();
+    Reflect::set(&range, &JsValue::from_str("$gte"), &date("2024-01-01T00:00:00Z")).unwrap();
+    Reflect::set(&range, &JsValue::from_str("$lt"), &JsValue::from_str("2024-02-01")).unwrap();
+    Reflect::set(&query, &JsValue::from_str("createdAt"), &range).unwrap();
+    Reflect::set(&query, &JsValue::from_str("holidays"), &date("2024-12-25T00:00:00Z")).unwrap();
+    let parsed = Query::new(query.into(), schema.clone()).unwrap().parse().unwrap();
+    let range = get(&parsed, "createdAt");
+    assert_eq!(get(&range, "$gte").as_string().unwrap(), "2024-01-01T00:00:00.000Z");
+    assert_eq!(get(&range, "$lt").as_string().unwrap(), "2024-02-01T00:00:00.000Z");
+    assert_eq!(get(&parsed, "holidays").as_string().unwrap(), "2024-12-25T00:00:00.000Z");
+
+    let errors = [
+        (r#"{ "createdAt": "yesterday" }"#, "Expected a date"),
+        (r#"{ "createdAt": { "$in": ["2024-01-01", 5] } }"#, "Expected a date"),
+        (r#"{ "createdAt": { "$regex": "^2024" } }"#, "$regex operator is only supported on string properties"),
+    ];
+    for (query_str, message) in errors {
+        let query = Query::new(JSON::parse(query_str).unwrap(), schema.clone()).unwrap();
+        assert_eq!(query.parse().err().unwrap().as_string().unwrap(), message);
+    }
+    let query = Object::new();
+    Reflect::set(&query, &JsValue::from_str("createdAt"), &date("not a date")).unwrap();
+    let query = Query::new(query.into(), schema).unwrap();
+    assert_eq!(query.parse().err().unwrap().as_string().unwrap(), "Expected a date");
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_text() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "title": { "type": "string", "fullText": true },
+            "views": { "type": "number" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let parse = |query_str: &str| Query::new(JSON::parse(query_str).unwrap(), schema.clone()).unwrap().parse();
+
+    assert!(parse(r#"{ "$text": { "$search": "rust notes", "$operator": "and" }, "views": { "$gt": 1 } }"#).is_ok());
+
+    let errors = [
+        (r#"{ "$or": [{ "$text": { "$search": "rust" } }] }"#, "$text is only supported at the top level of a query"),
+        (r#"{ "$text": "rust" }"#, "$text operator requires an object"),
+        (r#"{ "$text": { "$operator": "and" } }"#, "$text requires a $search string"),
+        (r#"{ "$text": { "$search": " - " } }"#, "$text $search must contain at least one term"),
+        (r#"{ "$text": { "$search": "rust", "$operator": "xor" } }"#, "$text $operator must be 'and' or 'or'"),
+        (r#"{ "$text": { "$search": "rust", "$language": "en" } }"#, "Unsupported $text option: $language"),
+    ];
+    for (query_str, message) in errors {
+        assert_eq!(parse(query_str).err().unwrap().as_string().unwrap(), message);
+    }
+
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": { "id": { "type": "string" } }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let query = Query::new(JSON::parse(r#"{ "$text": { "$search": "rust" } }"#).unwrap(), schema).unwrap();
+    assert_eq!(
+        query.parse().err().unwrap().as_string().unwrap(),
+        "$text requires a fullText property in the schema"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_parse_near() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "title": { "type": "string", "fullText": true },
+            "embedding": { "type": "vector", "dimensions": 2 },
+            "thumbnail": { "type": "vector", "dimensions": 2 }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let parse = |query_str: &str| Query::new(JSON::parse(query_str).unwrap(), schema.clone()).unwrap().parse();
+
+    assert!(parse(r#"{ "embedding": { "$near": { "$vector": [0.5, 1], "$k": 3, "$metric": "dot" } }, "id": { "$ne": "a" } }"#).is_ok());
+    assert!(parse(r#"{ "embedding": [0.5, 1] }"#).is_ok());
+
+    let errors = [
+        (r#"{ "$or": [{ "embedding": { "$near": { "$vector": [0, 1], "$k": 1 } } }] }"#, "$near is only supported at the top level of a query"),
+        (r#"{ "id": { "$near": { "$vector": [0, 1], "$k": 1 } } }"#, "$near operator is only supported on vector properties"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1], "$k": 1 }, "$exists": true } }"#, "$near cannot be combined with other operators"),
+        (r#"{ "embedding": { "$near": [0, 1] } }"#, "$near operator requires an object"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1, 2], "$k": 1 } } }"#, "$near $vector must be an array of 2 numbers"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1], "$k": 0 } } }"#, "$near $k must be a positive integer"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1], "$k": 1, "$metric": "manhattan" } } }"#, "$near $metric must be 'cosine', 'dot' or 'euclidean'"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1], "$k": 1, "$radius": 2 } } }"#, "Unsupported $near option: $radius"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1] } } }"#, "$near requires a $k"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1], "$k": 1 } }, "thumbnail": { "$near": { "$vector": [0, 1], "$k": 1 } } }"#, "A query supports a single $near"),
+        (r#"{ "embedding": { "$near": { "$vector": [0, 1], "$k": 1 } }, "$text": { "$search": "rust" } }"#, "$near cannot be combined with $text"),
+        (r#"{ "embedding": ["a", "b"] }"#, "Expected a vector of 2 numbers"),
+    ];
+    for (query_str, message) in errors {
+        assert_eq!(parse(query_str).err().unwrap().as_string().unwrap(), message);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_query_match_bytes() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "publicKey": { "type": "bytes" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let bytes = |bytes: &[u8]| JsValue::from(js_sys::Uint8Array::from(bytes));
+    let document = |key: &[u8]| {
+        let document = Object::new();
+        Reflect::set(&document, &JsValue::from_str("publicKey"), &bytes(key)).unwrap();
+        JsValue::from(document)
+    };
+    let storage = crate::storage::internals::core::CoreStorage {};
+
+    // Bytes are compared by content, whether given as a Uint8Array or an ArrayBuffer
+    let query = Object::new();
+    Reflect::set(&query, &JsValue::from_str("publicKey"), &js_sys::Uint8Array::from(&[1u8, 2, 3][..]).buffer()).unwrap();
+    let predicate = Query::new(query.into(), schema.clone()).unwrap().compile().unwrap();
+    assert!(storage.matches(&document(&[1, 2, 3]), &predicate).unwrap());
+    assert!(!storage.matches(&document(&[1, 2]), &predicate).unwrap());
+
+    let query = Object::new();
+    let operators = Object::new();
+    Reflect::set(&operators, &JsValue::from_str("$in"), &Array::of2(&bytes(&[4]), &bytes(&[5, 6]))).unwrap();
+    Reflect::set(&query, &JsValue::from_str("publicKey"), &operators).unwrap();
+    let predicate = Query::new(query.into(), schema.clone()).unwrap().compile().unwrap();
+    assert!(storage.matches(&document(&[5, 6]), &predicate).unwrap());
+    assert!(!storage.matches(&document(&[1, 2, 3]), &predicate).unwrap());
+
+    let query = Query::new(JSON::parse(r#"{ "publicKey": "AQID" }"#).unwrap(), schema).unwrap();
+    assert_eq!(query.parse().err().unwrap().as_string().unwrap(), "Expected bytes");
+}
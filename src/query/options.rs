@@ -0,0 +1,612 @@
+use std::cmp::Ordering;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
+use js_sys::{Array, Object, Reflect, JSON};
+use wasm_bindgen::JsValue;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen_test::wasm_bindgen_test;
+use crate::schema::{resolve_property_path, Schema};
+use crate::schema::property_type::PropertyType;
+use crate::utils::parse_date;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Sort direction for a single field, 1 for ascending and -1 for descending.
+ */
+export type SortDirection = 1 | -1;
+
+/**
+ * Fields to include (1 or true) or exclude (0 or false) from returned documents, nested fields
+ * are addressed with dot paths. Inclusions and exclusions cannot be mixed.
+ */
+export type Select<T extends SchemaType> = Partial<Record<Extract<keyof T['properties'], string> | `${string}.${string}`, 0 | 1 | boolean>>;
+
+/**
+ * Options accepted by find operations.
+ */
+export type QueryOptions<T extends SchemaType> = {
+    /**
+     * Fields to sort by, in order of precedence. Ties are broken by the primary key.
+     */
+    sort?: Partial<Record<Extract<keyof T['properties'], string> | `${string}.${string}`, SortDirection>>;
+    /**
+     * Maximum number of documents to return.
+     */
+    limit?: number;
+    /**
+     * Number of matching documents to skip, applied after sorting.
+     */
+    skip?: number;
+    /**
+     * Keyset to resume from: the sort values of the last seen document followed by its primary key.
+     * Only documents ordered strictly after it are returned.
+     */
+    after?: unknown[];
+};
+"#;
+
+/// A single sort criterion, resolved against the schema.
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    /// The dot-separated path of the sorted property.
+    pub(crate) path: String,
+    /// Whether the order is reversed.
+    pub(crate) descending: bool,
+    /// The type of the sorted property.
+    pub(crate) property_type: PropertyType,
+    /// Whether the property holds dates, which are ordered as instants rather than as text.
+    pub(crate) date: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Represents the options of a find operation, validated against the collection schema.
+pub struct QueryOptions {
+    /// The sort criteria, in order of precedence.
+    pub(crate) sort: Vec<SortKey>,
+    /// The maximum number of documents to return, if any.
+    pub(crate) limit: Option<usize>,
+    /// The number of matching documents to skip.
+    pub(crate) skip: usize,
+    /// The keyset to resume after: the sort values of a document followed by its primary key.
+    pub(crate) after: Option<Vec<JsValue>>,
+}
+
+impl QueryOptions {
+    /// Parses and validates find options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options object, `undefined` and `null` mean no options.
+    /// * `schema` - The schema of the queried collection.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<QueryOptions, JsValue>` - The validated options or an error.
+    pub fn new(options: JsValue, schema: &Schema) -> Result<QueryOptions, JsValue> {
+        if options.is_undefined() || options.is_null() {
+            return Ok(QueryOptions::default());
+        }
+        if !options.is_object() {
+            return Err(JsValue::from_str("Query options must be an object"));
+        }
+        let sort = Reflect::get(&options, &JsValue::from_str("sort"))?;
+        let limit = Reflect::get(&options, &JsValue::from_str("limit"))?;
+        let skip = Reflect::get(&options, &JsValue::from_str("skip"))?;
+        let after = Reflect::get(&options, &JsValue::from_str("after"))?;
+        let sort = Self::parse_sort(&sort, schema)?;
+        let after = Self::parse_after(&after, sort.len())?;
+        Ok(QueryOptions {
+            sort,
+            limit: Self::parse_count("limit", &limit)?,
+            skip: Self::parse_count("skip", &skip)?.unwrap_or(0),
+            after,
+        })
+    }
+
+    /// Whether matching documents have to be ordered, either for the sort or to resume after a keyset.
+    pub(crate) fn is_ordered(&self) -> bool {
+        !self.sort.is_empty() || self.after.is_some()
+    }
+
+    /// Returns how many matches a scan needs before it can stop, when it can stop early at all.
+    ///
+    /// Sorted results need every match, since any document may sort first.
+    pub(crate) fn scan_limit(&self) -> Option<usize> {
+        if !self.is_ordered() {
+            self.limit.map(|limit| limit + self.skip)
+        } else {
+            None
+        }
+    }
+
+    /// Applies `skip` and `limit` to documents that are already sorted.
+    pub(crate) fn paginate(&self, documents: Vec<JsValue>) -> Vec<JsValue> {
+        let documents = documents.into_iter().skip(self.skip);
+        match self.limit {
+            Some(limit) => documents.take(limit).collect(),
+            None => documents.collect(),
+        }
+    }
+
+    fn parse_after(after: &JsValue, sort_len: usize) -> Result<Option<Vec<JsValue>>, JsValue> {
+        if after.is_undefined() || after.is_null() {
+            return Ok(None);
+        }
+        if !Array::is_array(after) || Array::from(after).length() as usize != sort_len + 1 {
+            return Err(JsValue::from_str("after must hold one value per sort key followed by the primary key"));
+        }
+        Ok(Some(Array::from(after).to_vec()))
+    }
+
+    fn parse_count(name: &str, value: &JsValue) -> Result<Option<usize>, JsValue> {
+        if value.is_undefined() || value.is_null() {
+            return Ok(None);
+        }
+        match value.as_f64() {
+            Some(count) if count >= 0.0 && count.fract() == 0.0 => Ok(Some(count as usize)),
+            _ => Err(JsValue::from_str(&format!("{} must be a non-negative integer", name))),
+        }
+    }
+
+    fn parse_sort(sort: &JsValue, schema: &Schema) -> Result<Vec<SortKey>, JsValue> {
+        if sort.is_undefined() || sort.is_null() {
+            return Ok(Vec::new());
+        }
+        if !sort.is_object() {
+            return Err(JsValue::from_str("Sort must be an object"));
+        }
+        let encrypted = schema.encrypted.clone().unwrap_or_default();
+        let keys = Object::keys(&Object::from(sort.clone()));
+        let mut sort_keys = Vec::new();
+        for i in 0..keys.length() {
+            let path = keys.get(i).as_string().unwrap_or_default();
+            let direction = Reflect::get(sort, &JsValue::from_str(&path))?;
+            let descending = match direction.as_f64() {
+                Some(1.0) => false,
+                Some(-1.0) => true,
+                _ => return Err(JsValue::from_str(&format!("Sort direction for {} must be 1 or -1", path))),
+            };
+            let property = resolve_property_path(&schema.properties, &path)
+                .ok_or_else(|| JsValue::from_str(&format!("Invalid sort property: {}", path)))?;
+            // Encrypted values are stored as ciphertext, so their order is meaningless
+            let root = path.split('.').next().unwrap_or_default();
+            if encrypted.iter().any(|field| field == root) {
+                return Err(JsValue::from_str(&format!("Cannot sort on encrypted property: {}", path)));
+            }
+            match property.property_type {
+                PropertyType::String | PropertyType::Number | PropertyType::Integer | PropertyType::Boolean | PropertyType::Date => {}
+                _ => return Err(JsValue::from_str(&format!(
+                    "Cannot sort on property {} of type {}",
+                    path, property.property_type
+                ))),
+            }
+            sort_keys.push(SortKey {
+                path,
+                descending,
+                property_type: property.property_type,
+                date: property.is_date(),
+            });
+        }
+        Ok(sort_keys)
+    }
+}
+
+/// Encodes a keyset into the opaque cursor token handed out by paginated reads.
+pub(crate) fn encode_cursor(keyset: &[JsValue]) -> Result<String, JsValue> {
+    let values = keyset.iter().collect::<Array>();
+    let json = JSON::stringify(&values)?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("Failed to encode page cursor"))?;
+    Ok(BASE64.encode(json))
+}
+
+/// Decodes a cursor token produced by [`encode_cursor`] back into its keyset.
+pub(crate) fn decode_cursor(token: &str) -> Result<JsValue, JsValue> {
+    let invalid = || JsValue::from_str("Invalid page cursor");
+    let bytes = BASE64.decode(token).map_err(|_| invalid())?;
+    let json = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let keyset = JSON::parse(&json).map_err(|_| invalid())?;
+    if !Array::is_array(&keyset) {
+        return Err(invalid());
+    }
+    Ok(keyset)
+}
+
+/// Represents a `select` projection, validated against the collection schema.
+///
+/// Projections only shape the documents handed back to the caller, the storage always reads
+/// whole documents so that plugins such as encryption and migrations can process them.
+#[derive(Debug, Clone)]
+pub struct Projection {
+    /// Whether the paths are the only fields kept, rather than the fields dropped.
+    pub(crate) include: bool,
+    /// The dot-separated paths of the projected properties.
+    pub(crate) paths: Vec<String>,
+    /// The primary key, which is always returned.
+    primary_key: String,
+}
+
+impl Projection {
+    /// Parses and validates a `select` option, `undefined` and `null` mean no projection.
+    pub fn new(select: &JsValue, schema: &Schema) -> Result<Option<Projection>, JsValue> {
+        if select.is_undefined() || select.is_null() {
+            return Ok(None);
+        }
+        if !select.is_object() || Array::is_array(select) {
+            return Err(JsValue::from_str("select must be an object"));
+        }
+        let keys = Object::keys(&Object::from(select.clone()));
+        let mut include = None;
+        let mut paths = Vec::new();
+        for i in 0..keys.length() {
+            let path = keys.get(i).as_string().unwrap_or_default();
+            let value = Reflect::get(select, &JsValue::from_str(&path))?;
+            let included = match (value.as_f64(), value.as_bool()) {
+                (Some(1.0), _) | (_, Some(true)) => true,
+                (Some(0.0), _) | (_, Some(false)) => false,
+                _ => return Err(JsValue::from_str(&format!("select value for {} must be 0, 1 or a boolean", path))),
+            };
+            if resolve_property_path(&schema.properties, &path).is_none() {
+                return Err(JsValue::from_str(&format!("Invalid select property: {}", path)));
+            }
+            if *include.get_or_insert(included) != included {
+                return Err(JsValue::from_str("select cannot mix included and excluded fields"));
+            }
+            paths.push(path);
+        }
+        Ok(Some(Projection {
+            include: include.unwrap_or(false),
+            paths,
+            primary_key: schema.primary_key.clone(),
+        }))
+    }
+
+    /// Returns the top-level fields the projected documents are built from.
+    pub(crate) fn required_fields(&self, schema: &Schema) -> Vec<String> {
+        let roots: Vec<&str> = self.paths
+            .iter()
+            .map(|path| path.split('.').next().unwrap_or_default())
+            .collect();
+        schema.properties
+            .keys()
+            .filter(|field| {
+                if self.include {
+                    **field == self.primary_key || roots.contains(&field.as_str())
+                } else {
+                    // Excluding a nested path still needs the rest of its parent
+                    !self.paths.contains(field)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Builds the projected copy of a document, leaving the document itself untouched.
+    pub(crate) fn apply(&self, document: &JsValue) -> Result<JsValue, JsValue> {
+        if !document.is_object() {
+            return Ok(document.clone());
+        }
+        let projected = if self.include {
+            let projected = Object::new();
+            for field in [self.primary_key.as_str(), "__version"] {
+                let value = Reflect::get(document, &JsValue::from_str(field))?;
+                if !value.is_undefined() {
+                    Reflect::set(&projected, &JsValue::from_str(field), &value)?;
+                }
+            }
+            for path in &self.paths {
+                let segments: Vec<&str> = path.split('.').collect();
+                Self::copy_path(document, &projected, &segments)?;
+            }
+            projected
+        } else {
+            let projected = Object::assign(&Object::new(), &Object::from(document.clone()));
+            for path in &self.paths {
+                let segments: Vec<&str> = path.split('.').collect();
+                Self::remove_path(&projected, &segments)?;
+            }
+            projected
+        };
+        // Ciphertext of fields that were not decrypted is never handed out
+        Reflect::delete_property(&projected, &JsValue::from_str("__encrypted"))?;
+        Ok(projected.into())
+    }
+
+    fn copy_path(source: &JsValue, target: &JsValue, segments: &[&str]) -> Result<(), JsValue> {
+        let key = JsValue::from_str(segments[0]);
+        let value = Reflect::get(source, &key)?;
+        if value.is_undefined() {
+            return Ok(());
+        }
+        if segments.len() == 1 {
+            return Reflect::set(target, &key, &value).map(|_| ());
+        }
+        if Array::is_array(&value) {
+            if let Ok(index) = segments[1].parse::<u32>() {
+                // A numeric segment keeps the array shape around the single addressed element
+                let items = Self::child(target, &key, || Array::new().into())?;
+                let item = Array::from(&value).get(index);
+                if segments.len() == 2 {
+                    Reflect::set(&items, &JsValue::from_f64(index as f64), &item)?;
+                } else if item.is_object() {
+                    let child = Self::child(&items, &JsValue::from_f64(index as f64), || Object::new().into())?;
+                    Self::copy_path(&item, &child, &segments[2..])?;
+                }
+                return Ok(());
+            }
+            let items = Self::child(target, &key, || Array::new().into())?;
+            for (index, item) in Array::from(&value).iter().enumerate() {
+                if item.is_object() {
+                    let child = Self::child(&items, &JsValue::from_f64(index as f64), || Object::new().into())?;
+                    Self::copy_path(&item, &child, &segments[1..])?;
+                }
+            }
+        } else if value.is_object() {
+            let child = Self::child(target, &key, || Object::new().into())?;
+            Self::copy_path(&value, &child, &segments[1..])?;
+        }
+        Ok(())
+    }
+
+    fn remove_path(target: &JsValue, segments: &[&str]) -> Result<(), JsValue> {
+        let key = JsValue::from_str(segments[0]);
+        if segments.len() == 1 {
+            return Reflect::delete_property(&Object::from(target.clone()), &key).map(|_| ());
+        }
+        let value = Reflect::get(target, &key)?;
+        // Nested values are copied before removing from them, they belong to the stored document
+        if Array::is_array(&value) {
+            let items = Array::new();
+            for item in Array::from(&value).iter() {
+                if item.is_object() {
+                    let copy: JsValue = Object::assign(&Object::new(), &Object::from(item)).into();
+                    Self::remove_path(&copy, &segments[1..])?;
+                    items.push(&copy);
+                } else {
+                    items.push(&item);
+                }
+            }
+            Reflect::set(target, &key, &items)?;
+        } else if value.is_object() {
+            let copy: JsValue = Object::assign(&Object::new(), &Object::from(value)).into();
+            Self::remove_path(&copy, &segments[1..])?;
+            Reflect::set(target, &key, &copy)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the value under `key`, creating it first when missing.
+    fn child(parent: &JsValue, key: &JsValue, create: impl Fn() -> JsValue) -> Result<JsValue, JsValue> {
+        let existing = Reflect::get(parent, key)?;
+        if !existing.is_undefined() {
+            return Ok(existing);
+        }
+        let created = create();
+        Reflect::set(parent, key, &created)?;
+        Ok(created)
+    }
+}
+
+impl SortKey {
+    /// Compares two values of the sorted property in ascending order.
+    ///
+    /// Missing and `null` values come first, values of an unexpected type after them. Dates are
+    /// compared as the instants range filters compare, whatever their UTC offsets.
+    pub(crate) fn compare(&self, a: &JsValue, b: &JsValue) -> Ordering {
+        let rank = |value: &JsValue| -> u8 {
+            if value.is_undefined() || value.is_null() {
+                0
+            } else if self.matches_type(value) {
+                2
+            } else {
+                1
+            }
+        };
+        match rank(a).cmp(&rank(b)) {
+            Ordering::Equal if rank(a) == 2 => match self.property_type {
+                PropertyType::Number | PropertyType::Integer => a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(Ordering::Equal),
+                PropertyType::Boolean => a.as_bool().cmp(&b.as_bool()),
+                _ if self.date => {
+                    let time = |value: &JsValue| value.as_string().as_deref().and_then(parse_date);
+                    time(a).partial_cmp(&time(b)).unwrap_or(Ordering::Equal)
+                }
+                _ => a.as_string().cmp(&b.as_string()),
+            },
+            ordering => ordering,
+        }
+    }
+
+    pub(crate) fn matches_type(&self, value: &JsValue) -> bool {
+        match self.property_type {
+            PropertyType::Number | PropertyType::Integer => value.as_f64().is_some(),
+            PropertyType::Boolean => value.as_bool().is_some(),
+            _ => value.is_string(),
+        }
+    }
+}
+
+
+#[wasm_bindgen_test]
+fn test_query_options_parse_sort() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "name": { "type": "string" },
+            "createdAt": { "type": "number" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let options = JSON::parse(r#"{ "sort": { "createdAt": -1, "name": 1 } }"#).unwrap();
+    let options = QueryOptions::new(options, &schema).unwrap();
+    assert_eq!(options.sort.len(), 2);
+    assert_eq!(options.sort[0].path, "createdAt");
+    assert!(options.sort[0].descending);
+    assert_eq!(options.sort[1].path, "name");
+    assert!(!options.sort[1].descending);
+}
+
+#[wasm_bindgen_test]
+fn test_query_options_invalid_sort() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "encrypted": ["secret"],
+        "properties": {
+            "id": { "type": "string" },
+            "name": { "type": "string" },
+            "secret": { "type": "string" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+
+    let options = JSON::parse(r#"{ "sort": { "name": "asc" } }"#).unwrap();
+    let result = QueryOptions::new(options, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Sort direction for name must be 1 or -1"
+    );
+
+    let options = JSON::parse(r#"{ "sort": { "age": 1 } }"#).unwrap();
+    let result = QueryOptions::new(options, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Invalid sort property: age"
+    );
+
+    let options = JSON::parse(r#"{ "sort": { "secret": 1 } }"#).unwrap();
+    let result = QueryOptions::new(options, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Cannot sort on encrypted property: secret"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_options_parse_limit_and_skip() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+
+    let options = JSON::parse(r#"{ "limit": 10, "skip": 20 }"#).unwrap();
+    let options = QueryOptions::new(options, &schema).unwrap();
+    assert_eq!(options.limit, Some(10));
+    assert_eq!(options.skip, 20);
+    assert_eq!(options.scan_limit(), Some(30));
+
+    let options = JSON::parse(r#"{ "limit": -1 }"#).unwrap();
+    let result = QueryOptions::new(options, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "limit must be a non-negative integer"
+    );
+
+    let options = JSON::parse(r#"{ "skip": 1.5 }"#).unwrap();
+    let result = QueryOptions::new(options, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "skip must be a non-negative integer"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_query_options_cursor_round_trip() {
+    let keyset = vec![JsValue::from_f64(100.0), JsValue::NULL, JsValue::from_str("doc1")];
+    let token = encode_cursor(&keyset).unwrap();
+    let decoded = Array::from(&decode_cursor(&token).unwrap());
+    assert_eq!(decoded.length(), 3);
+    assert_eq!(decoded.get(0).as_f64(), Some(100.0));
+    assert!(decoded.get(1).is_null());
+    assert_eq!(decoded.get(2).as_string(), Some("doc1".to_string()));
+
+    let result = decode_cursor("not a cursor");
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "Invalid page cursor"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_projection_include_and_exclude() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "name": { "type": "string" },
+            "address": {
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" },
+                    "zip": { "type": "string" }
+                }
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let document = JSON::parse(r#"{
+        "id": "1", "name": "John", "address": { "city": "Madrid", "zip": "28001" }
+    }"#).unwrap();
+
+    let select = JSON::parse(r#"{ "address.city": 1 }"#).unwrap();
+    let projection = Projection::new(&select, &schema).unwrap().unwrap();
+    let projected = projection.apply(&document).unwrap();
+    assert_eq!(
+        JSON::stringify(&projected).unwrap().as_string().unwrap(),
+        r#"{"id":"1","address":{"city":"Madrid"}}"#
+    );
+
+    let select = JSON::parse(r#"{ "address.zip": 0, "name": false }"#).unwrap();
+    let projection = Projection::new(&select, &schema).unwrap().unwrap();
+    let projected = projection.apply(&document).unwrap();
+    assert_eq!(
+        JSON::stringify(&projected).unwrap().as_string().unwrap(),
+        r#"{"id":"1","address":{"city":"Madrid"}}"#
+    );
+    // The source document is left untouched
+    assert_eq!(
+        JSON::stringify(&document).unwrap().as_string().unwrap(),
+        r#"{"id":"1","name":"John","address":{"city":"Madrid","zip":"28001"}}"#
+    );
+
+    let select = JSON::parse(r#"{ "name": 1, "address": 0 }"#).unwrap();
+    let result = Projection::new(&select, &schema);
+    assert_eq!(
+        result.err().unwrap().as_string().unwrap(),
+        "select cannot mix included and excluded fields"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_sort_key_compares_dates_as_instants() {
+    let schema_str = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "createdAt": { "type": "date" },
+            "updatedAt": { "type": "string", "format": "date-time" },
+            "name": { "type": "string" }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_str).unwrap()).unwrap();
+    let options = JSON::parse(r#"{ "sort": { "createdAt": 1, "updatedAt": 1, "name": 1 } }"#).unwrap();
+    let options = QueryOptions::new(options, &schema).unwrap();
+    // 10:00+02:00 is 08:00 UTC, before 09:00Z although it sorts after it as text
+    let (earlier, later) = (JsValue::from_str("2024-01-01T10:00:00+02:00"), JsValue::from_str("2024-01-01T09:00:00Z"));
+    assert_eq!(options.sort[0].compare(&earlier, &later), Ordering::Less);
+    assert_eq!(options.sort[1].compare(&earlier, &later), Ordering::Less);
+    assert_eq!(options.sort[0].compare(&JsValue::from_str("2024-01-01T08:00:00Z"), &earlier), Ordering::Equal);
+    assert_eq!(options.sort[2].compare(&earlier, &later), Ordering::Greater);
+}
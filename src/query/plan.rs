@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsValue;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen_test::wasm_bindgen_test;
+use crate::query::predicate::{Condition, NearSearch, Operator, Predicate, TextSearch, Value};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Describes how a storage answered a query.
+ */
+export type ExplainResult = {
+    /**
+     * The query as normalized before evaluation.
+     */
+    query: unknown;
+    /**
+     * `primaryKey` when only the documents with the queried primary keys were read, `fullText`
+     * when only those holding the `$text` terms were, `vector` when `$near` candidates were read
+     * from an approximate index, `scan` when the whole collection was, `null` when the storage
+     * does not report it.
+     */
+    strategy: 'primaryKey' | 'fullText' | 'vector' | 'scan' | null;
+    /**
+     * The index used to read documents, `null` for full scans. Full-text searches list the
+     * indexed properties, separated by commas, vector searches name the vector property.
+     */
+    index: string | null;
+    /**
+     * Number of documents read, `null` when the storage does not report it.
+     */
+    scanned: number | null;
+    /**
+     * Number of documents matching the query, `null` when the storage does not report it.
+     */
+    matched: number | null;
+    /**
+     * Number of documents returned once `skip` and `limit` are applied.
+     */
+    returned: number;
+    /**
+     * Time spent answering the query, in milliseconds.
+     */
+    executionTimeMs: number;
+};
+"#;
+
+/// How a storage reads the documents a query may match.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryPlan {
+    /// Only the documents with these primary keys can match, they are looked up directly.
+    PrimaryKey { field: String, keys: Vec<Value> },
+    /// Only the documents holding the searched terms can match, they are read from the full-text
+    /// index by relevance.
+    FullText(TextSearch),
+    /// The documents matching the query are ranked by distance to the searched vector. Their
+    /// candidates are read from the approximate index of the property when it has one, and
+    /// from every document otherwise.
+    Nearest(NearSearch),
+    /// Every document of the collection has to be read.
+    Scan,
+}
+
+impl QueryPlan {
+    /// Chooses how to read the documents a compiled query may match.
+    ///
+    /// A `$near` search always ranks the matching documents, a `$text` search always reads from
+    /// the full-text index, which yields candidates by relevance. Otherwise, a primary key
+    /// equality or `$in` at the top level of the query, or within a top-level `$and`, restricts
+    /// the candidates to the listed keys. Other conditions are still evaluated against each
+    /// candidate.
+    pub fn new(predicate: &Predicate, primary_key: &str) -> QueryPlan {
+        if let Some(search) = Self::near_search(predicate) {
+            return QueryPlan::Nearest(search.clone());
+        }
+        if let Some(search) = Self::text_search(predicate) {
+            return QueryPlan::FullText(search.clone());
+        }
+        match Self::primary_keys(predicate, primary_key) {
+            Some(keys) => QueryPlan::PrimaryKey { field: primary_key.to_string(), keys },
+            None => QueryPlan::Scan,
+        }
+    }
+
+    /// `$text` is only allowed at the top level of a query, which compiles to a top-level `$and`
+    /// along with the other conditions.
+    fn text_search(predicate: &Predicate) -> Option<&TextSearch> {
+        match predicate {
+            Predicate::Text(search) => Some(search),
+            Predicate::And(predicates) => predicates.iter().find_map(|predicate| match predicate {
+                Predicate::Text(search) => Some(search),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// `$near` is only allowed at the top level of a query as well.
+    fn near_search(predicate: &Predicate) -> Option<&NearSearch> {
+        match predicate {
+            Predicate::Near(search) => Some(search),
+            Predicate::And(predicates) => predicates.iter().find_map(|predicate| match predicate {
+                Predicate::Near(search) => Some(search),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn primary_keys(predicate: &Predicate, primary_key: &str) -> Option<Vec<Value>> {
+        match predicate {
+            Predicate::And(predicates) => predicates
+                .iter()
+                .find_map(|predicate| Self::primary_keys(predicate, primary_key)),
+            Predicate::Field(path, condition) if path.path == primary_key => match condition {
+                Condition::Equals(value) => Some(Self::keys(std::slice::from_ref(value))),
+                Condition::Operators(operators) => operators.iter().find_map(|operator| match operator {
+                    Operator::In(values) => Some(Self::keys(values)),
+                    _ => None,
+                }),
+            },
+            _ => None,
+        }
+    }
+
+    /// Keeps the values that can be primary keys, once each.
+    fn keys(values: &[Value]) -> Vec<Value> {
+        let mut keys: Vec<Value> = Vec::new();
+        for value in values {
+            if matches!(value, Value::String(_) | Value::Number(_)) && !keys.contains(value) {
+                keys.push(value.clone());
+            }
+        }
+        keys
+    }
+}
+
+/// The documents a storage read to answer a query, along with the plan it followed.
+#[derive(Debug, Clone)]
+pub struct Execution {
+    /// The returned documents, sorted and paginated.
+    pub(crate) documents: Vec<JsValue>,
+    pub(crate) plan: QueryPlan,
+    /// Number of documents read.
+    pub(crate) scanned: usize,
+    /// Number of documents matching the query, before pagination.
+    pub(crate) matched: usize,
+}
+
+/// The report returned by `explain`.
+#[derive(Debug, Clone)]
+pub struct Explain {
+    /// The normalized query.
+    pub(crate) query: JsValue,
+    /// The plan followed, `None` when the storage does not report it.
+    pub(crate) plan: Option<QueryPlan>,
+    pub(crate) scanned: Option<usize>,
+    pub(crate) matched: Option<usize>,
+    pub(crate) returned: usize,
+    pub(crate) execution_time_ms: f64,
+}
+
+impl Explain {
+    /// Builds the report of an execution that started at `started_at`, as given by `Date.now()`.
+    pub(crate) fn from_execution(query: JsValue, execution: &Execution, started_at: f64) -> Explain {
+        Explain {
+            query,
+            plan: Some(execution.plan.clone()),
+            scanned: Some(execution.scanned),
+            matched: Some(execution.matched),
+            returned: execution.documents.len(),
+            execution_time_ms: js_sys::Date::now() - started_at,
+        }
+    }
+
+    pub(crate) fn to_js(&self) -> Result<JsValue, JsValue> {
+        let count = |value: Option<usize>| value.map_or(JsValue::NULL, |value| JsValue::from_f64(value as f64));
+        let (strategy, index) = match &self.plan {
+            Some(QueryPlan::PrimaryKey { field, .. }) => (JsValue::from_str("primaryKey"), JsValue::from_str(field)),
+            Some(QueryPlan::FullText(search)) => {
+                let fields: Vec<&str> = search.fields.iter().map(|field| field.path.as_str()).collect();
+                (JsValue::from_str("fullText"), JsValue::from_str(&fields.join(",")))
+            }
+            Some(QueryPlan::Nearest(search)) if search.approximate => {
+                (JsValue::from_str("vector"), JsValue::from_str(&search.field.path))
+            }
+            Some(QueryPlan::Nearest(_)) | Some(QueryPlan::Scan) => (JsValue::from_str("scan"), JsValue::NULL),
+            None => (JsValue::NULL, JsValue::NULL),
+        };
+        let result = Object::new();
+        Reflect::set(&result, &JsValue::from_str("query"), &self.query)?;
+        Reflect::set(&result, &JsValue::from_str("strategy"), &strategy)?;
+        Reflect::set(&result, &JsValue::from_str("index"), &index)?;
+        Reflect::set(&result, &JsValue::from_str("scanned"), &count(self.scanned))?;
+        Reflect::set(&result, &JsValue::from_str("matched"), &count(self.matched))?;
+        Reflect::set(&result, &JsValue::from_str("returned"), &JsValue::from_f64(self.returned as f64))?;
+        Reflect::set(&result, &JsValue::from_str("executionTimeMs"), &JsValue::from_f64(self.execution_time_ms))?;
+        Ok(result.into())
+    }
+}
+
+
+#[wasm_bindgen_test]
+fn test_plan_primary_key_lookup() {
+    let query = js_sys::JSON::parse(r#"{
+        "$and": [
+            { "id": { "$in": ["a", "b", "a"] } },
+            { "age": { "$gt": 30 } }
+        ]
+    }"#).unwrap();
+    let plan = QueryPlan::new(&Predicate::compile(&query, &HashMap::new()).unwrap(), "id");
+    assert_eq!(plan, QueryPlan::PrimaryKey {
+        field: "id".to_string(),
+        keys: vec![Value::String("a".to_string()), Value::String("b".to_string())],
+    });
+}
+
+#[wasm_bindgen_test]
+fn test_plan_scan() {
+    let query = js_sys::JSON::parse(r#"{
+        "$or": [
+            { "id": "a" },
+            { "age": { "$gt": 30 } }
+        ]
+    }"#).unwrap();
+    let plan = QueryPlan::new(&Predicate::compile(&query, &HashMap::new()).unwrap(), "id");
+    assert_eq!(plan, QueryPlan::Scan);
+}
+
+#[wasm_bindgen_test]
+fn test_plan_full_text() {
+    let schema = crate::schema::Schema::create(js_sys::JSON::parse(r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "title": { "type": "string", "fullText": true }
+        }
+    }"#).unwrap()).unwrap();
+    // The search is preferred over primary key lookups, since it yields candidates by relevance
+    let query = js_sys::JSON::parse(r#"{
+        "id": { "$in": ["a", "b"] },
+        "$text": { "$search": "Rust rust notes" }
+    }"#).unwrap();
+    let plan = QueryPlan::new(&Predicate::compile(&query, &schema.properties).unwrap(), "id");
+    match plan {
+        QueryPlan::FullText(search) => {
+            assert_eq!(search.terms, vec!["rust", "notes"]);
+            assert_eq!(search.fields.len(), 1);
+            assert_eq!(search.fields[0].path, "title");
+        }
+        other => panic!("Expected a full-text plan, got {:?}", other),
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_plan_nearest() {
+    let schema = crate::schema::Schema::create(js_sys::JSON::parse(r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "embedding": { "type": "vector", "dimensions": 3, "approximate": true }
+        }
+    }"#).unwrap()).unwrap();
+    let query = js_sys::JSON::parse(r#"{
+        "id": { "$in": ["a", "b"] },
+        "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2, "$metric": "euclidean" } }
+    }"#).unwrap();
+    let plan = QueryPlan::new(&Predicate::compile(&query, &schema.properties).unwrap(), "id");
+    match plan {
+        QueryPlan::Nearest(search) => {
+            assert_eq!(search.field.path, "embedding");
+            assert_eq!(search.vector, vec![1.0, 0.0, 0.0]);
+            assert_eq!(search.k, 2);
+            assert_eq!(search.metric, crate::storage::internals::vector::Metric::Euclidean);
+            assert!(search.approximate);
+        }
+        other => panic!("Expected a nearest neighbours plan, got {:?}", other),
+    }
+}
@@ -0,0 +1,439 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use js_sys::{Array, Object, Reflect, RegExp};
+use wasm_bindgen::JsValue;
+use crate::schema::property::Property;
+use crate::schema::{full_text_paths, resolve_property_path};
+use crate::storage::internals::fulltext::{tokenize, DocumentTerms};
+use crate::storage::internals::vector::{to_vector, Metric};
+use crate::utils::{build_regex, is_bytes, parse_date, to_bytes};
+
+/// A query compiled into a tree of predicates.
+///
+/// Queries are compiled once, after validation, so that storages evaluate plain Rust values
+/// instead of reflecting on the query object for every document.
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    /// Matches when every predicate does.
+    And(Vec<Predicate>),
+    /// Matches when at least one predicate does.
+    Or(Vec<Predicate>),
+    /// Matches when no predicate does.
+    Nor(Vec<Predicate>),
+    /// A condition on the values a path resolves to.
+    Field(FieldPath, Condition),
+    /// A `$text` search over the full-text indexed properties.
+    Text(TextSearch),
+    /// A `$near` search over a vector property.
+    Near(NearSearch),
+}
+
+/// The condition attached to a property of the query.
+#[derive(Debug, Clone)]
+pub enum Condition {
+    /// A direct value, matched by equality.
+    Equals(Value),
+    /// An operator object, matched when every operator does.
+    Operators(Vec<Operator>),
+}
+
+/// A single query operator along with its compiled argument.
+#[derive(Debug, Clone)]
+pub enum Operator {
+    Gt(Bound),
+    Gte(Bound),
+    Lt(Bound),
+    Lte(Bound),
+    Ne(Value),
+    In(Vec<Value>),
+    Nin(Vec<Value>),
+    Regex(RegExp),
+    StartsWith(TextPattern),
+    Contains(TextPattern),
+    All(Vec<Value>),
+    Size(f64),
+    Exists(bool),
+    ElemMatch(ElemMatch),
+    Not(Vec<Operator>),
+}
+
+/// The condition of an `$elemMatch` operator.
+#[derive(Debug, Clone)]
+pub enum ElemMatch {
+    /// A query over object elements.
+    Document(Box<Predicate>),
+    /// An operator object over scalar elements.
+    Value(Vec<Operator>),
+}
+
+/// The bound of a range operator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bound {
+    /// Compared against numbers.
+    Number(f64),
+    /// Compared lexicographically against strings.
+    String(String),
+    /// Compared against strings of a `date` or `date-time` property, as milliseconds since the epoch.
+    Date(f64),
+}
+
+impl Bound {
+    /// Orders a document value against the bound, `None` when they cannot be compared.
+    pub(crate) fn compare(&self, doc_value: &JsValue) -> Option<Ordering> {
+        match self {
+            Bound::Number(bound) => doc_value.as_f64()?.partial_cmp(bound),
+            Bound::String(bound) => Some(doc_value.as_string()?.as_str().cmp(bound.as_str())),
+            Bound::Date(bound) => parse_date(&doc_value.as_string()?)?.partial_cmp(bound),
+        }
+    }
+}
+
+/// A value of the query, compared strictly against document values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Value>),
+}
+
+/// The needle of `$startsWith` and `$contains`, lowercased when matching ignores case.
+#[derive(Debug, Clone)]
+pub struct TextPattern {
+    pub(crate) needle: String,
+    pub(crate) ignore_case: bool,
+}
+
+/// Whether a `$text` search requires every term or any of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextMode {
+    All,
+    Any,
+}
+
+/// A compiled `$text` search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSearch {
+    /// The distinct terms of `$search`, tokenized like indexed values.
+    pub(crate) terms: Vec<String>,
+    pub(crate) mode: TextMode,
+    /// The full-text indexed properties the terms are searched in.
+    pub(crate) fields: Vec<FieldPath>,
+}
+
+impl TextSearch {
+    fn compile(value: &JsValue, properties: &HashMap<String, Property>) -> Result<TextSearch, JsValue> {
+        let search = Reflect::get(value, &JsValue::from_str("$search"))?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("$text requires a $search string"))?;
+        let mode = match Reflect::get(value, &JsValue::from_str("$operator"))?.as_string().as_deref() {
+            None | Some("or") => TextMode::Any,
+            Some("and") => TextMode::All,
+            Some(_) => return Err(JsValue::from_str("$text $operator must be 'and' or 'or'")),
+        };
+        let mut terms: Vec<String> = Vec::new();
+        for term in tokenize(&search) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        Ok(TextSearch {
+            terms,
+            mode,
+            fields: full_text_paths(properties).iter().map(|path| FieldPath::new(path)).collect(),
+        })
+    }
+
+    /// Whether a document holding these terms matches the search.
+    pub(crate) fn matches(&self, terms: &DocumentTerms) -> bool {
+        match self.mode {
+            TextMode::All => self.terms.iter().all(|term| terms.contains(term)),
+            TextMode::Any => self.terms.iter().any(|term| terms.contains(term)),
+        }
+    }
+}
+
+/// A compiled `$near` search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearSearch {
+    /// The vector property searched.
+    pub(crate) field: FieldPath,
+    /// The vector of `$vector`, with the dimensions of the property.
+    pub(crate) vector: Vec<f64>,
+    /// The number of neighbours kept.
+    pub(crate) k: usize,
+    pub(crate) metric: Metric,
+    /// Whether the property has an approximate index to read candidates from.
+    pub(crate) approximate: bool,
+}
+
+impl NearSearch {
+    fn compile(path: &str, value: &JsValue, property: Option<&Property>) -> Result<NearSearch, JsValue> {
+        let near = Reflect::get(value, &JsValue::from_str("$near"))?;
+        let dimensions = property
+            .and_then(|property| property.dimensions)
+            .ok_or_else(|| JsValue::from_str("$near operator is only supported on vector properties"))?;
+        let vector = to_vector(&Reflect::get(&near, &JsValue::from_str("$vector"))?, dimensions as usize)
+            .ok_or_else(|| JsValue::from_str(&format!("$near $vector must be an array of {} numbers", dimensions)))?;
+        let k = Reflect::get(&near, &JsValue::from_str("$k"))?
+            .as_f64()
+            .filter(|k| *k >= 1.0 && k.fract() == 0.0)
+            .ok_or_else(|| JsValue::from_str("$near $k must be a positive integer"))?;
+        let metric = match Reflect::get(&near, &JsValue::from_str("$metric"))?.as_string() {
+            None => Metric::Cosine,
+            Some(metric) => Metric::parse(&metric)
+                .ok_or_else(|| JsValue::from_str("$near $metric must be 'cosine', 'dot' or 'euclidean'"))?,
+        };
+        Ok(NearSearch {
+            field: FieldPath::new(path),
+            vector,
+            k: k as usize,
+            metric,
+            approximate: property.is_some_and(|property| property.approximate == Some(true)),
+        })
+    }
+
+    /// Whether a property condition is a `$near` search.
+    pub(crate) fn is_near(value: &JsValue) -> bool {
+        value.is_object() && !Array::is_array(value) && Reflect::has(value, &JsValue::from_str("$near")).unwrap_or(false)
+    }
+}
+
+/// A dot-separated path, split into the keys used to read each level of a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPath {
+    /// The path as written in the query.
+    pub(crate) path: String,
+    pub(crate) segments: Vec<PathSegment>,
+}
+
+/// A single level of a `FieldPath`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSegment {
+    /// The segment as an object key.
+    pub(crate) key: JsValue,
+    /// The segment as an array index, when it is numeric.
+    pub(crate) index: Option<u32>,
+}
+
+impl FieldPath {
+    /// Splits a dot-separated path, converting each segment once for all documents.
+    pub fn new(path: &str) -> FieldPath {
+        FieldPath {
+            path: path.to_string(),
+            segments: path
+                .split('.')
+                .map(|segment| PathSegment {
+                    key: JsValue::from_str(segment),
+                    index: segment.parse::<u32>().ok(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Predicate {
+    /// Compiles a query that `Query::parse` already validated.
+    ///
+    /// The properties tell how range bounds compare, strings of `date` and `date-time`
+    /// properties compare as dates.
+    pub fn compile(query: &JsValue, properties: &HashMap<String, Property>) -> Result<Predicate, JsValue> {
+        if !query.is_object() {
+            return Err(JsValue::from_str("Query must be an object"));
+        }
+        let keys = Object::keys(&Object::from(query.clone()));
+        let mut predicates = Vec::with_capacity(keys.length() as usize);
+        for i in 0..keys.length() {
+            let key = keys.get(i).as_string().unwrap_or_default();
+            let value = Reflect::get(query, &JsValue::from_str(&key))?;
+            if key == "$and" || key == "$or" || key == "$nor" {
+                if !Array::is_array(&value) {
+                    return Err(JsValue::from_str(&format!("{} must be an array", key)));
+                }
+                let items = Array::from(&value)
+                    .iter()
+                    .map(|item| Predicate::compile(&item, properties))
+                    .collect::<Result<Vec<Predicate>, JsValue>>()?;
+                predicates.push(match key.as_str() {
+                    "$and" => Predicate::And(items),
+                    "$or" => Predicate::Or(items),
+                    _ => Predicate::Nor(items),
+                });
+            } else if key == "$text" {
+                predicates.push(Predicate::Text(TextSearch::compile(&value, properties)?));
+            } else if NearSearch::is_near(&value) {
+                let property = resolve_property_path(properties, &key);
+                predicates.push(Predicate::Near(NearSearch::compile(&key, &value, property)?));
+            } else {
+                let property = resolve_property_path(properties, &key);
+                predicates.push(Predicate::Field(FieldPath::new(&key), Condition::compile(&value, property)?));
+            }
+        }
+        if predicates.len() == 1 {
+            Ok(predicates.remove(0))
+        } else {
+            Ok(Predicate::And(predicates))
+        }
+    }
+}
+
+impl Condition {
+    fn compile(value: &JsValue, property: Option<&Property>) -> Result<Condition, JsValue> {
+        if value.is_object() && !Array::is_array(value) && !is_bytes(value) {
+            Ok(Condition::Operators(Operator::compile_all(value, property)?))
+        } else {
+            Ok(Condition::Equals(Value::from_js(value)?))
+        }
+    }
+}
+
+impl Operator {
+    fn compile_all(condition: &JsValue, property: Option<&Property>) -> Result<Vec<Operator>, JsValue> {
+        let options = Reflect::get(condition, &JsValue::from_str("$options"))?
+            .as_string()
+            .unwrap_or_default();
+        let keys = Object::keys(&Object::from(condition.clone()));
+        let mut operators = Vec::with_capacity(keys.length() as usize);
+        for i in 0..keys.length() {
+            let key = keys.get(i).as_string().unwrap_or_default();
+            let value = Reflect::get(condition, &JsValue::from_str(&key))?;
+            let operator = match key.as_str() {
+                "$gt" => Operator::Gt(Self::bound(&key, &value, property)?),
+                "$gte" => Operator::Gte(Self::bound(&key, &value, property)?),
+                "$lt" => Operator::Lt(Self::bound(&key, &value, property)?),
+                "$lte" => Operator::Lte(Self::bound(&key, &value, property)?),
+                "$ne" => Operator::Ne(Value::from_js(&value)?),
+                "$in" => Operator::In(Value::list(&key, &value)?),
+                "$nin" => Operator::Nin(Value::list(&key, &value)?),
+                "$all" => Operator::All(Value::list(&key, &value)?),
+                "$regex" => Operator::Regex(build_regex(&Self::string(&key, &value)?, &options)?),
+                "$startsWith" => Operator::StartsWith(TextPattern::new(Self::string(&key, &value)?, &options)),
+                "$contains" => Operator::Contains(TextPattern::new(Self::string(&key, &value)?, &options)),
+                "$size" => Operator::Size(Self::number(&key, &value)?),
+                "$exists" => Operator::Exists(
+                    value
+                        .as_bool()
+                        .ok_or_else(|| JsValue::from_str("$exists value must be a boolean"))?,
+                ),
+                "$not" => Operator::Not(Operator::compile_all(&value, property)?),
+                "$elemMatch" => Operator::ElemMatch(ElemMatch::compile(&value, property)?),
+                // Modifier for the string operators, not a condition on its own
+                "$options" => continue,
+                _ => return Err(JsValue::from_str(&format!("Unsupported operator: {}", key))),
+            };
+            operators.push(operator);
+        }
+        Ok(operators)
+    }
+
+    fn bound(operator: &str, value: &JsValue, property: Option<&Property>) -> Result<Bound, JsValue> {
+        if let Some(number) = value.as_f64() {
+            return Ok(Bound::Number(number));
+        }
+        let string = value
+            .as_string()
+            .ok_or_else(|| JsValue::from_str(&format!("{} value must be a number or a string", operator)))?;
+        if property.is_some_and(|property| property.is_date()) {
+            let time = parse_date(&string)
+                .ok_or_else(|| JsValue::from_str(&format!("{} value must be a valid date", operator)))?;
+            Ok(Bound::Date(time))
+        } else {
+            Ok(Bound::String(string))
+        }
+    }
+
+    fn number(operator: &str, value: &JsValue) -> Result<f64, JsValue> {
+        value
+            .as_f64()
+            .ok_or_else(|| JsValue::from_str(&format!("{} value must be a number", operator)))
+    }
+
+    fn string(operator: &str, value: &JsValue) -> Result<String, JsValue> {
+        value
+            .as_string()
+            .ok_or_else(|| JsValue::from_str(&format!("{} value must be a string", operator)))
+    }
+}
+
+impl ElemMatch {
+    fn compile(value: &JsValue, property: Option<&Property>) -> Result<ElemMatch, JsValue> {
+        if !value.is_object() || Array::is_array(value) {
+            return Err(JsValue::from_str("$elemMatch value must be an object"));
+        }
+        // Operator objects apply to scalar elements, anything else is a query over object elements
+        let keys = Object::keys(&Object::from(value.clone()));
+        let is_operator_condition = keys.iter().all(|key| {
+            let key = key.as_string().unwrap_or_default();
+            key.starts_with('$') && !["$and", "$or", "$nor"].contains(&key.as_str())
+        });
+        let item_property = property
+            .and_then(|property| property.items.as_ref())
+            .and_then(|items| items.first());
+        if is_operator_condition {
+            Ok(ElemMatch::Value(Operator::compile_all(value, item_property)?))
+        } else {
+            let empty = HashMap::new();
+            let item_properties = item_property
+                .and_then(|item| item.properties.as_ref())
+                .unwrap_or(&empty);
+            Ok(ElemMatch::Document(Box::new(Predicate::compile(value, item_properties)?)))
+        }
+    }
+}
+
+impl Value {
+    /// Converts a query value, objects are not valid query values.
+    pub fn from_js(value: &JsValue) -> Result<Value, JsValue> {
+        if value.is_null() {
+            Ok(Value::Null)
+        } else if let Some(boolean) = value.as_bool() {
+            Ok(Value::Bool(boolean))
+        } else if let Some(number) = value.as_f64() {
+            Ok(Value::Number(number))
+        } else if let Some(string) = value.as_string() {
+            Ok(Value::String(string))
+        } else if let Some(bytes) = to_bytes(value) {
+            Ok(Value::Bytes(bytes))
+        } else if Array::is_array(value) {
+            Ok(Value::Array(
+                Array::from(value)
+                    .iter()
+                    .map(|item| Value::from_js(&item))
+                    .collect::<Result<Vec<Value>, JsValue>>()?,
+            ))
+        } else {
+            Err(JsValue::from_str("Unsupported query value"))
+        }
+    }
+
+    /// Converts the value back to JavaScript.
+    pub fn to_js(&self) -> JsValue {
+        match self {
+            Value::Null => JsValue::NULL,
+            Value::Bool(boolean) => JsValue::from_bool(*boolean),
+            Value::Number(number) => JsValue::from_f64(*number),
+            Value::String(string) => JsValue::from_str(string),
+            Value::Bytes(bytes) => js_sys::Uint8Array::from(bytes.as_slice()).into(),
+            Value::Array(items) => items.iter().map(Value::to_js).collect::<Array>().into(),
+        }
+    }
+
+    fn list(operator: &str, value: &JsValue) -> Result<Vec<Value>, JsValue> {
+        match Value::from_js(value)? {
+            Value::Array(items) => Ok(items),
+            _ => Err(JsValue::from_str(&format!("{} value must be an array", operator))),
+        }
+    }
+}
+
+impl TextPattern {
+    fn new(needle: String, options: &str) -> TextPattern {
+        let ignore_case = options.contains('i');
+        TextPattern {
+            needle: if ignore_case { needle.to_lowercase() } else { needle },
+            ignore_case,
+        }
+    }
+}
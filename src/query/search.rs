@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use js_sys::{Array, JSON, Reflect};
+use wasm_bindgen::JsValue;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen_test::wasm_bindgen_test;
+use crate::query::predicate::FieldPath;
+use crate::schema::{resolve_property_path, Schema};
+use crate::schema::property::Property;
+use crate::schema::property_type::PropertyType;
+use crate::storage::internals::core::CoreStorage;
+use crate::storage::internals::fulltext::tokenize;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Property paths accepted by searches.
+ */
+export type SearchField<T extends SchemaType> = Extract<keyof T['properties'], string> | `${string}.${string}`;
+
+/**
+ * Options of a fuzzy search.
+ */
+export type SearchOptions<T extends SchemaType> = {
+    /**
+     * String properties to search, every string property of the schema when omitted.
+     */
+    fields?: SearchField<T>[];
+    /**
+     * Maximum number of edits (insertions, deletions, substitutions or transpositions of adjacent
+     * characters) tolerated per term, defaults to 2. Terms of up to 2 characters always have to
+     * match exactly and terms of up to 5 tolerate a single edit.
+     */
+    maxDistance?: number;
+    /**
+     * Maximum number of hits, every hit when omitted.
+     */
+    limit?: number;
+};
+
+/**
+ * A document found by a search, along with its relevance.
+ */
+export type SearchHit<T extends SchemaType> = {
+    doc: Doc<T>;
+    /**
+     * Relevance from 0 to 1, 1 when every searched term is found as is.
+     */
+    score: number;
+};
+"#;
+
+/// Edits tolerated per term when the options do not set `maxDistance`.
+const DEFAULT_MAX_DISTANCE: usize = 2;
+
+#[derive(Debug, Clone)]
+/// Represents a fuzzy search, validated against the collection schema.
+pub struct Search {
+    /// The distinct searched terms.
+    pub(crate) terms: Vec<String>,
+    /// The string properties searched.
+    pub(crate) fields: Vec<FieldPath>,
+    /// The edits tolerated per term.
+    pub(crate) max_distance: usize,
+    /// The maximum number of hits.
+    pub(crate) limit: Option<usize>,
+}
+
+impl Search {
+    /// Parses and validates a search.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The searched text.
+    /// * `options` - The optional `{ fields, maxDistance, limit }` object.
+    /// * `schema` - The schema of the searched collection.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Search, JsValue>` - The validated search or an error.
+    pub fn new(text: &JsValue, options: &JsValue, schema: &Schema) -> Result<Search, JsValue> {
+        let text = text
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("Search text must be a string"))?;
+        let mut terms: Vec<String> = Vec::new();
+        for term in tokenize(&text) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        if terms.is_empty() {
+            return Err(JsValue::from_str("Search text must contain at least one term"));
+        }
+
+        if options.is_undefined() || options.is_null() {
+            return Ok(Search {
+                terms,
+                fields: Self::fields(schema, None)?,
+                max_distance: DEFAULT_MAX_DISTANCE,
+                limit: None,
+            });
+        }
+        if !options.is_object() || Array::is_array(options) {
+            return Err(JsValue::from_str("Search options must be an object"));
+        }
+        let fields = Reflect::get(options, &JsValue::from_str("fields"))?;
+        let fields = if fields.is_undefined() || fields.is_null() {
+            Self::fields(schema, None)?
+        } else if Array::is_array(&fields) {
+            let paths = Array::from(&fields)
+                .iter()
+                .map(|path| path.as_string().ok_or_else(|| JsValue::from_str("fields must hold property names")))
+                .collect::<Result<Vec<String>, JsValue>>()?;
+            Self::fields(schema, Some(paths))?
+        } else {
+            return Err(JsValue::from_str("fields must be an array of property names"));
+        };
+        let max_distance = Self::count(options, "maxDistance")?.unwrap_or(DEFAULT_MAX_DISTANCE);
+        let limit = Self::count(options, "limit")?;
+        if limit == Some(0) {
+            return Err(JsValue::from_str("limit must be a positive integer"));
+        }
+        Ok(Search { terms, fields, max_distance, limit })
+    }
+
+    /// Resolves the searched properties, every string property of the schema by default.
+    fn fields(schema: &Schema, paths: Option<Vec<String>>) -> Result<Vec<FieldPath>, JsValue> {
+        let paths = match paths {
+            Some(paths) => {
+                for path in &paths {
+                    let property = resolve_property_path(&schema.properties, path)
+                        .ok_or_else(|| JsValue::from_str(&format!("Invalid search property: {}", path)))?;
+                    if !Self::is_text(property) {
+                        return Err(JsValue::from_str(&format!(
+                            "Cannot search property {} of type {}",
+                            path, property.property_type
+                        )));
+                    }
+                }
+                paths
+            }
+            None => Self::text_paths(&schema.properties),
+        };
+        if paths.is_empty() {
+            return Err(JsValue::from_str("Search requires at least one string property"));
+        }
+        Ok(paths.iter().map(|path| FieldPath::new(path)).collect())
+    }
+
+    /// Lists the string properties, and arrays of strings, nested objects included.
+    fn text_paths(properties: &HashMap<String, Property>) -> Vec<String> {
+        let mut paths = Vec::new();
+        for (key, property) in properties {
+            if Self::is_text(property) {
+                paths.push(key.clone());
+            } else if let (PropertyType::Object, Some(nested)) = (property.property_type, &property.properties) {
+                paths.extend(Self::text_paths(nested).into_iter().map(|path| format!("{}.{}", key, path)));
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    fn is_text(property: &Property) -> bool {
+        match property.property_type {
+            PropertyType::String => true,
+            PropertyType::Array => property
+                .items
+                .as_ref()
+                .and_then(|items| items.first())
+                .is_some_and(|item| item.property_type == PropertyType::String),
+            _ => false,
+        }
+    }
+
+    fn count(options: &JsValue, name: &str) -> Result<Option<usize>, JsValue> {
+        let value = Reflect::get(options, &JsValue::from_str(name))?;
+        if value.is_undefined() || value.is_null() {
+            return Ok(None);
+        }
+        match value.as_f64() {
+            Some(count) if count >= 0.0 && count.fract() == 0.0 => Ok(Some(count as usize)),
+            _ => Err(JsValue::from_str(&format!("{} must be a non-negative integer", name))),
+        }
+    }
+}
+
+
+#[wasm_bindgen_test]
+fn test_search_parse_options() {
+    let schema = Schema::create(JSON::parse(r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "name": { "type": "string" },
+            "tags": { "type": "array", "items": [{ "type": "string" }] },
+            "age": { "type": "number" },
+            "address": {
+                "type": "object",
+                "properties": { "city": { "type": "string" } }
+            }
+        }
+    }"#).unwrap()).unwrap();
+
+    let search = Search::new(&JsValue::from_str("Jonh jonh Smith"), &JsValue::undefined(), &schema).unwrap();
+    assert_eq!(search.terms, vec!["jonh", "smith"]);
+    let paths: Vec<&str> = search.fields.iter().map(|field| field.path.as_str()).collect();
+    assert_eq!(paths, vec!["address.city", "id", "name", "tags"]);
+    assert_eq!(search.max_distance, 2);
+    assert_eq!(search.limit, None);
+
+    let options = JSON::parse(r#"{ "fields": ["name"], "maxDistance": 1, "limit": 5 }"#).unwrap();
+    let search = Search::new(&JsValue::from_str("jonh"), &options, &schema).unwrap();
+    assert_eq!(search.fields.len(), 1);
+    assert_eq!(search.max_distance, 1);
+    assert_eq!(search.limit, Some(5));
+
+    let errors = [
+        ("jonh", r#"{ "fields": ["age"] }"#, "Cannot search property age of type number"),
+        ("jonh", r#"{ "fields": ["missing"] }"#, "Invalid search property: missing"),
+        ("jonh", r#"{ "maxDistance": -1 }"#, "maxDistance must be a non-negative integer"),
+        ("jonh", r#"{ "limit": 0 }"#, "limit must be a positive integer"),
+        (" ? ", r#"{}"#, "Search text must contain at least one term"),
+    ];
+    for (text, options, message) in errors {
+        let result = Search::new(&JsValue::from_str(text), &JSON::parse(options).unwrap(), &schema);
+        assert_eq!(result.err().unwrap().as_string().unwrap(), message);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_search_scores_documents() {
+    let schema = Schema::create(JSON::parse(r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "name": { "type": "string" }
+        }
+    }"#).unwrap()).unwrap();
+    let documents: Vec<JsValue> = [
+        r#"{ "id": "1", "name": "John Smith" }"#,
+        r#"{ "id": "2", "name": "Jane Doe" }"#,
+        r#"{ "id": "3", "name": "Jonh Smyth" }"#,
+    ]
+    .iter()
+    .map(|doc| JSON::parse(doc).unwrap())
+    .collect();
+
+    let options = JSON::parse(r#"{ "fields": ["name"] }"#).unwrap();
+    let search = Search::new(&JsValue::from_str("jonh smith"), &options, &schema).unwrap();
+    let hits = Array::from(&CoreStorage {}.search(&documents, &search, "id").unwrap());
+    let ids: Vec<String> = hits
+        .iter()
+        .map(|hit| {
+            let doc = Reflect::get(&hit, &JsValue::from_str("doc")).unwrap();
+            Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap()
+        })
+        .collect();
+    // Neither document holds both terms as is, Jane Doe holds neither of them
+    assert_eq!(ids, vec!["3", "1"]);
+    let score = |index: u32| Reflect::get(&hits.get(index), &JsValue::from_str("score")).unwrap().as_f64().unwrap();
+    assert!(score(0) < 1.0);
+    assert!(score(1) > 0.0 && score(1) < score(0));
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use serde_json::{json, Map, Value};
+use crate::error::RIDBError;
+use crate::schema::property::{pointer, Property};
+use crate::schema::property_type::PropertyType;
+use crate::schema::Schema;
+
+/// The dialect of exported JSON Schemas.
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Keywords that only annotate a JSON Schema, dropped on import.
+const ANNOTATIONS: [&str; 9] = [
+    "$schema", "$id", "$comment", "title", "description", "examples", "deprecated", "readOnly", "writeOnly",
+];
+
+/// Validation keywords that RIDB properties share with JSON Schema, under the same names.
+const CONSTRAINTS: [&str; 14] = [
+    "required", "minLength", "maxLength", "pattern", "format", "minItems", "maxItems", "minimum", "maximum",
+    "exclusiveMinimum", "exclusiveMaximum", "multipleOf", "enum", "default",
+];
+
+/// Keywords whose value must be a number.
+const NUMERIC: [&str; 9] = [
+    "minLength", "maxLength", "minItems", "maxItems", "minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum",
+    "multipleOf",
+];
+
+/// Converts a JSON Schema of an object type into a schema.
+///
+/// The supported subset covers `type` (unions and `null` included), `properties`, `required`,
+/// `items`, length and numeric range constraints, `enum`, `const`, `pattern`, the `date` and
+/// `date-time` formats and `default`. Annotations such as `title` or `description` are dropped,
+/// any other keyword is rejected, naming the property it appears in.
+///
+/// # Arguments
+///
+/// * `definition` - The JSON Schema.
+/// * `primary_key` - The property identifying documents.
+/// * `version` - The version of the schema.
+pub(crate) fn from_json_schema(definition: &Value, primary_key: &str, version: i32) -> Result<Schema, RIDBError> {
+    let keywords = definition
+        .as_object()
+        .ok_or_else(|| RIDBError::validation("JSON Schema must be an object"))?;
+    if keywords.get("type") != Some(&json!("object")) {
+        return Err(RIDBError::validation("JSON Schema must have the object type"));
+    }
+    let mut schema = Map::new();
+    schema.insert("version".to_string(), json!(version));
+    schema.insert("primaryKey".to_string(), json!(primary_key));
+    schema.insert("type".to_string(), json!("object"));
+    for (keyword, value) in keywords {
+        match keyword.as_str() {
+            "type" => {}
+            "properties" => {
+                schema.insert(keyword.clone(), import_properties(value, "")?);
+            }
+            "required" => {
+                schema.insert(keyword.clone(), value.clone());
+            }
+            "additionalProperties" if value == &Value::Bool(true) => {}
+            keyword if ANNOTATIONS.contains(&keyword) => {}
+            keyword => return Err(unsupported(keyword, "")),
+        }
+    }
+    let has_primary_key = schema
+        .get("properties")
+        .and_then(|properties| properties.get(primary_key))
+        .is_some();
+    if !has_primary_key {
+        return Err(RIDBError::validation(&format!("Primary key {} is not a property of the JSON Schema", primary_key)));
+    }
+    let schema: Schema = serde_json::from_value(Value::Object(schema))
+        .map_err(|e| RIDBError::validation(&format!("Invalid JSON Schema: {}", e)))?;
+    schema.is_valid()?;
+    Ok(schema)
+}
+
+/// Converts a schema into a JSON Schema of an object type.
+///
+/// Date-type properties become strings with the `date-time` format and vector-type properties
+/// arrays of numbers. Settings JSON Schema has no keyword for, such as the primary key, indexes,
+/// encrypted properties, full-text indexing and default value generators, are left out. Bytes
+/// have no JSON equivalent, so schemas with bytes-type properties cannot be converted.
+pub(crate) fn to_json_schema(schema: &Schema) -> Result<Value, RIDBError> {
+    let mut definition = Map::new();
+    definition.insert("$schema".to_string(), json!(DIALECT));
+    definition.insert("type".to_string(), json!("object"));
+    definition.insert("properties".to_string(), export_properties(&schema.properties, "")?);
+    if let Some(required) = schema.required.as_ref().filter(|required| !required.is_empty()) {
+        definition.insert("required".to_string(), json!(required));
+    }
+    Ok(Value::Object(definition))
+}
+
+fn import_properties(properties: &Value, path: &str) -> Result<Value, RIDBError> {
+    let properties = properties
+        .as_object()
+        .ok_or_else(|| invalid("properties", "an object", path))?;
+    let mut imported = Map::new();
+    for (key, property) in properties {
+        let property_path = format!("{}/properties{}", path, pointer(key));
+        imported.insert(key.clone(), import_property(property, &property_path)?);
+    }
+    Ok(Value::Object(imported))
+}
+
+fn import_property(definition: &Value, path: &str) -> Result<Value, RIDBError> {
+    let keywords = definition
+        .as_object()
+        .ok_or_else(|| RIDBError::validation(&format!("JSON Schema at {} must be an object", path)))?;
+    if !keywords.contains_key("type") {
+        return Err(RIDBError::validation(&format!("JSON Schema at {} must have a type", path)));
+    }
+    let mut property = Map::new();
+    for (keyword, value) in keywords {
+        match keyword.as_str() {
+            "type" => {
+                property.insert(keyword.clone(), value.clone());
+            }
+            "properties" => {
+                property.insert(keyword.clone(), import_properties(value, path)?);
+            }
+            "items" if value.is_object() => {
+                property.insert(keyword.clone(), json!([import_property(value, &format!("{}/items", path))?]));
+            }
+            "items" => return Err(invalid(keyword, "a single schema", path)),
+            "const" => {
+                property.insert("enum".to_string(), json!([value]));
+            }
+            "format" if value != "date" && value != "date-time" => {
+                return Err(RIDBError::validation(&format!("Unsupported JSON Schema format {} at {}", value, path)));
+            }
+            keyword if NUMERIC.contains(&keyword) && !value.is_number() => return Err(invalid(keyword, "a number", path)),
+            keyword if CONSTRAINTS.contains(&keyword) => {
+                property.insert(keyword.to_string(), value.clone());
+            }
+            "additionalProperties" if value == &Value::Bool(true) => {}
+            keyword if ANNOTATIONS.contains(&keyword) => {}
+            keyword => return Err(unsupported(keyword, path)),
+        }
+    }
+    Ok(Value::Object(property))
+}
+
+fn export_properties(properties: &HashMap<String, Property>, path: &str) -> Result<Value, RIDBError> {
+    let mut exported = Map::new();
+    for (key, property) in properties {
+        let property_path = format!("{}/properties{}", path, pointer(key));
+        exported.insert(key.clone(), export_property(property, &property_path)?);
+    }
+    Ok(Value::Object(exported))
+}
+
+fn export_property(property: &Property, path: &str) -> Result<Value, RIDBError> {
+    let serialized = serde_json::to_value(property)
+        .map_err(|e| RIDBError::serialisation(&e.to_string()))?;
+    let mut definition = Map::new();
+    for keyword in CONSTRAINTS {
+        if let Some(value) = serialized.get(keyword) {
+            definition.insert(keyword.to_string(), value.clone());
+        }
+    }
+    // Generators have no JSON Schema equivalent
+    if definition.get("default").is_some_and(|default| default.get("$generate").is_some()) {
+        definition.remove("default");
+    }
+
+    let mut types: Vec<&str> = Vec::new();
+    for property_type in property.accepted_types() {
+        let json_type = match property_type {
+            PropertyType::String => "string",
+            PropertyType::Number => "number",
+            PropertyType::Integer => "integer",
+            PropertyType::Boolean => "boolean",
+            PropertyType::Object => "object",
+            PropertyType::Array => "array",
+            PropertyType::Vector => {
+                let dimensions = property.dimensions.unwrap_or_default();
+                definition.insert("items".to_string(), json!({ "type": "number" }));
+                definition.insert("minItems".to_string(), json!(dimensions));
+                definition.insert("maxItems".to_string(), json!(dimensions));
+                "array"
+            }
+            PropertyType::Date => {
+                definition.insert("format".to_string(), json!("date-time"));
+                "string"
+            }
+            property_type => {
+                return Err(RIDBError::validation(&format!(
+                    "Cannot convert {} property at {} to JSON Schema",
+                    property_type, path
+                )))
+            }
+        };
+        if !types.contains(&json_type) {
+            types.push(json_type);
+        }
+    }
+    if property.is_nullable() {
+        types.push("null");
+    }
+    let json_type = match types.as_slice() {
+        [single] => json!(single),
+        union => json!(union),
+    };
+    definition.insert("type".to_string(), json_type);
+
+    if let Some(properties) = &property.properties {
+        definition.insert("properties".to_string(), export_properties(properties, path)?);
+    }
+    if let Some(item) = property.items.as_ref().and_then(|items| items.first()) {
+        definition.insert("items".to_string(), export_property(item, &format!("{}/items", path))?);
+    }
+    Ok(Value::Object(definition))
+}
+
+fn unsupported(keyword: &str, path: &str) -> RIDBError {
+    if path.is_empty() {
+        RIDBError::validation(&format!("Unsupported JSON Schema keyword {}", keyword))
+    } else {
+        RIDBError::validation(&format!("Unsupported JSON Schema keyword {} at {}", keyword, path))
+    }
+}
+
+fn invalid(keyword: &str, expected: &str, path: &str) -> RIDBError {
+    if path.is_empty() {
+        RIDBError::validation(&format!("JSON Schema keyword {} must be {}", keyword, expected))
+    } else {
+        RIDBError::validation(&format!("JSON Schema keyword {} at {} must be {}", keyword, path, expected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(result: Result<Schema, RIDBError>) -> String {
+        match result {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(error) => error.message,
+        }
+    }
+
+    #[test]
+    fn test_from_json_schema() {
+        let definition = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "User",
+            "type": "object",
+            "required": ["id", "email"],
+            "properties": {
+                "id": { "type": "string", "maxLength": 36 },
+                "email": { "type": "string", "maxLength": 254, "description": "Where to reach the user" },
+                "age": { "type": ["integer", "null"], "minimum": 0, "exclusiveMaximum": 150 },
+                "role": { "const": "member", "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string", "minLength": 1 }, "maxItems": 10 },
+                "address": {
+                    "type": "object",
+                    "required": ["city"],
+                    "additionalProperties": true,
+                    "properties": { "city": { "type": "string" }, "zip": { "type": ["string", "number"] } }
+                },
+                "createdAt": { "type": "string", "format": "date-time", "default": "2024-01-01T00:00:00Z" }
+            }
+        });
+        let schema = from_json_schema(&definition, "id", 2).unwrap();
+        assert_eq!(schema.version, 2);
+        assert_eq!(schema.primary_key, "id");
+        assert_eq!(schema.required, Some(vec!["id".to_string(), "email".to_string()]));
+        let age = &schema.properties["age"];
+        assert_eq!(age.property_type, PropertyType::Integer);
+        assert!(age.is_nullable());
+        assert_eq!((age.minimum, age.exclusive_maximum), (Some(0.0), Some(150.0)));
+        assert_eq!(schema.properties["role"].enum_values, Some(vec![json!("member")]));
+        let tags = &schema.properties["tags"];
+        assert_eq!(tags.max_items, Some(10));
+        assert_eq!(tags.items.as_ref().unwrap()[0].min_length, Some(1));
+        let address = &schema.properties["address"];
+        assert_eq!(address.required, Some(vec!["city".to_string()]));
+        let zip = &address.properties.as_ref().unwrap()["zip"];
+        assert_eq!(zip.types, Some(vec![PropertyType::String, PropertyType::Number]));
+        assert_eq!(schema.properties["createdAt"].format.as_deref(), Some("date-time"));
+    }
+
+    #[test]
+    fn test_from_json_schema_errors() {
+        let schema = |properties: Value| json!({ "type": "object", "properties": properties });
+        let errors = [
+            (json!({ "type": "array" }), "JSON Schema must have the object type"),
+            (json!({ "type": "object", "anyOf": [] }), "Unsupported JSON Schema keyword anyOf"),
+            (schema(json!({ "name": { "type": "string" } })), "Primary key id is not a property of the JSON Schema"),
+            (schema(json!({ "id": { "type": "string" }, "a/b": { "type": "string", "$ref": "#/$defs/name" } })), "Unsupported JSON Schema keyword $ref at /properties/a~1b"),
+            (schema(json!({ "id": { "type": "string" }, "meta": { "type": "object", "properties": { "note": {} } } })), "JSON Schema at /properties/meta/properties/note must have a type"),
+            (schema(json!({ "id": { "type": "string" }, "tags": { "type": "array", "items": [{ "type": "string" }] } })), "JSON Schema keyword items at /properties/tags must be a single schema"),
+            (schema(json!({ "id": { "type": "string" }, "email": { "type": "string", "format": "email" } })), "Unsupported JSON Schema format \"email\" at /properties/email"),
+            (schema(json!({ "id": { "type": "string" }, "age": { "type": "number", "exclusiveMinimum": true } })), "JSON Schema keyword exclusiveMinimum at /properties/age must be a number"),
+            (schema(json!({ "id": { "type": "string" }, "meta": { "type": "object", "additionalProperties": false, "properties": { "a": { "type": "string" } } } })), "Unsupported JSON Schema keyword additionalProperties at /properties/meta"),
+            (schema(json!({ "id": { "type": "string" }, "age": { "type": "number", "minimum": 5, "maximum": 1 } })), "Minimum higher than maximum"),
+        ];
+        for (definition, expected) in errors {
+            assert_eq!(message(from_json_schema(&definition, "id", 0)), format!("Validation Error: {}", expected));
+        }
+    }
+
+    #[test]
+    fn test_to_json_schema() {
+        let schema: Schema = serde_json::from_value(json!({
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "required": ["id"],
+            "indexes": ["name"],
+            "properties": {
+                "id": { "type": "string", "default": { "$generate": "uuid" } },
+                "name": { "type": "string", "maxLength": 60, "fullText": true },
+                "score": { "type": ["number", "string"], "nullable": true, "enum": [1, "A", null] },
+                "createdAt": { "type": "date" },
+                "embedding": { "type": "vector", "dimensions": 3 },
+                "tags": { "type": "array", "items": [{ "type": "string" }], "default": [] }
+            }
+        }))
+        .unwrap();
+        let definition = to_json_schema(&schema).unwrap();
+        assert_eq!(definition, json!({
+            "$schema": DIALECT,
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string", "maxLength": 60 },
+                "score": { "type": ["number", "string", "null"], "enum": [1, "A", null] },
+                "createdAt": { "type": "string", "format": "date-time" },
+                "embedding": { "type": "array", "items": { "type": "number" }, "minItems": 3, "maxItems": 3 },
+                "tags": { "type": "array", "items": { "type": "string" }, "default": [] }
+            }
+        }));
+
+        // Exported schemas import back, up to the settings JSON Schema cannot express
+        let imported = from_json_schema(&definition, "id", 1).unwrap();
+        assert_eq!(imported.properties["score"], schema.properties["score"]);
+        assert_eq!(imported.properties["tags"], schema.properties["tags"]);
+
+        let bytes: Schema = serde_json::from_value(json!({
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": { "id": { "type": "string" }, "key": { "type": "bytes" } }
+        }))
+        .unwrap();
+        match to_json_schema(&bytes) {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(error) => assert_eq!(error.message, "Validation Error: Cannot convert bytes property at /properties/key to JSON Schema"),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod property_type;
 pub mod property;
+pub mod json_schema;
 
 use std::collections::HashMap;
 use js_sys::{Object, Reflect, JSON};
@@ -9,7 +10,7 @@ use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen_test::{ wasm_bindgen_test};
 use crate::error::RIDBError;
-use crate::schema::property::Property;
+use crate::schema::property::{pointer, Property};
 
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT: &'static str = r#"
@@ -108,7 +109,51 @@ export class Schema<T extends SchemaType> {
      * @returns {SchemaType} The JSON representation of the schema.
      */
     toJSON(): SchemaType;
+
+    /**
+     * Creates a new `Schema` instance from a standard JSON Schema of an object type.
+     *
+     * Supports `type` (unions and `'null'` included), `properties`, `required`, `items`, length and
+     * numeric range constraints, `enum`, `const`, `pattern`, the `date` and `date-time` formats and
+     * `default`. Annotations such as `title` or `description` are dropped, any other keyword throws.
+     *
+     * @param {JSONSchema} definition - The JSON Schema.
+     * @param {JSONSchemaOptions} [options] - The primary key and version of the schema.
+     * @returns {Schema<SchemaType>} The created `Schema` instance.
+     */
+    static fromJSONSchema(definition: JSONSchema, options?: JSONSchemaOptions): Schema<SchemaType>;
+
+    /**
+     * Converts the schema to a standard JSON Schema. Date-type properties become `date-time`
+     * strings and vector-type properties arrays of numbers, while the primary key, indexes,
+     * encrypted properties, full-text indexing and default value generators are left out.
+     * Throws for schemas with bytes-type properties, which JSON has no equivalent for.
+     *
+     * @returns {JSONSchema} The JSON Schema.
+     */
+    toJSONSchema(): JSONSchema;
 }
+
+/**
+ * A standard JSON Schema.
+ */
+export type JSONSchema = {
+    [keyword: string]: unknown;
+};
+
+/**
+ * Settings of a schema created from a JSON Schema, which has no keyword for them.
+ */
+export type JSONSchemaOptions = {
+    /**
+     * The property identifying documents, `id` when omitted.
+     */
+    primaryKey?: string;
+    /**
+     * The version of the schema, 0 when omitted.
+     */
+    version?: number;
+};
 "#;
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
@@ -138,53 +183,29 @@ pub struct Schema {
 #[wasm_bindgen]
 impl Schema {
 
+    /// Validates a document against the schema, collecting every violation before failing
+    /// with a validation error that lists them, ordered by path.
     pub fn validate_schema(&self, document: JsValue) -> Result<(), JsValue> {
         let required = self.required.clone().unwrap_or(Vec::new());
         let encrypted = self.encrypted.clone().unwrap_or(Vec::new());
 
-        let properties = self.properties.clone();
-
-        for (key, prop) in properties {
-            let value = Reflect::get(&document, &JsValue::from_str(&key))
+        let mut violations = Vec::new();
+        for (key, prop) in &self.properties {
+            let value = Reflect::get(&document, &JsValue::from_str(key))
                 .map_err(|e| JsValue::from_str(&format!("Failed to get property '{}': {:?}", key, e)))?;
-
-            if value.is_undefined() {
-                if required.contains(&key) && !encrypted.contains(&key) {
-                    return Err(JsValue::from_str(&format!("Field '{}' is required", key)));
-                }
-            } else {
-                if !self.is_type_correct(&value, prop.property_type) {
-                    return Err(JsValue::from_str(&format!(
-                        "Field '{}' should be of type '{:?}'",
-                        key, prop.property_type
-                    )));
-                }
-            }
+            // Encrypted values were moved away by the encryption plugin
+            let required = required.contains(key) && !encrypted.contains(key);
+            violations.extend(prop.validate_at(&value, &pointer(key), required));
         }
-        Ok(())
-    }
-
-
-    fn is_type_correct(&self, value: &JsValue, prop_type: PropertyType) -> bool {
-        match prop_type {
-            PropertyType::String => value.is_string(),
-            PropertyType::Number => {
-                // Check if the value can be converted to an f64
-                value.as_f64().is_some()
-            },
-            PropertyType::Boolean => value.as_bool().is_some(),
-            PropertyType::Object => {
-                // Exclude null, arrays, and functions
-                value.is_object()
-                    && !value.is_null()
-                    && !js_sys::Array::is_array(value)
-            },
-            PropertyType::Array => js_sys::Array::is_array(value),
-            // Add other property types as needed
-            _ => false,
+        if violations.is_empty() {
+            return Ok(());
         }
+        // Stable, so that the violations of a value keep the order of its checks
+        violations.sort_by(|a, b| a.path.cmp(&b.path));
+        Err(JsValue::from(RIDBError::invalid(violations)))
     }
 
+
     pub fn is_valid(&self) -> Result<bool, RIDBError> {
         // Check if the schema type is "object"
         let schema_type = self.get_schema_type();
@@ -199,6 +220,21 @@ impl Schema {
             property.is_valid()?;
         }
 
+        // Encrypted values are not readable by storages, so they cannot be indexed
+        let encrypted = self.encrypted.clone().unwrap_or_default();
+        for path in full_text_paths(&self.properties) {
+            let field = path.split('.').next().unwrap_or_default();
+            if encrypted.iter().any(|key| key == field) {
+                return Err(RIDBError::validation(&format!("Cannot full-text index encrypted property: {}", path)));
+            }
+        }
+        for (path, _) in vector_index_paths(&self.properties) {
+            let field = path.split('.').next().unwrap_or_default();
+            if encrypted.iter().any(|key| key == field) {
+                return Err(RIDBError::validation(&format!("Cannot index encrypted vector property: {}", path)));
+            }
+        }
+
         Ok(true)
     }
 
@@ -222,6 +258,52 @@ impl Schema {
         }
     }
 
+    /// Creates a new `Schema` instance from a standard JSON Schema of an object type.
+    ///
+    /// # Arguments
+    ///
+    /// * `definition` - A `JsValue` representing the JSON Schema.
+    /// * `options` - The optional `{ primaryKey, version }` object, `id` and 0 by default.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Schema, JsValue>` - A result containing the new `Schema` instance or an error.
+    #[wasm_bindgen(js_name = "fromJSONSchema")]
+    pub fn from_json_schema(definition: JsValue, options: JsValue) -> Result<Schema, JsValue> {
+        let definition: serde_json::Value = from_value(definition)
+            .map_err(|e| JsValue::from(RIDBError::from(e)))?;
+        let (mut primary_key, mut version) = ("id".to_string(), 0);
+        if options.is_object() {
+            let option = Reflect::get(&options, &JsValue::from_str("primaryKey"))?;
+            if !option.is_undefined() {
+                primary_key = option
+                    .as_string()
+                    .ok_or_else(|| JsValue::from(RIDBError::validation("primaryKey must be a string")))?;
+            }
+            let option = Reflect::get(&options, &JsValue::from_str("version"))?;
+            if !option.is_undefined() {
+                version = option
+                    .as_f64()
+                    .filter(|version| version.fract() == 0.0 && *version >= 0.0)
+                    .ok_or_else(|| JsValue::from(RIDBError::validation("version must be a non-negative integer")))? as i32;
+            }
+        }
+        json_schema::from_json_schema(&definition, &primary_key, version).map_err(JsValue::from)
+    }
+
+    /// Converts the schema to a standard JSON Schema.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<JsValue, JsValue>` - A result containing the JSON Schema or an error.
+    #[wasm_bindgen(js_name = "toJSONSchema")]
+    pub fn to_json_schema(&self) -> Result<JsValue, JsValue> {
+        let definition = json_schema::to_json_schema(self).map_err(JsValue::from)?;
+        definition
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(JsValue::from)
+    }
+
     /// Retrieves the version of the schema.
     ///
     /// # Returns
@@ -293,11 +375,22 @@ impl Schema {
                 PropertyType::Boolean => "boolean",
                 PropertyType::Array => "array",
                 PropertyType::Object => "object",
+                PropertyType::Vector => "vector",
+                PropertyType::Integer => "integer",
+                PropertyType::Date => "date",
+                PropertyType::Bytes => "bytes",
                 _ => "object",
             };
 
-            // Set the 'type' field in the property object
-            Reflect::set(&prop_obj, &JsValue::from_str("type"), &JsValue::from_str(prop_type_str))?;
+            // Set the 'type' field in the property object, an array for union types
+            let prop_type = match &property.types {
+                Some(types) => types.iter().map(|prop_type| JsValue::from_str(&prop_type.to_string())).collect::<js_sys::Array>().into(),
+                None => JsValue::from_str(prop_type_str),
+            };
+            Reflect::set(&prop_obj, &JsValue::from_str("type"), &prop_type)?;
+            if property.is_nullable() {
+                Reflect::set(&prop_obj, &JsValue::from_str("nullable"), &JsValue::TRUE)?;
+            }
 
             // If you have other fields like 'maxLength', 'minLength', etc., set them here as well
             // Example:
@@ -315,6 +408,161 @@ impl Schema {
 
 }
 
+/// Resolves a dot-separated path such as `address.city` or `tags.0.name` to its property definition.
+///
+/// Object properties are descended through their nested `properties`, array properties through
+/// their `items` definition, optionally addressed with a numeric index segment.
+pub(crate) fn resolve_property_path<'a>(properties: &'a HashMap<String, Property>, path: &str) -> Option<&'a Property> {
+    let mut segments = path.split('.');
+    let mut current = properties.get(segments.next()?)?;
+    for segment in segments {
+        current = match current.property_type {
+            PropertyType::Object => current.properties.as_ref()?.get(segment)?,
+            PropertyType::Array => {
+                let item = current.items.as_ref()?.first()?;
+                if segment.parse::<u32>().is_ok() {
+                    item
+                } else if item.property_type == PropertyType::Object {
+                    item.properties.as_ref()?.get(segment)?
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Returns the dot-separated paths of the full-text indexed properties, nested objects included,
+/// sorted so that every storage indexes them in the same order.
+pub(crate) fn full_text_paths(properties: &HashMap<String, Property>) -> Vec<String> {
+    let mut paths = Vec::new();
+    for (key, property) in properties {
+        if property.full_text == Some(true) {
+            paths.push(key.clone());
+        } else if let (PropertyType::Object, Some(nested)) = (property.property_type, &property.properties) {
+            paths.extend(full_text_paths(nested).into_iter().map(|path| format!("{}.{}", key, path)));
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// Returns the dot-separated paths of the vector properties with an approximate index, nested
+/// objects included, along with their dimensions, sorted like `full_text_paths`.
+pub(crate) fn vector_index_paths(properties: &HashMap<String, Property>) -> Vec<(String, usize)> {
+    let mut paths = Vec::new();
+    for (key, property) in properties {
+        if let (PropertyType::Vector, Some(true), Some(dimensions)) = (property.property_type, property.approximate, property.dimensions) {
+            paths.push((key.clone(), dimensions as usize));
+        } else if let (PropertyType::Object, Some(nested)) = (property.property_type, &property.properties) {
+            paths.extend(
+                vector_index_paths(nested)
+                    .into_iter()
+                    .map(|(path, dimensions)| (format!("{}.{}", key, path), dimensions)),
+            );
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// Returns a copy of a document in which every property it lacks is set to its default value,
+/// nested objects included. Generated defaults are only set when `generate` is true. The document
+/// itself is left untouched.
+pub(crate) fn apply_defaults(
+    properties: &HashMap<String, Property>,
+    document: &JsValue,
+    generate: bool,
+) -> Result<JsValue, JsValue> {
+    if !document.is_object() || js_sys::Array::is_array(document) {
+        return Ok(document.clone());
+    }
+    let copy = Object::assign(&Object::new(), &Object::from(document.clone()));
+    for (key, property) in properties {
+        let value = Reflect::get(document, &JsValue::from_str(key))?;
+        if value.is_undefined() {
+            if let Some(default) = property.default_value(generate)? {
+                Reflect::set(&copy, &JsValue::from_str(key), &default)?;
+            }
+        } else if let (PropertyType::Object, Some(nested)) = (property.property_type, &property.properties) {
+            Reflect::set(&copy, &JsValue::from_str(key), &apply_defaults(nested, &value, generate)?)?;
+        }
+    }
+    Ok(copy.into())
+}
+
+/// Whether any property is of the given type, nested objects and array items included.
+pub(crate) fn has_type(properties: &HashMap<String, Property>, property_type: PropertyType) -> bool {
+    properties.values().any(|property| holds_type(property, property_type))
+}
+
+fn holds_type(property: &Property, property_type: PropertyType) -> bool {
+    if property.accepts(property_type) {
+        return true;
+    }
+    match property.property_type {
+        PropertyType::Object => property
+            .properties
+            .as_ref()
+            .is_some_and(|nested| has_type(nested, property_type)),
+        PropertyType::Array => property
+            .items
+            .as_ref()
+            .and_then(|items| items.first())
+            .is_some_and(|item| holds_type(item, property_type)),
+        _ => false,
+    }
+}
+
+/// Copies a document, converting the values of its properties of the given type, nested
+/// objects and array items included, with `convert`. Values `convert` returns `None` for are
+/// kept as is.
+///
+/// The document itself is left untouched, as storages may hold on to it.
+pub(crate) fn map_typed(
+    properties: &HashMap<String, Property>,
+    document: &JsValue,
+    property_type: PropertyType,
+    convert: &dyn Fn(&JsValue) -> Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    if !document.is_object() || js_sys::Array::is_array(document) {
+        return Ok(document.clone());
+    }
+    let copy = Object::assign(&Object::new(), &Object::from(document.clone()));
+    for (key, property) in properties {
+        if !holds_type(property, property_type) {
+            continue;
+        }
+        let value = Reflect::get(document, &JsValue::from_str(key))?;
+        if !value.is_undefined() && !value.is_null() {
+            let converted = map_typed_value(property, &value, property_type, convert)?;
+            Reflect::set(&copy, &JsValue::from_str(key), &converted)?;
+        }
+    }
+    Ok(copy.into())
+}
+
+fn map_typed_value(
+    property: &Property,
+    value: &JsValue,
+    property_type: PropertyType,
+    convert: &dyn Fn(&JsValue) -> Option<JsValue>,
+) -> Result<JsValue, JsValue> {
+    if property.accepts(property_type) {
+        return Ok(convert(value).unwrap_or_else(|| value.clone()));
+    }
+    match (property.property_type, &property.properties, property.items.as_ref().and_then(|items| items.first())) {
+        (PropertyType::Object, Some(nested), _) => map_typed(nested, value, property_type, convert),
+        (PropertyType::Array, _, Some(item)) if js_sys::Array::is_array(value) => js_sys::Array::from(value)
+            .iter()
+            .map(|element| map_typed_value(item, &element, property_type, convert))
+            .collect::<Result<js_sys::Array, JsValue>>()
+            .map(JsValue::from),
+        _ => Ok(value.clone()),
+    }
+}
 
 #[cfg(feature = "browser")]
 use wasm_bindgen_test::{wasm_bindgen_test_configure};
@@ -376,3 +624,310 @@ fn test_invalid_schema() {
     assert!(result.is_err());
 }
 
+
+#[wasm_bindgen_test]
+fn test_schema_full_text_paths() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "title": {"type": "string", "fullText": true},
+            "meta": {
+                "type": "object",
+                "properties": {
+                    "summary": {"type": "string", "fullText": true},
+                    "author": {"type": "string"}
+                }
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    assert_eq!(full_text_paths(&schema.properties), vec!["meta.summary", "title"]);
+
+    let encrypted_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "encrypted": ["title"],
+        "properties": {
+            "id": {"type": "string"},
+            "title": {"type": "string", "fullText": true}
+        }
+    }"#;
+    assert!(Schema::create(JSON::parse(encrypted_js).unwrap()).is_err());
+}
+
+fn error_message(result: Result<(), JsValue>) -> String {
+    RIDBError::from(result.unwrap_err()).message
+}
+
+#[wasm_bindgen_test]
+fn test_schema_vector_properties() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "embedding": {"type": "vector", "dimensions": 3, "approximate": true},
+            "thumbnail": {"type": "vector", "dimensions": 2}
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    assert_eq!(vector_index_paths(&schema.properties), vec![("embedding".to_string(), 3)]);
+
+    assert!(schema.validate_schema(JSON::parse(r#"{"id": "1", "embedding": [1, 0.5, -2]}"#).unwrap()).is_ok());
+    let result = schema.validate_schema(JSON::parse(r#"{"id": "1", "embedding": [1, "0.5", -2]}"#).unwrap());
+    assert_eq!(error_message(result), "Validation Error: Field embedding should be a vector of 3 numbers");
+    assert!(schema.validate_schema(JSON::parse(r#"{"id": "1", "thumbnail": [1]}"#).unwrap()).is_err());
+
+    let missing_dimensions = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "embedding": {"type": "vector"}
+        }
+    }"#;
+    assert!(Schema::create(JSON::parse(missing_dimensions).unwrap()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_schema_validate_constraints() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string", "maxLength": 60},
+            "tags": {"type": "array", "items": [{"type": "string"}], "maxItems": 2}
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    assert!(schema.validate_schema(JSON::parse(r#"{"id": "1", "tags": ["a", "b"]}"#).unwrap()).is_ok());
+
+    let long_id = JSON::parse(&format!(r#"{{"id": "{}"}}"#, "x".repeat(61))).unwrap();
+    let result = schema.validate_schema(long_id);
+    assert_eq!(error_message(result), "Validation Error: Field id should be at most 60 characters long");
+
+    let result = schema.validate_schema(JSON::parse(r#"{"id": "1", "tags": ["a", "b", "c"]}"#).unwrap());
+    assert_eq!(error_message(result), "Validation Error: Field tags should have at most 2 items");
+}
+
+#[wasm_bindgen_test]
+fn test_schema_validate_nested() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "address": {
+                "type": "object",
+                "required": ["city"],
+                "properties": {
+                    "city": {"type": "string"},
+                    "geo": {
+                        "type": "object",
+                        "properties": {"lat": {"type": "number"}, "lng": {"type": "number"}}
+                    }
+                }
+            },
+            "contacts": {
+                "type": "array",
+                "items": [{
+                    "type": "object",
+                    "required": ["email"],
+                    "properties": {"email/work": {"type": "string"}, "email": {"type": "string", "maxLength": 10}}
+                }]
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    let valid = r#"{"id": "1", "address": {"city": "Paris", "geo": {"lat": 48.8}}, "contacts": [{"email": "a@b.c"}]}"#;
+    assert!(schema.validate_schema(JSON::parse(valid).unwrap()).is_ok());
+
+    let errors = [
+        (r#"{"id": "1", "address": {}}"#, "Validation Error: Field /address/city is required"),
+        (r#"{"id": "1", "address": {"city": 1}}"#, "Validation Error: Field /address/city should match type String"),
+        (r#"{"id": "1", "address": {"city": "Paris", "geo": {"lat": "48.8"}}}"#, "Validation Error: Field /address/geo/lat should match type Number"),
+        (r#"{"id": "1", "contacts": [{"email": "a@b.c"}, {}]}"#, "Validation Error: Field /contacts/1/email is required"),
+        (r#"{"id": "1", "contacts": ["a@b.c"]}"#, "Validation Error: Field /contacts/0 should match type Object"),
+        (r#"{"id": "1", "contacts": [{"email": "someone@example.com"}]}"#, "Validation Error: Field /contacts/0/email should be at most 10 characters long"),
+        (r#"{"id": "1", "contacts": [{"email": "a@b.c", "email/work": 1}]}"#, "Validation Error: Field /contacts/0/email~1work should match type String"),
+    ];
+    for (document, message) in errors {
+        let result = schema.validate_schema(JSON::parse(document).unwrap());
+        assert_eq!(error_message(result), message);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_schema_validate_unions() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "score": {"type": ["integer", "string"], "minimum": 0, "maxLength": 3},
+            "nickname": {"type": ["string", "null"]},
+            "address": {
+                "type": "object",
+                "nullable": true,
+                "properties": {"zip": {"type": ["string", "number", "null"]}}
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    let valid = [
+        r#"{"id": "1", "score": 3, "nickname": null, "address": null}"#,
+        r#"{"id": "1", "score": "abc", "nickname": "jo", "address": {"zip": null}}"#,
+        r#"{"id": "1", "address": {"zip": 75001}}"#,
+    ];
+    for document in valid {
+        assert!(schema.validate_schema(JSON::parse(document).unwrap()).is_ok(), "{}", document);
+    }
+
+    let errors = [
+        (r#"{"id": "1", "score": true}"#, "Validation Error: Field score should match type Integer or String"),
+        (r#"{"id": "1", "score": null}"#, "Validation Error: Field score should match type Integer or String"),
+        (r#"{"id": "1", "score": -1}"#, "Validation Error: Field score should be greater than or equal to 0"),
+        (r#"{"id": "1", "score": "abcd"}"#, "Validation Error: Field score should be at most 3 characters long"),
+        (r#"{"id": "1", "address": {"zip": true}}"#, "Validation Error: Field /address/zip should match type String or Number or Null"),
+    ];
+    for (document, message) in errors {
+        let result = schema.validate_schema(JSON::parse(document).unwrap());
+        assert_eq!(error_message(result), message);
+    }
+
+    let query = crate::query::Query::new(JSON::parse(r#"{"score": {"$in": [1, "a"]}}"#).unwrap(), schema.clone()).unwrap();
+    assert!(query.parse().is_ok());
+    let query = crate::query::Query::new(JSON::parse(r#"{"score": false}"#).unwrap(), schema).unwrap();
+    assert_eq!(query.parse().err().unwrap().as_string().unwrap(), "Expected integer or string");
+}
+
+#[wasm_bindgen_test]
+fn test_schema_validate_every_violation() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "required": ["id", "name"],
+        "properties": {
+            "id": {"type": "string"},
+            "name": {"type": "string"},
+            "age": {"type": "integer", "minimum": 0},
+            "nickname": {"type": "string", "minLength": 3, "pattern": "^[a-z]+$"},
+            "address": {
+                "type": "object",
+                "required": ["city"],
+                "properties": {"city": {"type": "string"}, "zip": {"type": "string"}}
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    let document = r#"{"id": "1", "age": -1.5, "nickname": "J1", "address": {"zip": 75001}}"#;
+    let error = schema.validate_schema(JSON::parse(document).unwrap()).unwrap_err();
+
+    let violations: Vec<(String, String)> = RIDBError::from(error.clone())
+        .violations
+        .into_iter()
+        .map(|violation| (violation.path, violation.keyword))
+        .collect();
+    let expected = [
+        ("/address/city", "required"),
+        ("/address/zip", "type"),
+        ("/age", "type"),
+        ("/name", "required"),
+        ("/nickname", "minLength"),
+        ("/nickname", "pattern"),
+    ];
+    let expected: Vec<(String, String)> = expected.iter().map(|(path, keyword)| (path.to_string(), keyword.to_string())).collect();
+    assert_eq!(violations, expected);
+
+    // The violations are exposed on the thrown error, along with its code
+    let get = |value: &JsValue, key: &str| Reflect::get(value, &JsValue::from_str(key)).unwrap();
+    assert_eq!(get(&error, "code").as_string().unwrap(), "ValidationError");
+    let nickname = js_sys::Array::from(&get(&error, "violations")).get(4);
+    assert_eq!(get(&nickname, "expected").as_f64(), Some(3.0));
+    assert_eq!(get(&nickname, "actual").as_f64(), Some(2.0));
+    assert_eq!(get(&nickname, "message").as_string().unwrap(), "should be at least 3 characters long");
+    let age = js_sys::Array::from(&get(&error, "violations")).get(2);
+    assert_eq!(get(&age, "expected").as_string().unwrap(), "Integer");
+    assert_eq!(get(&age, "actual").as_string().unwrap(), "Number");
+}
+
+#[wasm_bindgen_test]
+fn test_schema_apply_defaults() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string", "default": {"$generate": "uuid"}},
+            "status": {"type": "string", "default": "draft"},
+            "tags": {"type": "array", "items": [{"type": "string"}], "default": []},
+            "secret": {"type": "string", "default": "hidden"},
+            "meta": {
+                "type": "object",
+                "properties": {"views": {"type": "number", "default": 0}}
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    let original = JSON::parse(r#"{"status": "published", "meta": {}}"#).unwrap();
+    let document = apply_defaults(&schema.properties, &original, true).unwrap();
+
+    let get = |value: &JsValue, key: &str| Reflect::get(value, &JsValue::from_str(key)).unwrap();
+    // The given document, nested objects included, is left untouched
+    assert_eq!(JSON::stringify(&original).unwrap().as_string().unwrap(), r#"{"status":"published","meta":{}}"#);
+    assert_eq!(get(&document, "id").as_string().unwrap().len(), 36);
+    assert_eq!(get(&document, "status").as_string().unwrap(), "published");
+    assert_eq!(js_sys::Array::from(&get(&document, "tags")).length(), 0);
+    assert_eq!(get(&document, "secret").as_string().unwrap(), "hidden");
+    assert_eq!(get(&get(&document, "meta"), "views").as_f64().unwrap(), 0.0);
+    assert!(schema.validate_schema(document).is_ok());
+
+    let document = apply_defaults(&schema.properties, &JSON::parse("{}").unwrap(), false).unwrap();
+    assert!(get(&document, "id").is_undefined());
+    assert_eq!(get(&document, "status").as_string().unwrap(), "draft");
+}
+
+#[wasm_bindgen_test]
+fn test_schema_map_typed() {
+    let schema_js = r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "createdAt": {"type": "date"},
+            "history": {
+                "type": "array",
+                "items": [{"type": "object", "properties": {"at": {"type": "date"}}}]
+            }
+        }
+    }"#;
+    let schema = Schema::create(JSON::parse(schema_js).unwrap()).unwrap();
+    assert!(has_type(&schema.properties, PropertyType::Date));
+    assert!(!has_type(&schema.properties, PropertyType::Bytes));
+    let document = JSON::parse(r#"{"id": "1", "createdAt": "2024-01-31", "history": [{"at": "2024-02-01T10:00:00+02:00"}]}"#).unwrap();
+    let normalized = map_typed(&schema.properties, &document, PropertyType::Date, &|value| {
+        crate::utils::to_iso_date(value).map(JsValue::from)
+    }).unwrap();
+    assert!(schema.validate_schema(normalized.clone()).is_ok());
+
+    let get = |value: &JsValue, key: &str| Reflect::get(value, &JsValue::from_str(key)).unwrap();
+    assert_eq!(get(&normalized, "createdAt").as_string().unwrap(), "2024-01-31T00:00:00.000Z");
+    let entry = js_sys::Array::from(&get(&normalized, "history")).get(0);
+    assert_eq!(get(&entry, "at").as_string().unwrap(), "2024-02-01T08:00:00.000Z");
+    // The original document is left untouched
+    assert_eq!(get(&document, "createdAt").as_string().unwrap(), "2024-01-31");
+
+    let result = schema.validate_schema(JSON::parse(r#"{"id": "1", "createdAt": "someday"}"#).unwrap());
+    assert_eq!(error_message(result), "Validation Error: Field createdAt should be a valid date");
+}
@@ -2,13 +2,17 @@
 extern crate wasm_bindgen_test;
 
 use std::collections::HashMap;
-use js_sys::{ JSON};
-use serde::{Deserialize, Serialize};
+use js_sys::{Array, JSON, Reflect};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde::ser::Error as SerError;
 use serde_wasm_bindgen::to_value;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen::prelude::wasm_bindgen;
 use crate::error::RIDBError;
 use crate::schema::property_type::PropertyType;
+use crate::storage::internals::vector::to_vector;
+use crate::utils::{build_regex, generate_uuid, is_bytes, parse_date, to_iso_date};
 
 
 #[wasm_bindgen(typescript_custom_section)]
@@ -18,9 +22,15 @@ const TS_APPEND_CONTENT: &'static str = r#"
  */
 export class Property {
     /**
-     * The type of the property.
+     * The type of the property, or a union of types such as `['string', 'number']`. Listing
+     * `'null'` makes the property nullable.
      */
-    readonly type: string;
+    readonly type: string | readonly string[];
+
+    /**
+     * Whether the property accepts `null` values.
+     */
+    readonly nullable?: boolean;
 
     /**
      * The version of the property, if applicable.
@@ -58,9 +68,76 @@ export class Property {
     readonly minLength?: number;
 
     /**
-     * An optional array of required fields for object-type properties.
+     * The format of string-type properties: `date` and `date-time` values compare as dates in
+     * range queries, other strings compare lexicographically.
      */
-    readonly required?: boolean;
+    readonly format?: 'date' | 'date-time';
+
+    /**
+     * Whether string-type properties are full-text indexed, so that `$text` queries search them.
+     */
+    readonly fullText?: boolean;
+
+    /**
+     * The number of components of vector-type properties, required for them.
+     */
+    readonly dimensions?: number;
+
+    /**
+     * Whether `$near` queries on vector-type properties read their candidates from an
+     * approximate index instead of every document. Worth it for large collections, at the cost
+     * of sometimes missing a close neighbour.
+     */
+    readonly approximate?: boolean;
+
+    /**
+     * The values the property is restricted to, if applicable.
+     */
+    readonly enum?: readonly unknown[];
+
+    /**
+     * The inclusive lower bound of number-type and integer-type properties, if applicable.
+     */
+    readonly minimum?: number;
+
+    /**
+     * The inclusive upper bound of number-type and integer-type properties, if applicable.
+     */
+    readonly maximum?: number;
+
+    /**
+     * The exclusive lower bound of number-type and integer-type properties, if applicable.
+     */
+    readonly exclusiveMinimum?: number;
+
+    /**
+     * The exclusive upper bound of number-type and integer-type properties, if applicable.
+     */
+    readonly exclusiveMaximum?: number;
+
+    /**
+     * The number values of number-type and integer-type properties are multiples of, if applicable.
+     */
+    readonly multipleOf?: number;
+
+    /**
+     * A regular expression values of string-type properties match, anywhere in the string
+     * unless anchored with `^` and `$`.
+     */
+    readonly pattern?: string;
+
+    /**
+     * The value of the property in documents written or migrated without it: either a literal
+     * value, or `{ $generate: 'now' }` for the current date (an ISO string, or milliseconds
+     * since the epoch on number properties) or `{ $generate: 'uuid' }` for a random UUID.
+     */
+    readonly default?: unknown;
+
+    /**
+     * An optional array of required fields for object-type properties. `false` instead marks
+     * the property itself as optional in document types.
+     */
+    readonly required?: boolean | string[];
 
     /**
      * An optional map of nested properties for object-type properties.
@@ -74,6 +151,7 @@ export class Property {
 
 #[wasm_bindgen(skip_typescript)]
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(remote = "Self")]
 /// Represents a property within a schema, including type, items, length constraints, and other attributes.
 pub struct Property {
     /// The type of the property.
@@ -96,6 +174,10 @@ pub struct Property {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) properties: Option<HashMap<String, Property>>,
 
+    /// Optional required nested properties for object-type properties.
+    #[serde(default, deserialize_with = "deserialize_required", skip_serializing_if = "Option::is_none")]
+    pub(crate) required: Option<Vec<String>>,
+
     /// Optional maximum length for string-type properties.
     #[serde(rename = "maxLength", skip_serializing_if = "Option::is_none")]
     pub(crate) max_length: Option<i32>,
@@ -103,6 +185,575 @@ pub struct Property {
     /// Optional minimum length for string-type properties.
     #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
     pub(crate) min_length: Option<i32>,
+
+    /// Optional format for string-type properties, `date` or `date-time`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) format: Option<String>,
+
+    /// Whether the string-type property is full-text indexed.
+    #[serde(rename = "fullText", skip_serializing_if = "Option::is_none")]
+    pub(crate) full_text: Option<bool>,
+
+    /// The number of components of vector-type properties.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dimensions: Option<i32>,
+
+    /// Whether the vector-type property has an approximate nearest neighbours index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) approximate: Option<bool>,
+
+    /// Optional default value, a literal or a `{ "$generate": ... }` generator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) default: Option<serde_json::Value>,
+
+    /// Optional values the property is restricted to.
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub(crate) enum_values: Option<Vec<serde_json::Value>>,
+
+    /// Optional inclusive lower bound for number-type and integer-type properties.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) minimum: Option<f64>,
+
+    /// Optional inclusive upper bound for number-type and integer-type properties.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) maximum: Option<f64>,
+
+    /// Optional exclusive lower bound for number-type and integer-type properties.
+    #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
+    pub(crate) exclusive_minimum: Option<f64>,
+
+    /// Optional exclusive upper bound for number-type and integer-type properties.
+    #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
+    pub(crate) exclusive_maximum: Option<f64>,
+
+    /// Optional number that values of number-type and integer-type properties are multiples of.
+    #[serde(rename = "multipleOf", skip_serializing_if = "Option::is_none")]
+    pub(crate) multiple_of: Option<f64>,
+
+    /// Optional regular expression that values of string-type properties match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) pattern: Option<String>,
+
+    /// Every type of a union-type property, `property_type` being the first of them.
+    #[serde(skip)]
+    pub(crate) types: Option<Vec<PropertyType>>,
+
+    /// Whether the property accepts `null` values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) nullable: Option<bool>,
+}
+
+impl<'de> Deserialize<'de> for Property {
+    /// Deserializes a property, whose `type` may be a union of types such as
+    /// `["string", "number", "null"]`: the property takes the first of them other than `null`
+    /// as its type, and listing `null` makes it nullable.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut definition = serde_json::Value::deserialize(deserializer)?;
+        let mut types = Vec::new();
+        if let Some(serde_json::Value::Array(union)) = definition.get("type") {
+            let mut nullable = false;
+            for member in union.clone() {
+                if member == "null" {
+                    nullable = true;
+                    continue;
+                }
+                let property_type = PropertyType::deserialize(member).map_err(D::Error::custom)?;
+                if !types.contains(&property_type) {
+                    types.push(property_type);
+                }
+            }
+            let Some(first) = types.first() else {
+                return Err(D::Error::custom("Union type must list at least one type other than null"));
+            };
+            definition["type"] = serde_json::Value::from(first.to_string());
+            if nullable {
+                definition["nullable"] = serde_json::Value::Bool(true);
+            }
+        }
+        let mut property = Property::deserialize(definition).map_err(D::Error::custom)?;
+        if types.len() > 1 {
+            property.types = Some(types);
+        }
+        Ok(property)
+    }
+}
+
+impl Serialize for Property {
+    /// Serializes a property, the types of union-type properties as an array.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(types) = &self.types else {
+            return Property::serialize(self, serializer);
+        };
+        let mut definition = Property::serialize(self, serde_json::value::Serializer).map_err(S::Error::custom)?;
+        definition["type"] = serde_json::to_value(types).map_err(S::Error::custom)?;
+        definition.serialize(serializer)
+    }
+}
+
+impl Property {
+    /// The types values of the property may have, several for union-type properties.
+    pub(crate) fn accepted_types(&self) -> Vec<PropertyType> {
+        self.types.clone().unwrap_or_else(|| vec![self.property_type])
+    }
+
+    /// Whether values of the property may have the given type.
+    pub(crate) fn accepts(&self, property_type: PropertyType) -> bool {
+        self.accepted_types().contains(&property_type)
+    }
+
+    pub(crate) fn is_nullable(&self) -> bool {
+        self.nullable == Some(true)
+    }
+
+    /// Names the types of the property for error messages, as in `String or Null`.
+    pub(crate) fn type_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.accepted_types().iter().map(|property_type| format!("{:?}", property_type)).collect();
+        if self.is_nullable() {
+            names.push("Null".to_string());
+        }
+        names
+    }
+
+    /// Names the types of the property for error messages, as in `'String' or 'Null'`.
+    pub(crate) fn quoted_type_names(&self) -> String {
+        let names: Vec<String> = self.type_names().iter().map(|name| format!("'{}'", name)).collect();
+        names.join(" or ")
+    }
+
+    /// Whether values of the property, or of its array items, hold dates: date-type values, or
+    /// strings with a date format.
+    pub(crate) fn is_date(&self) -> bool {
+        let property = if self.property_type == PropertyType::Array {
+            match self.items.as_ref().and_then(|items| items.first()) {
+                Some(item) => item,
+                None => return false,
+            }
+        } else {
+            self
+        };
+        property.accepts(PropertyType::Date)
+            || matches!(property.format.as_deref(), Some("date") | Some("date-time"))
+    }
+
+    /// Checks a document value, already known to have the type of the property, against the
+    /// constraints of the property. Returns every constraint the value breaks, as in
+    /// "should be at most 60 characters long", located at the value itself.
+    ///
+    /// Lengths count Unicode code points, like JSON Schema does, rather than UTF-16 code units.
+    pub(crate) fn check_value(&self, value: &JsValue) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Some(values) = &self.enum_values {
+            if !values.iter().any(|expected| json_equals(value, expected)) {
+                let names: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                violations.push(Violation::new(
+                    "enum",
+                    serde_json::Value::from(values.clone()),
+                    to_json(value),
+                    format!("should be one of {}", names.join(", ")),
+                ));
+            }
+        }
+        // Each type of a union only checks the values of that type
+        for property_type in self.accepted_types() {
+            self.check_typed_value(property_type, value, &mut violations);
+        }
+        violations
+    }
+
+    fn check_typed_value(&self, property_type: PropertyType, value: &JsValue, violations: &mut Vec<Violation>) {
+        match property_type {
+            PropertyType::String => {
+                let Some(string) = value.as_string() else {
+                    return;
+                };
+                // Dates are compared as instants, so they have to parse
+                if self.is_date() && parse_date(&string).is_none() {
+                    let format = self.format.clone().unwrap_or_else(|| "date-time".to_string());
+                    violations.push(Violation::new("format", format, string.clone(), "should be a valid date".to_string()));
+                }
+                let length = string.chars().count();
+                if let Some(min) = self.min_length.filter(|min| length < *min as usize) {
+                    violations.push(Violation::new(
+                        "minLength",
+                        min,
+                        length,
+                        format!("should be at least {} long", count(min, "character")),
+                    ));
+                }
+                if let Some(max) = self.max_length.filter(|max| *max >= 0 && length > *max as usize) {
+                    violations.push(Violation::new(
+                        "maxLength",
+                        max,
+                        length,
+                        format!("should be at most {} long", count(max, "character")),
+                    ));
+                }
+                if let Some(pattern) = &self.pattern {
+                    let message = match build_regex(pattern, "u") {
+                        Ok(regex) if regex.test(&string) => None,
+                        Ok(_) => Some(format!("should match pattern {}", pattern)),
+                        Err(_) => Some(format!("cannot match invalid pattern {}", pattern)),
+                    };
+                    if let Some(message) = message {
+                        violations.push(Violation::new("pattern", pattern.as_str(), string, message));
+                    }
+                }
+            }
+            PropertyType::Number | PropertyType::Integer => {
+                let Some(number) = value.as_f64() else {
+                    return;
+                };
+                let mut check = |keyword: &str, bound: Option<f64>, breaks: fn(f64, f64) -> bool, message: &str| {
+                    if let Some(bound) = bound.filter(|bound| breaks(number, *bound)) {
+                        violations.push(Violation::new(keyword, bound, number, format!("{} {}", message, bound)));
+                    }
+                };
+                check("minimum", self.minimum, |number, minimum| number < minimum, "should be greater than or equal to");
+                check("exclusiveMinimum", self.exclusive_minimum, |number, minimum| number <= minimum, "should be greater than");
+                check("maximum", self.maximum, |number, maximum| number > maximum, "should be less than or equal to");
+                check("exclusiveMaximum", self.exclusive_maximum, |number, maximum| number >= maximum, "should be less than");
+                // Tolerates the rounding of decimal multiples such as 0.1
+                check("multipleOf", self.multiple_of, |number, multiple| {
+                    let quotient = number / multiple;
+                    (quotient - quotient.round()).abs() > 1e-9
+                }, "should be a multiple of");
+            }
+            PropertyType::Array => {
+                if !Array::is_array(value) {
+                    return;
+                }
+                let length = Array::from(value).length() as usize;
+                if let Some(min) = self.min_items.filter(|min| length < *min as usize) {
+                    violations.push(Violation::new(
+                        "minItems",
+                        min,
+                        length,
+                        format!("should have at least {}", count(min, "item")),
+                    ));
+                }
+                if let Some(max) = self.max_items.filter(|max| *max >= 0 && length > *max as usize) {
+                    violations.push(Violation::new(
+                        "maxItems",
+                        max,
+                        length,
+                        format!("should have at most {}", count(max, "item")),
+                    ));
+                }
+            }
+            PropertyType::Date if (value.is_string() || value.is_instance_of::<js_sys::Date>()) && to_iso_date(value).is_none() => {
+                violations.push(Violation::new("format", "date-time", to_json(value), "should be a valid date".to_string()));
+            }
+            PropertyType::Vector => {
+                let dimensions = self.dimensions.unwrap_or_default() as usize;
+                if to_vector(value, dimensions).is_none() {
+                    violations.push(Violation::new(
+                        "dimensions",
+                        dimensions,
+                        to_json(value),
+                        format!("should be a vector of {} numbers", dimensions),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that a default value is a generator the property supports, or a literal of the
+    /// type of the property.
+    fn check_default(&self, default: &serde_json::Value) -> Result<(), RIDBError> {
+        if let Some(generator) = generator(default) {
+            return match (generator.as_str(), self.property_type) {
+                (Some("now"), PropertyType::String | PropertyType::Number | PropertyType::Integer | PropertyType::Date) | (Some("uuid"), PropertyType::String) => Ok(()),
+                (Some(name @ ("now" | "uuid")), property_type) => Err(RIDBError::validation(&format!(
+                    "The {} default generator is not supported on {} properties",
+                    name, property_type
+                ))),
+                _ => Err(RIDBError::validation(&format!("Unsupported default generator: {}", generator))),
+            };
+        }
+        if self.is_literal(default) {
+            Ok(())
+        } else {
+            Err(RIDBError::validation(&format!("Default value should be of type {}", self.quoted_type_names())))
+        }
+    }
+
+    /// Whether a literal of a schema definition has one of the types of the property, or is
+    /// `null` for nullable properties.
+    fn is_literal(&self, literal: &serde_json::Value) -> bool {
+        (literal.is_null() && self.is_nullable())
+            || self.accepted_types().into_iter().any(|property_type| is_literal_of(property_type, literal))
+    }
+
+    /// Checks the numeric bounds of the property, which must leave some number valid.
+    fn check_bounds(&self) -> Result<(), RIDBError> {
+        let bounds = [self.minimum, self.maximum, self.exclusive_minimum, self.exclusive_maximum, self.multiple_of];
+        if bounds.iter().all(Option::is_none) {
+            return Ok(());
+        }
+        if !self.accepted_types().iter().any(PropertyType::is_numeric) {
+            return Err(RIDBError::validation("Numeric bounds are only supported on number and integer properties"));
+        }
+        if bounds.iter().flatten().any(|bound| !bound.is_finite()) {
+            return Err(RIDBError::validation("Numeric bounds must be finite numbers"));
+        }
+        if self.multiple_of.is_some_and(|multiple| multiple <= 0.0) {
+            return Err(RIDBError::validation("multipleOf must be a positive number"));
+        }
+        let lower = self.minimum.into_iter().chain(self.exclusive_minimum).fold(f64::NEG_INFINITY, f64::max);
+        let upper = self.maximum.into_iter().chain(self.exclusive_maximum).fold(f64::INFINITY, f64::min);
+        let exclusive = self.exclusive_minimum == Some(lower) || self.exclusive_maximum == Some(upper);
+        if lower > upper || (lower == upper && exclusive) {
+            return Err(RIDBError::validation("Minimum higher than maximum"));
+        }
+        Ok(())
+    }
+
+    /// Computes the default value of the property, `None` when it has none, or when it is
+    /// generated and `generate` is false.
+    pub(crate) fn default_value(&self, generate: bool) -> Result<Option<JsValue>, JsValue> {
+        let Some(default) = &self.default else {
+            return Ok(None);
+        };
+        match generator(default).and_then(|generator| generator.as_str()) {
+            Some(_) if !generate => Ok(None),
+            Some("now") if self.property_type.is_numeric() => Ok(Some(JsValue::from_f64(js_sys::Date::now()))),
+            Some("now") => Ok(Some(js_sys::Date::new_0().to_iso_string().into())),
+            Some("uuid") => Ok(Some(JsValue::from_str(&generate_uuid()?))),
+            Some(generator) => Err(JsValue::from_str(&format!("Unsupported default generator: {}", generator))),
+            // Parsed from JSON, so that every call returns a distinct object or array
+            None => JSON::parse(&default.to_string()).map(Some),
+        }
+    }
+
+    /// Validates a document value against the property: its constraints and, recursively, the
+    /// properties of objects and the items of arrays, nested required properties included.
+    /// Returns every violation found, located relative to the value.
+    ///
+    /// The value itself must already be known to have the type of the property, nested values
+    /// are type checked here.
+    pub(crate) fn validate(&self, value: &JsValue) -> Vec<Violation> {
+        if value.is_null() {
+            return Vec::new();
+        }
+        let mut violations = self.check_value(value);
+        match self.property_type {
+            PropertyType::Object if !Array::is_array(value) => {
+                let Some(properties) = &self.properties else {
+                    return violations;
+                };
+                let required = self.required.clone().unwrap_or_default();
+                // Sorted, so that the same document always reports violations in the same order
+                let mut keys: Vec<&String> = properties.keys().collect();
+                keys.sort();
+                for key in keys {
+                    let nested = Reflect::get(value, &JsValue::from_str(key)).unwrap_or_default();
+                    violations.extend(properties[key].validate_at(&nested, &pointer(key), required.contains(key)));
+                }
+            }
+            PropertyType::Array if Array::is_array(value) => {
+                if let Some(item) = self.items.as_ref().and_then(|items| items.first()) {
+                    for (index, nested) in Array::from(value).iter().enumerate() {
+                        violations.extend(item.validate_at(&nested, &format!("/{}", index), true));
+                    }
+                }
+            }
+            _ => {}
+        }
+        violations
+    }
+
+    /// Validates a value found at `pointer` in a document or in an enclosing value, `undefined`
+    /// when absent: its presence and type, then everything `validate` checks. Returns every
+    /// violation found, located under `pointer`.
+    pub(crate) fn validate_at(&self, value: &JsValue, pointer: &str, required: bool) -> Vec<Violation> {
+        let violations = match self.check_presence(value, required) {
+            Some(violation) => vec![violation],
+            None if value.is_undefined() => Vec::new(),
+            None => self.validate(value),
+        };
+        violations.into_iter().map(|violation| violation.nest(pointer)).collect()
+    }
+
+    /// Checks that a document value is given when `required`, and has one of the types of the
+    /// property when given. Returns the violation otherwise, located at the value itself.
+    fn check_presence(&self, value: &JsValue, required: bool) -> Option<Violation> {
+        if value.is_undefined() {
+            return required.then(|| {
+                Violation::new("required", self.expected_types(), type_of(value), "is required".to_string())
+            });
+        }
+        if self.is_type_correct(value) {
+            return None;
+        }
+        Some(Violation::new(
+            "type",
+            self.expected_types(),
+            type_of(value),
+            format!("should match type {}", self.type_names().join(" or ")),
+        ))
+    }
+
+    /// The types of the property as violations expect them: a name, or a list of names for
+    /// unions and nullable properties.
+    fn expected_types(&self) -> serde_json::Value {
+        match self.type_names().as_slice() {
+            [name] => serde_json::Value::from(name.as_str()),
+            names => serde_json::Value::from(names.to_vec()),
+        }
+    }
+
+    /// Whether a value has one of the types of the property, or is `null` for nullable
+    /// properties.
+    fn is_type_correct(&self, value: &JsValue) -> bool {
+        (value.is_null() && self.is_nullable())
+            || self.accepted_types().into_iter().any(|property_type| has_type(property_type, value))
+    }
+}
+
+/// Names the type of a document value the way violations do, as in `String` or `Undefined`.
+fn type_of(value: &JsValue) -> &'static str {
+    if value.is_undefined() {
+        "Undefined"
+    } else if value.is_null() {
+        "Null"
+    } else if value.is_string() {
+        "String"
+    } else if value.as_f64().is_some() {
+        "Number"
+    } else if value.as_bool().is_some() {
+        "Boolean"
+    } else if Array::is_array(value) {
+        "Array"
+    } else if is_bytes(value) {
+        "Bytes"
+    } else if value.is_instance_of::<js_sys::Date>() {
+        "Date"
+    } else {
+        "Object"
+    }
+}
+
+/// Whether a value has the given type.
+fn has_type(property_type: PropertyType, value: &JsValue) -> bool {
+    match property_type {
+        PropertyType::String => value.is_string(),
+        PropertyType::Number => value.as_f64().is_some(),
+        PropertyType::Integer => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        PropertyType::Date => value.is_string() || value.is_instance_of::<js_sys::Date>(),
+        PropertyType::Bytes => is_bytes(value),
+        PropertyType::Boolean => value.as_bool().is_some(),
+        PropertyType::Object => value.is_object() && !Array::is_array(value),
+        PropertyType::Array | PropertyType::Vector => Array::is_array(value),
+        _ => false,
+    }
+}
+
+/// Reads the required nested properties of an object-type property, ignoring the `required`
+/// flags TypeScript document types read.
+fn deserialize_required<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None | Some(serde_json::Value::Bool(_)) => Ok(None),
+        Some(properties) => Vec::<String>::deserialize(properties).map(Some).map_err(D::Error::custom),
+    }
+}
+
+/// A document value breaking a rule of its schema, located by a JSON pointer.
+///
+/// Properties locate violations relative to the value they validate, schemas relative to the
+/// document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    /// Empty when the validated value itself is invalid, as in `/address/city` otherwise.
+    pub path: String,
+    /// The schema keyword the value breaks, as in `maxLength`.
+    pub keyword: String,
+    /// What the keyword expects, as in the maximum length.
+    pub expected: serde_json::Value,
+    /// What the value holds instead, as in its length.
+    pub actual: serde_json::Value,
+    /// What is wrong with the value, as in "is required".
+    pub message: String,
+}
+
+impl Violation {
+    fn new(keyword: &str, expected: impl Into<serde_json::Value>, actual: impl Into<serde_json::Value>, message: String) -> Violation {
+        Violation {
+            path: String::new(),
+            keyword: keyword.to_string(),
+            expected: expected.into(),
+            actual: actual.into(),
+            message,
+        }
+    }
+
+    /// Prefixes the path with the location of the property it was found in.
+    fn nest(self, pointer: &str) -> Violation {
+        Violation { path: format!("{}{}", pointer, self.path), ..self }
+    }
+
+    /// Names the invalid field of a document in messages: the key of top-level properties,
+    /// the JSON pointer of nested values.
+    pub(crate) fn field(&self) -> String {
+        match self.path.strip_prefix('/') {
+            Some(key) if !key.contains('/') => key.replace("~1", "/").replace("~0", "~"),
+            _ => self.path.clone(),
+        }
+    }
+}
+
+/// Whether a document value equals a literal of a schema definition.
+fn json_equals(value: &JsValue, expected: &serde_json::Value) -> bool {
+    match expected {
+        serde_json::Value::Null => value.is_null(),
+        serde_json::Value::Bool(expected) => value.as_bool() == Some(*expected),
+        serde_json::Value::Number(expected) => value.as_f64() == expected.as_f64(),
+        serde_json::Value::String(expected) => value.as_string().as_deref() == Some(expected.as_str()),
+        // Objects and arrays are compared in their JSON form
+        _ => &to_json(value) == expected,
+    }
+}
+
+/// Converts a document value into its JSON form, `null` when it has none.
+fn to_json(value: &JsValue) -> serde_json::Value {
+    JSON::stringify(value)
+        .ok()
+        .and_then(|json| json.as_string())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Whether a literal of a schema definition has the given type.
+fn is_literal_of(property_type: PropertyType, literal: &serde_json::Value) -> bool {
+    match property_type {
+        PropertyType::String | PropertyType::Date => literal.is_string(),
+        PropertyType::Number => literal.is_number(),
+        PropertyType::Integer => literal.as_f64().is_some_and(|number| number.fract() == 0.0),
+        PropertyType::Boolean => literal.is_boolean(),
+        PropertyType::Object => literal.is_object(),
+        PropertyType::Array | PropertyType::Vector => literal.is_array(),
+        _ => false,
+    }
+}
+
+/// The `$generate` option of a default value, if it is a generator.
+fn generator(default: &serde_json::Value) -> Option<&serde_json::Value> {
+    default.as_object().and_then(|default| default.get("$generate"))
+}
+
+/// JSON pointer to a property, escaping `~` and `/` as RFC 6901 does.
+pub(crate) fn pointer(key: &str) -> String {
+    format!("/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+
+/// Formats a count along with its noun, as in "1 item" or "2 items".
+fn count(count: i32, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
 }
 
 #[wasm_bindgen]
@@ -114,6 +765,56 @@ impl Property {
     /// Throws exception if not valid
     #[wasm_bindgen]
     pub fn is_valid(&self) -> Result<bool, RIDBError> {
+        if let Some(format) = &self.format {
+            if self.property_type != PropertyType::String {
+                return Err(RIDBError::validation("Format is only supported on string properties"));
+            }
+            if format != "date" && format != "date-time" {
+                return Err(RIDBError::validation(&format!("Unsupported format: {}", format)));
+            }
+        }
+        if self.full_text == Some(true) && self.property_type != PropertyType::String {
+            return Err(RIDBError::validation("Full-text indexing is only supported on string properties"));
+        }
+        if self.property_type != PropertyType::Vector {
+            if self.dimensions.is_some() {
+                return Err(RIDBError::validation("Dimensions are only supported on vector properties"));
+            }
+            if self.approximate == Some(true) {
+                return Err(RIDBError::validation("Approximate indexing is only supported on vector properties"));
+            }
+        }
+        if let Some(types) = &self.types {
+            let scalar = |property_type: &PropertyType| matches!(
+                property_type,
+                PropertyType::String | PropertyType::Number | PropertyType::Integer | PropertyType::Boolean | PropertyType::Date | PropertyType::Bytes
+            );
+            if !types.iter().all(scalar) {
+                return Err(RIDBError::validation("Union types may only combine string, number, integer, boolean, date and bytes types"));
+            }
+            // Both hold strings, which could not tell dates from other strings
+            if types.contains(&PropertyType::String) && types.contains(&PropertyType::Date) {
+                return Err(RIDBError::validation("Union types cannot combine string and date types"));
+            }
+        }
+        if let Some(default) = &self.default {
+            self.check_default(default)?;
+        }
+        if let Some(values) = &self.enum_values {
+            if values.is_empty() {
+                return Err(RIDBError::validation("Enum must list at least one value"));
+            }
+            if !values.iter().all(|value| self.is_literal(value)) {
+                return Err(RIDBError::validation(&format!("Enum values should be of type {}", self.quoted_type_names())));
+            }
+        }
+        self.check_bounds()?;
+        if let Some(pattern) = &self.pattern {
+            if self.property_type != PropertyType::String {
+                return Err(RIDBError::validation("Pattern is only supported on string properties"));
+            }
+            build_regex(pattern, "u").map_err(|_| RIDBError::validation(&format!("Invalid pattern: {}", pattern)))?;
+        }
         match self.property_type {
             PropertyType::String => {
                 let min = self.min_length.unwrap_or_else(|| 0);
@@ -126,14 +827,14 @@ impl Property {
                     Ok(true)
                 }
             },
-            PropertyType::Number => Ok(true),
-            PropertyType::Boolean => Ok(true),
+            PropertyType::Number | PropertyType::Integer => Ok(true),
+            PropertyType::Boolean | PropertyType::Date | PropertyType::Bytes => Ok(true),
             PropertyType::Array => match self.clone().items {
                 Some(items) => {
                     let item = items.first();
                     match item {
                         Some(p) => {
-                            let is_valid = p.is_valid().unwrap();
+                            let is_valid = p.is_valid()?;
                             match is_valid {
                                 true => {
                                     let min = self.min_items.unwrap_or_else(|| 0);
@@ -157,6 +858,9 @@ impl Property {
             PropertyType::Object => match self.clone().properties {
                 Some(props) => {
                     if props.len() > 0 {
+                        for prop in props.values() {
+                            prop.is_valid()?;
+                        }
                         Ok(true)
                     } else {
                         Err(RIDBError::validation("Properties empty"))
@@ -164,6 +868,10 @@ impl Property {
                 },
                 _ => Err(RIDBError::validation("Properties empty"))
             },
+            PropertyType::Vector => match self.dimensions {
+                Some(dimensions) if dimensions > 0 => Ok(true),
+                _ => Err(RIDBError::validation("Vector dimensions must be a positive integer"))
+            },
             _ => Err(RIDBError::validation("Property type invalid"))
         }
     }
@@ -227,6 +935,46 @@ impl Property {
         Ok(to_value(&self.min_length).map_err(|e| JsValue::from(RIDBError::from(e)))?)
     }
 
+    /// Retrieves the format of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The format, if any.
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> Option<String> {
+        self.format.clone()
+    }
+
+    /// Retrieves whether the property is full-text indexed.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<bool>` - The full-text flag, if any.
+    #[wasm_bindgen(getter, js_name = "fullText")]
+    pub fn full_text(&self) -> Option<bool> {
+        self.full_text
+    }
+
+    /// Retrieves the number of components of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i32>` - The dimensions, if any.
+    #[wasm_bindgen(getter)]
+    pub fn dimensions(&self) -> Option<i32> {
+        self.dimensions
+    }
+
+    /// Retrieves whether the property has an approximate index.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<bool>` - The approximate flag, if any.
+    #[wasm_bindgen(getter)]
+    pub fn approximate(&self) -> Option<bool> {
+        self.approximate
+    }
+
     /// Retrieves the nested properties of the property.
     ///
     /// # Returns
@@ -236,6 +984,112 @@ impl Property {
     pub fn properties(&self) -> Result<JsValue, JsValue> {
         Ok(to_value(&self.properties).map_err(|e| JsValue::from(RIDBError::from(e)))?)
     }
+
+    /// Retrieves the default value of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<JsValue, JsValue>` - A result containing the default value, `undefined` if none, or an error.
+    #[wasm_bindgen(getter, js_name = "default")]
+    pub fn get_default(&self) -> Result<JsValue, JsValue> {
+        match &self.default {
+            Some(default) => JSON::parse(&default.to_string()),
+            None => Ok(JsValue::undefined()),
+        }
+    }
+
+    /// Retrieves the values the property is restricted to.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<JsValue, JsValue>` - A result containing the values, `undefined` if none, or an error.
+    #[wasm_bindgen(getter, js_name = "enum")]
+    pub fn enum_values(&self) -> Result<JsValue, JsValue> {
+        match &self.enum_values {
+            Some(values) => JSON::parse(&serde_json::Value::from(values.clone()).to_string()),
+            None => Ok(JsValue::undefined()),
+        }
+    }
+
+    /// Retrieves the inclusive lower bound of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The minimum, if any.
+    #[wasm_bindgen(getter)]
+    pub fn minimum(&self) -> Option<f64> {
+        self.minimum
+    }
+
+    /// Retrieves the inclusive upper bound of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The maximum, if any.
+    #[wasm_bindgen(getter)]
+    pub fn maximum(&self) -> Option<f64> {
+        self.maximum
+    }
+
+    /// Retrieves the exclusive lower bound of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The exclusive minimum, if any.
+    #[wasm_bindgen(getter, js_name = "exclusiveMinimum")]
+    pub fn exclusive_minimum(&self) -> Option<f64> {
+        self.exclusive_minimum
+    }
+
+    /// Retrieves the exclusive upper bound of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The exclusive maximum, if any.
+    #[wasm_bindgen(getter, js_name = "exclusiveMaximum")]
+    pub fn exclusive_maximum(&self) -> Option<f64> {
+        self.exclusive_maximum
+    }
+
+    /// Retrieves the number values of the property are multiples of.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The multiple, if any.
+    #[wasm_bindgen(getter, js_name = "multipleOf")]
+    pub fn multiple_of(&self) -> Option<f64> {
+        self.multiple_of
+    }
+
+    /// Retrieves the regular expression values of the property match.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The pattern, if any.
+    #[wasm_bindgen(getter)]
+    pub fn pattern(&self) -> Option<String> {
+        self.pattern.clone()
+    }
+
+    /// Retrieves whether the property accepts `null` values.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the property is nullable.
+    #[wasm_bindgen(getter)]
+    pub fn nullable(&self) -> bool {
+        self.is_nullable()
+    }
+
+    /// Retrieves the required nested properties of the property.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<JsValue, JsValue>` - A result containing the required property names or an error.
+    #[wasm_bindgen(getter)]
+    pub fn required(&self) -> Result<JsValue, JsValue> {
+        to_value(&self.required).map_err(|e| JsValue::from(RIDBError::from(e)))
+    }
 }
 
 
@@ -251,7 +1105,8 @@ wasm_bindgen_test_configure!(run_in_browser);
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use crate::schema::property::Property;
+    use crate::error::RIDBError;
+    use crate::schema::property::{pointer, Property, Violation};
     use crate::schema::property_type::PropertyType;
 
     #[test]
@@ -264,6 +1119,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         assert_eq!(default_property.property_type, PropertyType::String);
         assert!(default_property.items.is_none());
@@ -285,6 +1155,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         // Test default values to ensure proper initialization
         assert_eq!(default_property.property_type, PropertyType::Array);
@@ -312,6 +1197,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let default_property = Property {
             property_type: PropertyType::Array,
@@ -321,6 +1221,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property.is_valid();
         match result {
@@ -339,6 +1254,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
 
         let default_property2 = Property {
@@ -349,6 +1279,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property2.is_valid();
         // Check the result for an error message
@@ -369,6 +1314,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
 
         let default_property2 = Property {
@@ -379,6 +1339,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property2.is_valid();
         // Check the result for an error message
@@ -399,6 +1374,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property2.is_valid();
         // Check the result for an error message
@@ -418,6 +1408,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property2.is_valid();
         // Check the result for an error message
@@ -437,6 +1442,21 @@ mod tests {
             max_length: Some(1),
             min_length: Some(2),
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property2.is_valid();
         // Check the result for an error message
@@ -456,6 +1476,21 @@ mod tests {
             max_length: Some(1),
             min_length: Some(-1),
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         };
         let result = default_property2.is_valid();
         // Check the result for an error message
@@ -465,6 +1500,229 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_property_format_requires_string() {
+        let result = Property {
+            property_type: PropertyType::Number,
+            items: None,
+            max_items: None,
+            min_items: None,
+            max_length: None,
+            min_length: None,
+            properties: None,
+            format: Some("date".to_string()),
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
+        }.is_valid();
+        match result {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(js_val) => assert_eq!(js_val.message, "Validation Error: Format is only supported on string properties")
+        }
+    }
+
+    #[test]
+    fn test_property_format_unsupported() {
+        let result = Property {
+            property_type: PropertyType::String,
+            items: None,
+            max_items: None,
+            min_items: None,
+            max_length: None,
+            min_length: None,
+            properties: None,
+            format: Some("email".to_string()),
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
+        }.is_valid();
+        match result {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(js_val) => assert_eq!(js_val.message, "Validation Error: Unsupported format: email")
+        }
+    }
+
+    #[test]
+    fn test_property_full_text_requires_string() {
+        let result = Property {
+            property_type: PropertyType::Number,
+            items: None,
+            max_items: None,
+            min_items: None,
+            max_length: None,
+            min_length: None,
+            properties: None,
+            format: None,
+            full_text: Some(true),
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
+        }.is_valid();
+        match result {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(js_val) => assert_eq!(js_val.message, "Validation Error: Full-text indexing is only supported on string properties")
+        }
+    }
+
+    #[test]
+    fn test_property_vector_dimensions() {
+        let vector = |dimensions: Option<i32>| Property {
+            property_type: PropertyType::Vector,
+            items: None,
+            max_items: None,
+            min_items: None,
+            max_length: None,
+            min_length: None,
+            properties: None,
+            format: None,
+            full_text: None,
+            dimensions,
+            approximate: Some(true),
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
+        };
+        assert!(vector(Some(3)).is_valid().unwrap());
+        for dimensions in [None, Some(0)] {
+            match vector(dimensions).is_valid() {
+                Ok(_) => panic!("Expected an error, but got Ok"),
+                Err(js_val) => assert_eq!(js_val.message, "Validation Error: Vector dimensions must be a positive integer")
+            }
+        }
+    }
+
+    #[test]
+    fn test_property_default() {
+        let property = |property_type: PropertyType, default: serde_json::Value| Property {
+            property_type,
+            items: None,
+            max_items: None,
+            min_items: None,
+            max_length: None,
+            min_length: None,
+            properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: Some(default),
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
+        };
+        assert!(property(PropertyType::String, serde_json::json!("draft")).is_valid().unwrap());
+        assert!(property(PropertyType::Number, serde_json::json!({ "$generate": "now" })).is_valid().unwrap());
+        assert!(property(PropertyType::String, serde_json::json!({ "$generate": "uuid" })).is_valid().unwrap());
+
+        let errors = [
+            (PropertyType::Number, serde_json::json!("0"), "Default value should be of type 'Number'"),
+            (PropertyType::Boolean, serde_json::json!({ "$generate": "now" }), "The now default generator is not supported on boolean properties"),
+            (PropertyType::Number, serde_json::json!({ "$generate": "uuid" }), "The uuid default generator is not supported on number properties"),
+            (PropertyType::String, serde_json::json!({ "$generate": "random" }), "Unsupported default generator: \"random\""),
+        ];
+        for (property_type, default, message) in errors {
+            match property(property_type, default).is_valid() {
+                Ok(_) => panic!("Expected an error, but got Ok"),
+                Err(js_val) => assert_eq!(js_val.message, format!("Validation Error: {}", message))
+            }
+        }
+    }
+
+    #[test]
+    fn test_property_numeric_constraints() {
+        let number = |property_type: PropertyType, minimum: Option<f64>, exclusive_maximum: Option<f64>, multiple_of: Option<f64>| Property {
+            property_type,
+            items: None,
+            max_items: None,
+            min_items: None,
+            max_length: None,
+            min_length: None,
+            properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: Some(vec![serde_json::json!(1), serde_json::json!(2)]),
+            minimum,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum,
+            multiple_of,
+            pattern: None,
+            types: None,
+            nullable: None,
+        };
+        assert!(number(PropertyType::Integer, Some(0.0), Some(10.0), Some(2.0)).is_valid().unwrap());
+
+        let errors = [
+            (number(PropertyType::String, Some(0.0), None, None), "Enum values should be of type 'String'"),
+            (number(PropertyType::Integer, Some(10.0), Some(10.0), None), "Minimum higher than maximum"),
+            (number(PropertyType::Number, None, None, Some(0.0)), "multipleOf must be a positive number"),
+        ];
+        for (property, message) in errors {
+            match property.is_valid() {
+                Ok(_) => panic!("Expected an error, but got Ok"),
+                Err(js_val) => assert_eq!(js_val.message, format!("Validation Error: {}", message))
+            }
+        }
+
+        let mut bounded_string = number(PropertyType::String, Some(0.0), None, None);
+        bounded_string.enum_values = None;
+        match bounded_string.is_valid() {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(js_val) => assert_eq!(js_val.message, "Validation Error: Numeric bounds are only supported on number and integer properties")
+        }
+    }
+
     #[test]
     fn test_property_object_no_props_err() {
         let result = Property {
@@ -475,6 +1733,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: None,
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         }.is_valid();
         // Check the result for an error message
         match result {
@@ -493,6 +1766,21 @@ mod tests {
             max_length: None,
             min_length: None,
             properties: Some(HashMap::new()),
+            format: None,
+            full_text: None,
+            dimensions: None,
+            approximate: None,
+            required: None,
+            default: None,
+            enum_values: None,
+            minimum: None,
+            maximum: None,
+            exclusive_minimum: None,
+            exclusive_maximum: None,
+            multiple_of: None,
+            pattern: None,
+            types: None,
+            nullable: None,
         }.is_valid();
         // Check the result for an error message
         match result {
@@ -501,6 +1789,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_property_array_invalid_items_err() {
+        let property: Property = serde_json::from_value(serde_json::json!({
+            "type": "array",
+            "items": [{ "type": "string", "minLength": -1 }]
+        })).unwrap();
+        match property.is_valid() {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(error) => assert_eq!(error.message, "Validation Error: Min property not valid")
+        }
+    }
+
+    #[test]
+    fn test_property_object_invalid_nested_property_err() {
+        let property: Property = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string" },
+                "status": { "type": "string", "enum": [] }
+            }
+        })).unwrap();
+        match property.is_valid() {
+            Ok(_) => panic!("Expected an error, but got Ok"),
+            Err(error) => assert_eq!(error.message, "Validation Error: Enum must list at least one value")
+        }
+    }
+
+    #[test]
+    fn test_property_union_types() {
+        let property: Property = serde_json::from_value(serde_json::json!({ "type": ["number", "string", "null"] })).unwrap();
+        assert_eq!(property.property_type, PropertyType::Number);
+        assert_eq!(property.types, Some(vec![PropertyType::Number, PropertyType::String]));
+        assert!(property.is_nullable());
+        assert_eq!(property.quoted_type_names(), "'Number' or 'String' or 'Null'");
+        // Union types serialize back as arrays, null being kept as the nullable flag
+        assert_eq!(
+            serde_json::to_value(&property).unwrap(),
+            serde_json::json!({ "type": ["number", "string"], "nullable": true })
+        );
+
+        let nullable: Property = serde_json::from_value(serde_json::json!({ "type": ["string", "null"] })).unwrap();
+        assert_eq!(nullable.property_type, PropertyType::String);
+        assert_eq!(nullable.types, None);
+        assert!(nullable.is_nullable());
+        assert_eq!(serde_json::to_value(&nullable).unwrap(), serde_json::json!({ "type": "string", "nullable": true }));
+
+        let only_null = serde_json::from_value::<Property>(serde_json::json!({ "type": ["null"] }));
+        assert!(only_null.unwrap_err().to_string().contains("Union type must list at least one type other than null"));
+
+        let errors = [
+            (serde_json::json!({ "type": ["object", "null", "string"], "properties": { "a": { "type": "string" } } }), "Union types may only combine string, number, integer, boolean, date and bytes types"),
+            (serde_json::json!({ "type": ["date", "string"] }), "Union types cannot combine string and date types"),
+            (serde_json::json!({ "type": ["string", "number"], "enum": ["a", true] }), "Enum values should be of type 'String' or 'Number'"),
+        ];
+        for (definition, message) in errors {
+            let property: Property = serde_json::from_value(definition).unwrap();
+            match property.is_valid() {
+                Ok(_) => panic!("Expected an error, but got Ok"),
+                Err(js_val) => assert_eq!(js_val.message, format!("Validation Error: {}", message))
+            }
+        }
+        // Bounds apply to the numeric type of the union, null fits nullable enums
+        let bounded: Property = serde_json::from_value(serde_json::json!({ "type": ["string", "integer", "null"], "minimum": 0, "enum": ["a", 1, null] })).unwrap();
+        assert!(bounded.is_valid().unwrap());
+    }
+
+    #[test]
+    fn test_violation_fields() {
+        let violations = vec![
+            Violation::new("maxLength", 10, 12, "should be at most 10 characters long".to_string()).nest(&pointer("email/work")),
+            Violation::new("required", "String", "Undefined", "is required".to_string()).nest("/city").nest("/address"),
+            Violation::new("type", vec!["Integer", "Null"], "Boolean", "should match type Integer or Null".to_string()).nest("/contacts/0"),
+        ];
+        // Top-level keys are named as is, nested values by their JSON pointer
+        let fields: Vec<String> = violations.iter().map(Violation::field).collect();
+        assert_eq!(fields, vec!["email/work", "/address/city", "/contacts/0"]);
+        assert_eq!(
+            serde_json::to_value(&violations[0]).unwrap(),
+            serde_json::json!({
+                "path": "/email~1work",
+                "keyword": "maxLength",
+                "expected": 10,
+                "actual": 12,
+                "message": "should be at most 10 characters long"
+            })
+        );
+
+        let error = RIDBError::invalid(violations);
+        assert_eq!(
+            error.message,
+            "Validation Error: Field email/work should be at most 10 characters long; Field /address/city is required; Field /contacts/0 should match type Integer or Null"
+        );
+        assert_eq!(error.violations.len(), 3);
+    }
+
 }
 
 
@@ -543,4 +1926,54 @@ fn test_invalid_property() {
     let property = serde_wasm_bindgen::from_value::<Property>(property_value).unwrap();
 
     assert!(property.is_valid().is_err());
-}
\ No newline at end of file
+}
+#[wasm_bindgen_test]
+fn test_property_check_value() {
+    let property = |definition: &str| serde_wasm_bindgen::from_value::<Property>(JSON::parse(definition).unwrap()).unwrap();
+    let messages = |violations: Vec<Violation>| -> Vec<String> { violations.into_iter().map(|violation| violation.message).collect() };
+
+    let name = property(r#"{ "type": "string", "minLength": 2, "maxLength": 4 }"#);
+    assert!(name.check_value(&JsValue::from_str("ab")).is_empty());
+    // Lengths count code points, "ééé" is 3 characters long
+    assert!(name.check_value(&JsValue::from_str("ééé")).is_empty());
+    assert_eq!(messages(name.check_value(&JsValue::from_str("a"))), ["should be at least 2 characters long"]);
+    assert_eq!(messages(name.check_value(&JsValue::from_str("abcde"))), ["should be at most 4 characters long"]);
+
+    let tags = property(r#"{ "type": "array", "items": [{ "type": "string" }], "minItems": 1, "maxItems": 2 }"#);
+    assert!(tags.check_value(&JSON::parse(r#"["a"]"#).unwrap()).is_empty());
+    assert_eq!(messages(tags.check_value(&JSON::parse("[]").unwrap())), ["should have at least 1 item"]);
+    assert_eq!(messages(tags.check_value(&JSON::parse(r#"["a", "b", "c"]"#).unwrap())), ["should have at most 2 items"]);
+
+    let date = property(r#"{ "type": "string", "format": "date" }"#);
+    assert_eq!(messages(date.check_value(&JsValue::from_str("tomorrow"))), ["should be a valid date"]);
+}
+
+#[wasm_bindgen_test]
+fn test_property_check_constraints() {
+    let property = |definition: &str| serde_wasm_bindgen::from_value::<Property>(JSON::parse(definition).unwrap()).unwrap();
+    let messages = |violations: Vec<Violation>| -> Vec<String> { violations.into_iter().map(|violation| violation.message).collect() };
+
+    let status = property(r#"{ "type": "string", "enum": ["draft", "published"] }"#);
+    assert!(status.is_valid().unwrap());
+    assert!(status.check_value(&JsValue::from_str("draft")).is_empty());
+    assert_eq!(messages(status.check_value(&JsValue::from_str("archived"))), [r#"should be one of "draft", "published""#]);
+
+    let email = property(r#"{ "type": "string", "pattern": "^[^@\\s]+@[^@\\s]+$" }"#);
+    assert!(email.is_valid().unwrap());
+    assert!(email.check_value(&JsValue::from_str("jane@example.com")).is_empty());
+    assert_eq!(messages(email.check_value(&JsValue::from_str("jane"))), [r"should match pattern ^[^@\s]+@[^@\s]+$"]);
+    let invalid = property(r#"{ "type": "string", "pattern": "(" }"#);
+    assert_eq!(invalid.is_valid().unwrap_err().message, "Validation Error: Invalid pattern: (");
+
+    let age = property(r#"{ "type": "integer", "minimum": 0, "exclusiveMaximum": 150 }"#);
+    assert!(age.is_valid().unwrap());
+    assert!(age.check_value(&JsValue::from_f64(0.0)).is_empty());
+    assert_eq!(messages(age.check_value(&JsValue::from_f64(-1.0))), ["should be greater than or equal to 0"]);
+    assert_eq!(messages(age.check_value(&JsValue::from_f64(150.0))), ["should be less than 150"]);
+
+    let price = property(r#"{ "type": "number", "exclusiveMinimum": 0, "maximum": 100, "multipleOf": 0.01 }"#);
+    assert!(price.check_value(&JsValue::from_f64(19.99)).is_empty());
+    assert_eq!(messages(price.check_value(&JsValue::from_f64(0.0))), ["should be greater than 0"]);
+    assert_eq!(messages(price.check_value(&JsValue::from_f64(100.5))), ["should be less than or equal to 100"]);
+    assert_eq!(messages(price.check_value(&JsValue::from_f64(1.005))), ["should be a multiple of 0.01"]);
+}
@@ -12,9 +12,27 @@ pub enum PropertyType {
     Boolean="boolean",
     Array="array",
     Object="object",
+    Vector="vector",
+    Integer="integer",
+    Date="date",
+    Bytes="bytes",
 }
 
 
+impl PropertyType {
+    /// Whether values of the type are numbers, integers included.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, PropertyType::Number | PropertyType::Integer)
+    }
+}
+
+impl fmt::Display for PropertyType {
+    /// Formats a `PropertyType` as the string used in schema definitions.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.to_str())
+    }
+}
+
 impl Serialize for PropertyType {
     /// Serializes a `PropertyType` into a string value.
     ///
@@ -35,6 +53,10 @@ impl Serialize for PropertyType {
             PropertyType::Boolean => serializer.serialize_str("boolean"),
             PropertyType::Array => serializer.serialize_str("array"),
             PropertyType::Object => serializer.serialize_str("object"),
+            PropertyType::Vector => serializer.serialize_str("vector"),
+            PropertyType::Integer => serializer.serialize_str("integer"),
+            PropertyType::Date => serializer.serialize_str("date"),
+            PropertyType::Bytes => serializer.serialize_str("bytes"),
             _ => Err(SerError::custom("Wrong key")),
         }
     }
@@ -74,7 +96,7 @@ impl<'de> Visitor<'de> for PropertyTypeVisitor {
     ///
     /// * `fmt::Result` - A result indicating success or failure.
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("an PropertyType (String, Number, Boolean, Object or Array)")
+        formatter.write_str("an PropertyType (String, Number, Boolean, Object, Array, Vector, Integer, Date or Bytes)")
     }
 
     fn visit_i32<E>(self, value: i32) -> Result<Self::Value, E>
@@ -87,6 +109,10 @@ impl<'de> Visitor<'de> for PropertyTypeVisitor {
             2 => Ok(PropertyType::Boolean),
             3 => Ok(PropertyType::Array),
             4 => Ok(PropertyType::Object),
+            5 => Ok(PropertyType::Vector),
+            6 => Ok(PropertyType::Integer),
+            7 => Ok(PropertyType::Date),
+            8 => Ok(PropertyType::Bytes),
             _ => Err(E::invalid_value(de::Unexpected::Str("Wrong key"), &self)),
         }
     }
@@ -110,6 +136,10 @@ impl<'de> Visitor<'de> for PropertyTypeVisitor {
             "boolean" => Ok(PropertyType::Boolean),
             "array" => Ok(PropertyType::Array),
             "object" => Ok(PropertyType::Object),
+            "vector" => Ok(PropertyType::Vector),
+            "integer" => Ok(PropertyType::Integer),
+            "date" => Ok(PropertyType::Date),
+            "bytes" => Ok(PropertyType::Bytes),
             _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
         }
     }
@@ -1,5 +1,6 @@
 use wasm_bindgen::JsValue;
 use crate::query::Query;
+use crate::query::options::QueryOptions;
 
 use wasm_bindgen::prelude::wasm_bindgen;
 use crate::operation::Operation;
@@ -22,7 +23,8 @@ export abstract class StorageInternal<Schemas extends SchemaTypeRecord> {
     abstract close(): Promise<void>;
     abstract count(
         colectionName: keyof Schemas, 
-        query: QueryType<Schemas[keyof Schemas]>
+        query: QueryType<Schemas[keyof Schemas]>,
+        options?: QueryOptions<Schemas[keyof Schemas]>
     ): Promise<number>;
     abstract findDocumentById(
         collectionName: keyof Schemas, 
@@ -30,8 +32,25 @@ export abstract class StorageInternal<Schemas extends SchemaTypeRecord> {
     ): Promise<Doc<Schemas[keyof Schemas]> | null>;
     abstract find(
         collectionName: keyof Schemas, 
-        query: QueryType<Schemas[keyof Schemas]>
+        query: QueryType<Schemas[keyof Schemas]>,
+        options?: QueryOptions<Schemas[keyof Schemas]>
     ): Promise<Doc<Schemas[keyof Schemas]>[]>;
+    /**
+     * Optional report of how a query is answered, used by `Collection.explain`.
+     */
+    explain?(
+        collectionName: keyof Schemas,
+        query: QueryType<Schemas[keyof Schemas]>,
+        options?: QueryOptions<Schemas[keyof Schemas]>
+    ): Promise<ExplainResult>;
+    /**
+     * Optional aggregation pushdown, documents are aggregated in memory when it is not implemented.
+     */
+    aggregate?(
+        collectionName: keyof Schemas,
+        query: QueryType<Schemas[keyof Schemas]>,
+        options: AggregateOptions<Schemas[keyof Schemas]>
+    ): Promise<AggregateResult[]>;
     abstract write(
         op: Operation<Schemas[keyof Schemas]>
     ): Promise<Doc<Schemas[keyof Schemas]>>;
@@ -48,13 +67,19 @@ extern "C" {
     pub async fn write(this: &StorageExternal, op: Operation) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, catch)]
-    pub async fn find(this: &StorageExternal, collection_name: &str, query: JsValue) -> Result<JsValue, JsValue>;
+    pub async fn find(this: &StorageExternal, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub async fn explain(this: &StorageExternal, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub async fn aggregate(this: &StorageExternal, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, catch, js_name="findDocumentById")]
     pub async fn find_document_by_id(this: &StorageExternal, collection_name: &str, primary_key:JsValue) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, catch)]
-    pub async fn count(this: &StorageExternal, collection_name: &str, query: JsValue) -> Result<JsValue, JsValue>;
+    pub async fn count(this: &StorageExternal, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, catch)]
     pub async fn close(this: &StorageExternal) -> Result<JsValue, JsValue>;
@@ -67,9 +92,10 @@ extern "C" {
 //Represents a rust storage
 pub trait Storage {
     async fn write(&self, op: &Operation) -> Result<JsValue, JsValue>;
-    async fn find(&self, collection_name: &str, query: Query) -> Result<JsValue, JsValue>;
+    async fn find(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue>;
     async fn find_document_by_id(&self, collection_name: &str, primary_key:JsValue) -> Result<JsValue, JsValue>;
-    async fn count(&self, collection_name: &str, query: Query) -> Result<JsValue, JsValue>;
+    async fn count(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue>;
+    async fn explain(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue>;
     async fn close(&self) -> Result<JsValue, JsValue>;
     async fn start(&mut self) -> Result<JsValue, JsValue>;
 
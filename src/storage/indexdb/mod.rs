@@ -1,16 +1,23 @@
-use js_sys::{Array, Object, Promise, Reflect};
-use serde_wasm_bindgen::to_value;
+use js_sys::{Array, Object, Promise, Reflect, JSON};
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen::prelude::{wasm_bindgen, Closure};
 use wasm_bindgen_futures::JsFuture;
 use crate::query::Query;
+use crate::query::options::QueryOptions;
+use crate::query::plan::{Execution, Explain, QueryPlan};
+use crate::query::predicate::{FieldPath, NearSearch, Predicate, TextSearch, Value};
+use crate::schema::{full_text_paths, vector_index_paths, Schema};
+use crate::storage::internals::fulltext::{rank, DocumentTerms};
+use crate::storage::internals::vector::Hyperplanes;
 use crate::storage::internals::base_storage::BaseStorage;
 use crate::storage::internals::core::CoreStorage;
 use crate::operation::{OpType, Operation};
-use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest};
+use web_sys::{IdbCursorWithValue, IdbDatabase, IdbFactory, IdbIndexParameters, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransaction};
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Weak;
 use lazy_static::lazy_static;
 
@@ -58,7 +65,14 @@ impl Drop for IndexDB {
 }
 
 async fn idb_request_result(request: IdbRequest) -> Result<JsValue, JsValue> {
-    let promise = Promise::new(&mut |resolve, reject| {
+    JsFuture::from(request_promise(&request)).await
+}
+
+/// Wraps a request into a promise of its result.
+///
+/// Handlers are attached right away, so several requests can be issued before awaiting any.
+fn request_promise(request: &IdbRequest) -> Promise {
+    Promise::new(&mut |resolve, reject| {
 
         let reject2 = reject.clone();
         let success_callback = Closure::once(Box::new(move |event: web_sys::Event| {
@@ -89,9 +103,263 @@ async fn idb_request_result(request: IdbRequest) -> Result<JsValue, JsValue> {
         // The closures will automatically be dropped after the Promise resolves/rejects
         success_callback.forget();
         error_callback.forget();
+    })
+}
+
+/// Walks a cursor request and collects the documents that match the query, along with the
+/// number of documents read.
+///
+/// The cursor stops as soon as `scan_limit` matches are collected, so paginated reads without
+/// a sort do not load the rest of the store.
+async fn collect_matches(
+    request: IdbRequest,
+    core: CoreStorage,
+    predicate: Predicate,
+    scan_limit: Option<usize>,
+) -> Result<(Vec<JsValue>, usize), JsValue> {
+    if scan_limit == Some(0) {
+        return Ok((Vec::new(), 0));
+    }
+    let scanned = Rc::new(Cell::new(0));
+    let mut handlers = None;
+    let promise = Promise::new(&mut |resolve, reject| {
+        let core = core.clone();
+        let predicate = predicate.clone();
+        let scanned = scanned.clone();
+        let matched = Array::new();
+        let fail = reject.clone();
+        let onsuccess = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            let request: IdbRequest = event.target().unwrap().dyn_into().unwrap();
+            let result = request.result().unwrap();
+            if result.is_undefined() || result.is_null() {
+                // The cursor went past the last document
+                resolve.call1(&JsValue::undefined(), &matched).unwrap();
+                return;
+            }
+            let cursor: IdbCursorWithValue = result.dyn_into().unwrap();
+            let doc = cursor.value().unwrap();
+            scanned.set(scanned.get() + 1);
+            match core.matches(&doc, &predicate) {
+                Ok(true) => {
+                    matched.push(&doc);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    reject.call1(&JsValue::undefined(), &e).unwrap();
+                    return;
+                }
+            }
+            if scan_limit.is_some_and(|limit| matched.length() as usize >= limit) {
+                resolve.call1(&JsValue::undefined(), &matched).unwrap();
+            } else if let Err(e) = cursor.continue_() {
+                reject.call1(&JsValue::undefined(), &e).unwrap();
+            }
+        }) as Box<dyn FnMut(web_sys::Event)>);
+
+        // Aborted transactions, exceeded quotas and version changes fail the cursor
+        let onerror = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            let request: IdbRequest = event.target().unwrap().dyn_into().unwrap();
+            let error = match request.error() {
+                Ok(Some(error)) => JsValue::from(error),
+                _ => JsValue::from_str("Failed to read documents"),
+            };
+            fail.call1(&JsValue::undefined(), &error).unwrap();
+        }) as Box<dyn FnMut(web_sys::Event)>);
+
+        request.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+        request.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+        handlers = Some((onsuccess, onerror));
     });
 
-    JsFuture::from(promise).await
+    let matched = JsFuture::from(promise).await;
+    // The cursor is not continued once the promise settled, so its handlers can go
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    drop(handlers);
+    let matched = matched?;
+    Ok((Array::from(&matched).to_vec(), scanned.get()))
+}
+
+/// Reads the candidates of the plan and returns those matching the predicate, along with the
+/// number of documents read. The scan stops once `scan_limit` documents matched.
+///
+/// Full-text plans, and nearest neighbours plans over an approximate index, read the index store
+/// of the collection, which has to be part of the same transaction.
+async fn scan_store(
+    store: &IdbObjectStore,
+    index: Option<&IdbObjectStore>,
+    core: &CoreStorage,
+    predicate: &Predicate,
+    plan: &QueryPlan,
+    primary_key: &str,
+    scan_limit: Option<usize>,
+) -> Result<(Vec<JsValue>, usize), JsValue> {
+    let keys: Vec<JsValue> = match plan {
+        QueryPlan::PrimaryKey { keys, .. } => keys.iter().map(Value::to_js).collect(),
+        QueryPlan::FullText(search) => {
+            let full_text = index.ok_or_else(|| JsValue::from_str("Full-text store not found"))?;
+            search_full_text(full_text, search).await?
+        }
+        QueryPlan::Nearest(search) => {
+            // Any match may be among the nearest, so every candidate is read
+            let (mut matched, mut scanned) = (Vec::new(), 0);
+            if search.approximate {
+                let vectors = index.ok_or_else(|| JsValue::from_str("Vector store not found"))?;
+                let keys = search_vectors(vectors, search).await?;
+                (matched, scanned) = match_keys(store, core, predicate, &keys, None).await?;
+            }
+            if matched.len() < search.k {
+                // Too few candidates shared a bucket with the searched vector, every document is ranked.
+                // The full scan reads the candidates again, which are only counted once.
+                (matched, scanned) = collect_matches(store.open_cursor()?, core.clone(), predicate.clone(), None).await?;
+            }
+            return Ok((core.nearest(matched, search, primary_key)?, scanned));
+        }
+        QueryPlan::Scan => {
+            return collect_matches(store.open_cursor()?, core.clone(), predicate.clone(), scan_limit).await;
+        }
+    };
+    match_keys(store, core, predicate, &keys, scan_limit).await
+}
+
+/// Looks up documents by primary key and returns those matching the predicate, in the order of
+/// the keys, along with the number of documents read.
+async fn match_keys(
+    store: &IdbObjectStore,
+    core: &CoreStorage,
+    predicate: &Predicate,
+    keys: &[JsValue],
+    scan_limit: Option<usize>,
+) -> Result<(Vec<JsValue>, usize), JsValue> {
+    // Every lookup is issued before awaiting, within the same transaction
+    let requests = Array::new();
+    for key in keys {
+        requests.push(&request_promise(&store.get(key)?));
+    }
+    let documents = JsFuture::from(Promise::all(&requests)).await?;
+
+    let mut matched = Vec::new();
+    let mut scanned = 0;
+    for doc in Array::from(&documents).iter() {
+        if doc.is_undefined() {
+            continue;
+        }
+        if scan_limit.is_some_and(|limit| matched.len() >= limit) {
+            break;
+        }
+        scanned += 1;
+        if core.matches(&doc, predicate)? {
+            matched.push(doc);
+        }
+    }
+    Ok((matched, scanned))
+}
+
+/// Reads the full-text records holding any of the searched terms and returns the primary keys
+/// of the matching documents, most relevant first.
+async fn search_full_text(store: &IdbObjectStore, search: &TextSearch) -> Result<Vec<JsValue>, JsValue> {
+    let index = store.index(FULL_TEXT_INDEX)?;
+    let requests = Array::new();
+    for term in &search.terms {
+        requests.push(&request_promise(&index.get_all_with_key(&JsValue::from_str(term))?));
+    }
+    // Every document has a record, so the store also tells the size of the collection
+    requests.push(&request_promise(&store.count()?));
+    let results = Array::from(&JsFuture::from(Promise::all(&requests)).await?);
+    let total = results.pop().as_f64().unwrap_or(0.0) as usize;
+
+    // Records holding several of the terms are read once per term
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    for records in results.iter() {
+        for record in Array::from(&records).iter() {
+            let (key, terms) = DocumentTerms::from_record(&record)?;
+            if seen.insert(String::from(JSON::stringify(&key)?)) {
+                candidates.push((key, terms));
+            }
+        }
+    }
+    Ok(rank(search, candidates.iter().map(|(key, terms)| (key.clone(), terms)).collect(), total))
+}
+
+/// Name of the object store holding the full-text index of a collection.
+fn full_text_store(collection_name: &str) -> String {
+    format!("{}__fullText", collection_name)
+}
+
+/// Name of the multi-entry index over the terms of full-text records.
+const FULL_TEXT_INDEX: &str = "terms";
+
+/// Reads the primary keys of the documents sharing a bucket with the searched vector.
+async fn search_vectors(store: &IdbObjectStore, search: &NearSearch) -> Result<Vec<JsValue>, JsValue> {
+    let index = store.index(VECTOR_INDEX)?;
+    let buckets = Hyperplanes::new(search.vector.len()).buckets(&search.field.path, &search.vector);
+    let requests = Array::new();
+    for bucket in &buckets {
+        requests.push(&request_promise(&index.get_all_keys_with_key(&JsValue::from_str(bucket))?));
+    }
+    let results = JsFuture::from(Promise::all(&requests)).await?;
+
+    // Documents sharing several buckets are listed once per bucket
+    let mut seen = HashSet::new();
+    let mut keys = Vec::new();
+    for bucket_keys in Array::from(&results).iter() {
+        for key in Array::from(&bucket_keys).iter() {
+            if seen.insert(String::from(JSON::stringify(&key)?)) {
+                keys.push(key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Converts the buckets of a document into the record of the vector store.
+fn vector_record(buckets: Vec<String>) -> Result<JsValue, JsValue> {
+    let record = Object::new();
+    let buckets: Array = buckets.iter().map(|bucket| JsValue::from_str(bucket)).collect();
+    Reflect::set(&record, &JsValue::from_str(VECTOR_INDEX), &buckets)?;
+    Ok(record.into())
+}
+
+/// Name of the object store holding the approximate vector index of a collection.
+fn vector_store(collection_name: &str) -> String {
+    format!("{}__vectors", collection_name)
+}
+
+/// Name of the multi-entry index over the buckets of vector records.
+const VECTOR_INDEX: &str = "buckets";
+
+/// The stores of a collection, along with the paths its index stores are built from.
+#[derive(Debug, Clone)]
+struct CollectionStores {
+    name: String,
+    /// The full-text indexed properties, the collection has a full-text store unless empty.
+    full_text: Vec<String>,
+    /// The approximately indexed vector properties and their dimensions, the collection has a
+    /// vector store unless empty.
+    vectors: Vec<(String, usize)>,
+}
+
+impl CollectionStores {
+    fn new(name: &str, schema: &Schema) -> CollectionStores {
+        CollectionStores {
+            name: name.to_string(),
+            full_text: full_text_paths(&schema.properties),
+            vectors: vector_index_paths(&schema.properties),
+        }
+    }
+
+    /// Names of the stores that have to exist for the collection.
+    fn store_names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        if !self.full_text.is_empty() {
+            names.push(full_text_store(&self.name));
+        }
+        if !self.vectors.is_empty() {
+            names.push(vector_store(&self.name));
+        }
+        names
+    }
 }
 
 impl Storage for IndexDB {
@@ -110,8 +378,14 @@ impl Storage for IndexDB {
             stores, store_name
         )));
 
-        let transaction = match self.db.transaction_with_str_and_mode(
-            store_name,
+        let schema = self.base.schemas.get(op.collection.as_str()).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let collection = CollectionStores::new(store_name, schema);
+        let fields: Vec<FieldPath> = collection.full_text.iter().map(|path| FieldPath::new(path)).collect();
+
+        // Indexes are written in the same transaction as the documents
+        let transaction_stores: Array = collection.store_names().iter().map(|name| JsValue::from_str(name)).collect();
+        let transaction = match self.db.transaction_with_str_sequence_and_mode(
+            &transaction_stores,
             web_sys::IdbTransactionMode::Readwrite,
         ) {
             Ok(t) => t,
@@ -131,8 +405,16 @@ impl Storage for IndexDB {
             Ok(s) => s,
             Err(e) => return Err(e),
         };
-
-        let schema = self.base.schemas.get(op.collection.as_str()).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let full_text = if fields.is_empty() {
+            None
+        } else {
+            Some(transaction.object_store(&full_text_store(store_name))?)
+        };
+        let vectors = if collection.vectors.is_empty() {
+            None
+        } else {
+            Some(transaction.object_store(&vector_store(store_name))?)
+        };
 
         match op.op_type {
             OpType::CREATE | OpType::UPDATE => {
@@ -152,9 +434,29 @@ impl Storage for IndexDB {
                 // Validate document against schema
                 schema.validate_schema(document.clone())?;
 
-                // Store the document and wait for completion
+                // Store the document along with its terms and buckets and wait for completion
                 let request = store.put_with_key(&document, &pk_value)?;
+                let full_text_request = match &full_text {
+                    Some(full_text) => {
+                        let record = self.core.document_terms(&document, &fields)?.to_record(&pk_value)?;
+                        Some(full_text.put_with_key(&record, &pk_value)?)
+                    }
+                    None => None,
+                };
+                let vector_request = match &vectors {
+                    Some(vectors) => {
+                        let record = vector_record(self.core.vector_buckets(&document, &collection.vectors)?)?;
+                        Some(vectors.put_with_key(&record, &pk_value)?)
+                    }
+                    None => None,
+                };
                 idb_request_result(request).await?;
+                if let Some(full_text_request) = full_text_request {
+                    idb_request_result(full_text_request).await?;
+                }
+                if let Some(vector_request) = vector_request {
+                    idb_request_result(vector_request).await?;
+                }
 
                 Ok(document.clone())
             },
@@ -164,8 +466,16 @@ impl Storage for IndexDB {
                     return Err(JsValue::from_str("Primary key value is required for delete operation"));
                 }
 
-                // Delete the document and wait for completion
+                // Delete the document, its terms and buckets and wait for completion
                 let request = store.delete(&pk_value)?;
+                let full_text_request = match &full_text {
+                    Some(full_text) => Some(full_text.delete(&pk_value)?),
+                    None => None,
+                };
+                let vector_request = match &vectors {
+                    Some(vectors) => Some(vectors.delete(&pk_value)?),
+                    None => None,
+                };
                 let promise = Promise::new(&mut |resolve, reject| {
                     let onsucess = Closure::once(Box::new(move |_event: web_sys::Event| {
                         resolve.call1(&JsValue::undefined(), &JsValue::from_str("Document deleted")).unwrap();
@@ -181,73 +491,26 @@ impl Storage for IndexDB {
                     onerror.forget();
                 });
 
-                JsFuture::from(promise).await
+                let result = JsFuture::from(promise).await?;
+                if let Some(full_text_request) = full_text_request {
+                    idb_request_result(full_text_request).await?;
+                }
+                if let Some(vector_request) = vector_request {
+                    idb_request_result(vector_request).await?;
+                }
+                Ok(result)
             },
             _ => Err(JsValue::from_str("Unsupported operation type")),
         }
     }
 
-    async fn find(&self, collection_name: &str, query: Query) -> Result<JsValue, JsValue> {
-        let store_name = collection_name;
-        
-        let store_names = self.db.object_store_names();
-        let stores: Vec<String> = (0..store_names.length())
-        .filter_map(|i| {
-            let store = store_names.get(i)?;
-            Some(store.as_str().to_string())
-        })
-        .collect();
-
-        let transaction = match self.db.transaction_with_str(store_name) {
-            Ok(t) => t,
-            Err(e) => {
-                web_sys::console::error_1(&JsValue::from_str(&format!(
-                    "Failed to create transaction for store '{}': {:?}",
-                    store_name, e
-                )));
-                return Err(JsValue::from_str(&format!(
-                    "Failed to access store '{}'. Available stores: {:?}",
-                    store_name, stores
-                )));
-            }
-        };
-
-        let store = transaction.object_store(store_name)?;
-        
-        let normalized_query = query.parse()?;
-        let request = store.get_all()?;
-        let normalized_query = normalized_query.clone();
-        let promise = Promise::new(&mut |resolve, _reject| {
-            let value = normalized_query.clone();
-            let core = self.core.clone();
-            let onsucess = Closure::once(Box::new(move |event: web_sys::Event| {
-                let request: IdbRequest = event.target().unwrap().dyn_into().unwrap();
-                let result = request.result().unwrap();
-                // Filter documents based on query
-                let filtered = Array::new();
-
-
-                if !result.is_undefined() && !result.is_null() {
-                    let documents = Array::from(&result);
-
-                    for i in 0..documents.length() {
-                        let doc = documents.get(i);
-                        if let Ok(matches) = core.document_matches_query(&doc, &value) {
-                            if matches {
-                                filtered.push(&doc);
-                            }
-                        }
-                    }
-                }
-                
-                resolve.call1(&JsValue::undefined(), &filtered).unwrap();
-            }));
-            
-            request.set_onsuccess(Some(onsucess.as_ref().unchecked_ref()));
-            onsucess.forget();
-        });
-
-        JsFuture::from(promise).await
+    async fn find(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue> {
+        let execution = self.execute(collection_name, &query, &options).await?;
+        let results = Array::new();
+        for doc in execution.documents {
+            results.push(&doc);
+        }
+        Ok(results.into())
     }
 
     async fn find_document_by_id(&self, collection_name: &str, primary_key_value: JsValue) -> Result<JsValue, JsValue> {
@@ -276,40 +539,22 @@ impl Storage for IndexDB {
         JsFuture::from(promise).await
     }
 
-    async fn count(&self,collection_name: &str,   query: Query) -> Result<JsValue, JsValue> {
-        let store_name = collection_name;
-        let transaction = self.db.transaction_with_str(store_name)?;
-        let store = transaction.object_store(store_name)?;
-        
-        let normalized_query = query.parse()?;
-        let request = store.get_all()?;
-        let normalized_query = normalized_query.clone();        
-        let promise = Promise::new(&mut |resolve, _reject| {
-            let value = normalized_query.clone();
-            let core = self.core.clone();
-            let onsucess = Closure::once(Box::new(move |event: web_sys::Event| {
-                let request: IdbRequest = event.target().unwrap().dyn_into().unwrap();
-                let result = request.result().unwrap();
-                let documents = Array::from(&result);
-                
-                let mut count = 0;
-                for i in 0..documents.length() {
-                    let doc = documents.get(i);
-                    if let Ok(matches) = core.document_matches_query(&doc, &value) {
-                        if matches {
-                            count += 1;
-                        }
-                    }
-                }
-                
-                resolve.call1(&JsValue::undefined(), &JsValue::from_f64(count as f64)).unwrap();
-            }));
-            
-            request.set_onsuccess(Some(onsucess.as_ref().unchecked_ref()));
-            onsucess.forget();
-        });
+    async fn count(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue> {
+        let predicate = query.compile()?;
+        let plan = QueryPlan::new(&predicate, &query.schema.primary_key);
+        let (store, index) = self.read_stores(collection_name, &plan)?;
 
-        JsFuture::from(promise).await
+        // Order does not change how many documents a page holds, so counting never sorts
+        let scan_limit = options.limit.map(|limit| limit + options.skip);
+        let (matched, _) = scan_store(&store, index.as_ref(), &self.core, &predicate, &plan, &query.schema.primary_key, scan_limit).await?;
+
+        Ok(JsValue::from_f64(options.paginate(matched).len() as f64))
+    }
+
+    async fn explain(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue> {
+        let started_at = js_sys::Date::now();
+        let execution = self.execute(collection_name, &query, &options).await?;
+        Explain::from_execution(query.get_query()?, &execution, started_at).to_js()
     }
 
     async fn close(&self) -> Result<JsValue, JsValue> {
@@ -324,11 +569,7 @@ impl Storage for IndexDB {
             let store_name = test_store.unwrap();
             if let Err(_) = self.db.transaction_with_str(&store_name) {
                 // Database is closed, need to reopen
-                let schemas_js = Object::new();
-                for (collection, schema) in self.base.schemas.iter() {
-                    let _ = Reflect::set(&schemas_js, &JsValue::from_str(collection), &to_value(&schema)?);
-                }
-                let db = create_database(&self.base.name, &schemas_js).await?;
+                let db = create_database(&self.base.name, &self.base.schemas).await?;
                 // Update the pool with new connection
                 POOL.store_connection(self.base.name.clone(), Arc::downgrade(&db));
                 self.db = (*db).clone();
@@ -342,36 +583,98 @@ impl Storage for IndexDB {
     }
 }
 
-async fn create_database(name: &str, schemas_js: &Object) -> Result<Arc<IdbDatabase>, JsValue> {
+async fn create_database(name: &str, schemas: &HashMap<String, Schema>) -> Result<Arc<IdbDatabase>, JsValue> {
     let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window object"))?;
     let idb = window.indexed_db()?.ok_or_else(|| JsValue::from_str("IndexedDB not available"))?;
-    
-    let version = 1;
-    let db_request = idb.open_with_u32(name, version)?;
 
-    // Clone keys before entering the Promise
-    let keys_array = Object::keys(schemas_js);
-    let keys_vec: Vec<String> = (0..keys_array.length())
-        .filter_map(|i| keys_array.get(i).as_string())
+    let collections: Vec<CollectionStores> = schemas
+        .iter()
+        .map(|(collection, schema)| CollectionStores::new(collection, schema))
         .collect();
-    
+
+    let db = open_database(&idb, name, None, &collections).await?;
+
+    // Stores can only be created while upgrading, so schemas needing new ones bump the version
+    let store_names = db.object_store_names();
+    let missing_store = collections
+        .iter()
+        .flat_map(CollectionStores::store_names)
+        .any(|store| !store_names.contains(&store));
+    let db = if missing_store {
+        let version = db.version() as u32 + 1;
+        db.close();
+        open_database(&idb, name, Some(version), &collections).await?
+    } else {
+        db
+    };
+    Ok(Arc::new(db))
+}
+
+/// Opens the database at the given version, its current one when `None`, creating the missing
+/// stores if that upgrades it.
+async fn open_database(
+    idb: &IdbFactory,
+    name: &str,
+    version: Option<u32>,
+    collections: &[CollectionStores],
+) -> Result<IdbDatabase, JsValue> {
+    let db_request = match version {
+        Some(version) => idb.open_with_u32(name, version)?,
+        None => idb.open(name)?,
+    };
+
     let db = JsFuture::from(Promise::new(&mut |resolve, reject| {
-        let keys = keys_vec.clone();
+        let collections = collections.to_vec();
         let onupgradeneeded = Closure::once(Box::new(move |event: web_sys::Event| {
-            let db: IdbDatabase = event.target()
+            let request = event.target()
                 .unwrap()
                 .dyn_into::<IdbOpenDbRequest>()
-                .unwrap()
+                .unwrap();
+            let db: IdbDatabase = request
                 .result()
                 .unwrap()
                 .dyn_into()
                 .unwrap();
-            
-            for collection_name in keys {
-                if !db.object_store_names().contains(&collection_name) {
-                    db.create_object_store(&collection_name)
+            let transaction = request.transaction().unwrap();
+
+            for collection in collections {
+                let existed = db.object_store_names().contains(&collection.name);
+                if !existed {
+                    db.create_object_store(&collection.name)
                         .expect("Failed to create object store");
                 }
+                let mut parameters = IdbIndexParameters::new();
+                parameters.multi_entry(true);
+
+                let full_text_name = full_text_store(&collection.name);
+                if !collection.full_text.is_empty() && !db.object_store_names().contains(&full_text_name) {
+                    db.create_object_store(&full_text_name)
+                        .expect("Failed to create object store")
+                        .create_index_with_str_and_optional_parameters(FULL_TEXT_INDEX, FULL_TEXT_INDEX, &parameters)
+                        .expect("Failed to create full-text index");
+                    if existed {
+                        let fields: Vec<FieldPath> = collection.full_text.iter().map(|path| FieldPath::new(path)).collect();
+                        backfill(&transaction, &collection.name, &full_text_name, move |doc, key| {
+                            CoreStorage {}.document_terms(doc, &fields)?.to_record(key)
+                        })
+                        .expect("Failed to index existing documents");
+                    }
+                }
+
+                let vector_name = vector_store(&collection.name);
+                if !collection.vectors.is_empty() && !db.object_store_names().contains(&vector_name) {
+                    db.create_object_store(&vector_name)
+                        .expect("Failed to create object store")
+                        .create_index_with_str_and_optional_parameters(VECTOR_INDEX, VECTOR_INDEX, &parameters)
+                        .expect("Failed to create vector index");
+                    if existed {
+                        let paths = collection.vectors.clone();
+                        backfill(&transaction, &collection.name, &vector_name, move |doc, _| {
+                            vector_record(CoreStorage {}.vector_buckets(doc, &paths)?)
+                        })
+                        .expect("Failed to index existing documents");
+                    }
+                }
             }
         }));
 
@@ -400,7 +703,103 @@ async fn create_database(name: &str, schemas_js: &Object) -> Result<Arc<IdbDatab
         onerror.forget();
     })).await?;
 
-    Ok(Arc::new(db.dyn_into::<IdbDatabase>()?))
+    db.dyn_into::<IdbDatabase>()
+}
+
+/// Indexes the documents a collection already holds into one of its new index stores, within
+/// the upgrade transaction, which then only completes once every document is indexed.
+///
+/// `record` builds the record of a document out of the document and its primary key.
+fn backfill(
+    transaction: &IdbTransaction,
+    collection_name: &str,
+    index_store: &str,
+    record: impl Fn(&JsValue, &JsValue) -> Result<JsValue, JsValue> + 'static,
+) -> Result<(), JsValue> {
+    let store = transaction.object_store(collection_name)?;
+    let index = transaction.object_store(index_store)?;
+
+    let request = store.open_cursor()?;
+    let onsuccess = Closure::wrap(Box::new(move |event: web_sys::Event| {
+        let request: IdbRequest = event.target().unwrap().dyn_into().unwrap();
+        let Ok(cursor) = request.result().unwrap().dyn_into::<IdbCursorWithValue>() else {
+            // The cursor went past the last document
+            return;
+        };
+        let indexed = cursor.value().and_then(|doc| {
+            let key = cursor.primary_key()?;
+            index.put_with_key(&record(&doc, &key)?, &key)?;
+            cursor.continue_()
+        });
+        if indexed.is_err() {
+            // Aborting the upgrade makes opening the database fail
+            if let Some(transaction) = request.transaction() {
+                let _ = transaction.abort();
+            }
+        }
+    }) as Box<dyn FnMut(web_sys::Event)>);
+
+    request.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+    onsuccess.forget();
+    Ok(())
+}
+
+impl IndexDB {
+    /// Finds the documents matching a query, sorted and paginated.
+    async fn execute(&self, collection_name: &str, query: &Query, options: &QueryOptions) -> Result<Execution, JsValue> {
+        let primary_key = query.schema.primary_key.clone();
+        let predicate = query.compile()?;
+        let plan = QueryPlan::new(&predicate, &primary_key);
+
+        let (store, index) = match self.read_stores(collection_name, &plan) {
+            Ok(stores) => stores,
+            Err(e) => {
+                let store_names = self.db.object_store_names();
+                let stores: Vec<String> = (0..store_names.length())
+                    .filter_map(|i| store_names.get(i))
+                    .collect();
+                web_sys::console::error_1(&JsValue::from_str(&format!(
+                    "Failed to create transaction for store '{}': {:?}",
+                    collection_name, e
+                )));
+                return Err(JsValue::from_str(&format!(
+                    "Failed to access store '{}'. Available stores: {:?}",
+                    collection_name, stores
+                )));
+            }
+        };
+
+        let (matched, scanned) = scan_store(&store, index.as_ref(), &self.core, &predicate, &plan, &primary_key, options.scan_limit()).await?;
+        let matched_count = matched.len();
+        let sorted = self.core.sort_documents(matched, options, &primary_key)?;
+        Ok(Execution {
+            documents: options.paginate(sorted),
+            plan,
+            scanned,
+            matched: matched_count,
+        })
+    }
+
+    /// Opens a read-only transaction over the collection store, along with the index store the
+    /// plan reads from, if any.
+    fn read_stores(&self, collection_name: &str, plan: &QueryPlan) -> Result<(IdbObjectStore, Option<IdbObjectStore>), JsValue> {
+        let index = match plan {
+            QueryPlan::FullText(_) => Some(full_text_store(collection_name)),
+            QueryPlan::Nearest(search) if search.approximate => Some(vector_store(collection_name)),
+            _ => None,
+        };
+        let store_names = Array::of1(&JsValue::from_str(collection_name));
+        if let Some(index) = &index {
+            store_names.push(&JsValue::from_str(index));
+        }
+        let transaction = self.db.transaction_with_str_sequence(&store_names)?;
+        let store = transaction.object_store(collection_name)?;
+        let index_store = match &index {
+            Some(index) => Some(transaction.object_store(index)?),
+            None => None,
+        };
+        Ok((store, index_store))
+    }
 }
 
 #[wasm_bindgen]
@@ -418,7 +817,7 @@ impl IndexDB {
             Some(db) => db,
             None => {
                 // Create new connection if none exists
-                let db = create_database(name, &schemas_js).await?;
+                let db = create_database(name, &base.schemas).await?;
                 POOL.store_connection(name.to_string(), Arc::downgrade(&db));
                 db
             }
@@ -439,9 +838,17 @@ impl IndexDB {
     }
 
     #[wasm_bindgen(js_name = "find")]
-    pub async fn find_js(&self, collection_name: &str, query: JsValue) -> Result<JsValue, JsValue> {
+    pub async fn find_js(&self, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
         let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
-        self.find(collection_name, Query::new(query, schema.clone())?).await
+        let options = QueryOptions::new(options, schema)?;
+        self.find(collection_name, Query::new(query, schema.clone())?, options).await
+    }
+
+    #[wasm_bindgen(js_name = "explain")]
+    pub async fn explain_js(&self, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let options = QueryOptions::new(options, schema)?;
+        self.explain(collection_name, Query::new(query, schema.clone())?, options).await
     }
 
     #[wasm_bindgen(js_name = "findDocumentById")]
@@ -450,9 +857,10 @@ impl IndexDB {
     }
 
     #[wasm_bindgen(js_name = "count")]
-    pub async fn count_js(&self, collection_name: &str, query: JsValue) -> Result<JsValue, JsValue> {
+    pub async fn count_js(&self, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
         let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
-        self.count(collection_name, Query::new(query, schema.clone())?).await
+        let options = QueryOptions::new(options, schema)?;
+        self.count(collection_name, Query::new(query, schema.clone())?, options).await
     }
 
     #[wasm_bindgen(js_name = "close")]
@@ -666,7 +1074,7 @@ mod tests {
             "age": { "$gt": 30 }
         }"#).unwrap();
         
-        let result = db.find_js("demo", query_value).await.unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
         let result_array = Array::from(&result);
         
         assert_eq!(result_array.length(), 1);
@@ -726,7 +1134,7 @@ mod tests {
             "status": "active"
         }"#).unwrap();
         
-        let result = db.count_js("demo", query_value).await.unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
         assert_eq!(result.as_f64().unwrap(), 2.0);
 
         // Clean up
@@ -787,16 +1195,941 @@ mod tests {
         let empty_query = json_str_to_js_value("{}").unwrap();
         
         // Find all products (should be empty)
-        let products_result = db.find_js("products", empty_query.clone()).await.unwrap();
+        let products_result = db.find_js("products", empty_query.clone(), JsValue::undefined()).await.unwrap();
         let products_array = Array::from(&products_result);
         assert_eq!(products_array.length(), 0);
 
         // Count products (should be 0)
-        let count_result = db.count_js("products", empty_query).await.unwrap();
+        let count_result = db.count_js("products", empty_query, JsValue::undefined()).await.unwrap();
         assert_eq!(count_result.as_f64().unwrap(), 0.0);
 
         // Clean up
         db.close().await.unwrap();
     }
-}
 
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_negation_operators() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "age": { "type": "number" },
+                "country": { "type": "string" },
+                "status": { "type": "string" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_negation", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "name": "Alice", "age": 30, "country": "ES", "status": "active"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "name": "Bob", "age": 16, "country": "ES", "status": "archived"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "name": "Charlie", "age": 17, "country": "FR", "status": "active"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "4", "name": "Dave", "age": 40, "country": "FR", "status": "deleted"
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // $nin excludes every listed value
+        let query_value = json_str_to_js_value(r#"{
+            "status": { "$nin": ["archived", "deleted"] }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // $ne combined with a regular condition
+        let query_value = json_str_to_js_value(r#"{
+            "country": { "$ne": "ES" },
+            "status": "active"
+        }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("name")).unwrap(),
+            JsValue::from_str("Charlie")
+        );
+
+        // $not negates the wrapped operator object
+        let query_value = json_str_to_js_value(r#"{
+            "age": { "$not": { "$lt": 18 } }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // $nor: not (age < 18 and country = ES)
+        let query_value = json_str_to_js_value(r#"{
+            "$nor": [
+                { "$and": [ { "age": { "$lt": 18 } }, { "country": "ES" } ] }
+            ]
+        }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 3);
+        for i in 0..result_array.length() {
+            assert_ne!(
+                Reflect::get(&result_array.get(i), &JsValue::from_str("name")).unwrap(),
+                JsValue::from_str("Bob")
+            );
+        }
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_string_operators() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "email": { "type": "string" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_string_operators", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "name": "John", "email": "john@example.com"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "name": "Johanna", "email": "johanna@test.org"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "name": "Mary", "email": "mary@example.com"
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // Prefix lookups are case sensitive unless the "i" option is given
+        let query_value = json_str_to_js_value(r#"{
+            "name": { "$startsWith": "jo" }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 0.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "name": { "$startsWith": "jo", "$options": "i" }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "email": { "$contains": "@EXAMPLE", "$options": "i" }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "email": { "$regex": "^[a-z]+@test\\.org$" }
+        }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("name")).unwrap(),
+            JsValue::from_str("Johanna")
+        );
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_nested_paths() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "city": { "type": "string" },
+                        "zip": { "type": "number" }
+                    }
+                },
+                "tags": {
+                    "type": "array",
+                    "items": [{
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" }
+                        }
+                    }]
+                }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_nested_paths", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "address": { "city": "Madrid", "zip": 28001 }, "tags": [{ "name": "admin" }, { "name": "dev" }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "address": { "city": "Lisbon", "zip": 1000 }, "tags": [{ "name": "dev" }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "tags": []
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        let query_value = json_str_to_js_value(r#"{
+            "address.city": "Madrid"
+        }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("id")).unwrap(),
+            JsValue::from_str("1")
+        );
+
+        let query_value = json_str_to_js_value(r#"{
+            "address.zip": { "$lt": 5000 }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Paths through arrays match when any element matches
+        let query_value = json_str_to_js_value(r#"{
+            "tags.name": "dev"
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags.0.name": "admin"
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Negations match when no element matches, including missing values
+        let query_value = json_str_to_js_value(r#"{
+            "tags.name": { "$ne": "admin" }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_array_operators() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "tags": {
+                    "type": "array",
+                    "items": [{ "type": "string" }]
+                },
+                "roles": {
+                    "type": "array",
+                    "items": [{
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "level": { "type": "number" }
+                        }
+                    }]
+                }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_array_operators", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "tags": ["admin", "staff"], "roles": [{ "name": "owner", "level": 3 }, { "name": "viewer", "level": 1 }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "tags": ["staff"], "roles": [{ "name": "owner", "level": 1 }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "tags": [], "roles": []
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // A single value matches arrays that contain it
+        let query_value = json_str_to_js_value(r#"{
+            "tags": "staff"
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags": { "$all": ["staff", "admin"] }
+        }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("id")).unwrap(),
+            JsValue::from_str("1")
+        );
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags": { "$size": 0 }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Both conditions have to hold for the same element
+        let query_value = json_str_to_js_value(r#"{
+            "roles": { "$elemMatch": { "name": "owner", "level": { "$gte": 2 } } }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags": { "$nin": ["admin"] }
+        }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_null_and_missing_values() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "age": { "type": "number" },
+                "active": { "type": "boolean" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_null_values", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "name": "John", "age": 30, "active": false
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "name": null, "age": null
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3"
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // Missing values behave like null, except for $exists
+        let query_value = json_str_to_js_value(r#"{ "name": null }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$ne": null } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$exists": true } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$exists": false } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "age": { "$gt": 10 } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$in": [null, "John"] } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 3.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$nin": [null] } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$ne": "John" } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$startsWith": "J" } }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "active": false }"#).unwrap();
+        let result = db.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_sort() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "createdAt": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_sort", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{ "id": "1", "name": "Carol", "createdAt": 100 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "2", "name": "Alice", "createdAt": 200 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "3", "name": "Bob", "createdAt": 100 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "4", "name": "Bob", "createdAt": 100 }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // Newest first, then by name, with the primary key breaking the remaining tie
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "createdAt": -1, "name": 1 } }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await.unwrap();
+        let ids: Vec<String> = Array::from(&result)
+            .iter()
+            .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["2", "3", "4", "1"]);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "unknown": 1 } }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await;
+        assert!(result.is_err());
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_limit_and_skip() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "rank": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_limit_skip", schemas_obj).await.unwrap();
+
+        for i in 0..10 {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(&format!(r#"{{ "id": "doc{}", "rank": {} }}"#, i, 10 - i)).unwrap(),
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // Without a sort the scan stops once the page is full
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "limit": 3 }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await.unwrap();
+        assert_eq!(Array::from(&result).length(), 3);
+
+        // Skip and limit apply after sorting
+        let query_value = json_str_to_js_value(r#"{ "rank": { "$gt": 2 } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "rank": 1 }, "skip": 2, "limit": 3 }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await.unwrap();
+        let ranks: Vec<f64> = Array::from(&result)
+            .iter()
+            .map(|doc| Reflect::get(&doc, &JsValue::from_str("rank")).unwrap().as_f64().unwrap())
+            .collect();
+        assert_eq!(ranks, vec![5.0, 6.0, 7.0]);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "skip": 8, "limit": 5 }"#).unwrap();
+        let result = db.count_js("demo", query_value, options).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "skip": 20 }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await.unwrap();
+        assert_eq!(Array::from(&result).length(), 0);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_resume_after_keyset() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "score": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_resume_after", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{ "id": "a", "score": 10 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "b", "score": 20 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "c", "score": 20 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "d", "score": 30 }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // Resuming after ("b", 20) keeps its tie "c" thanks to the primary key
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "score": 1 }, "after": [20, "b"], "limit": 2 }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await.unwrap();
+        let ids: Vec<String> = Array::from(&result)
+            .iter()
+            .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["c", "d"]);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "score": 1 }, "after": [20] }"#).unwrap();
+        let result = db.find_js("demo", query_value, options).await;
+        assert!(result.is_err());
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_explain() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "rank": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_explain", schemas_obj).await.unwrap();
+
+        for i in 0..10 {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(&format!(r#"{{ "id": "doc{}", "rank": {} }}"#, i, i)).unwrap(),
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+        let field = |report: &JsValue, name: &str| Reflect::get(report, &JsValue::from_str(name)).unwrap();
+
+        // Primary keys are looked up directly, missing keys are not scanned
+        let query_value = json_str_to_js_value(r#"{ "id": { "$in": ["doc1", "doc2", "missing"] }, "rank": { "$gt": 1 } }"#).unwrap();
+        let report = db.explain_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(field(&report, "strategy").as_string().unwrap(), "primaryKey");
+        assert_eq!(field(&report, "index").as_string().unwrap(), "id");
+        assert_eq!(field(&report, "scanned").as_f64().unwrap(), 2.0);
+        assert_eq!(field(&report, "matched").as_f64().unwrap(), 1.0);
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 1.0);
+        assert!(field(&report, "executionTimeMs").as_f64().unwrap() >= 0.0);
+
+        // Other queries scan the whole collection
+        let query_value = json_str_to_js_value(r#"{ "rank": { "$gte": 5 } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "rank": -1 }, "limit": 2 }"#).unwrap();
+        let report = db.explain_js("demo", query_value, options).await.unwrap();
+        assert_eq!(field(&report, "strategy").as_string().unwrap(), "scan");
+        assert!(field(&report, "index").is_null());
+        assert_eq!(field(&report, "scanned").as_f64().unwrap(), 10.0);
+        assert_eq!(field(&report, "matched").as_f64().unwrap(), 5.0);
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 2.0);
+        assert!(field(&report, "query").is_object());
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_string_and_date_ranges() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "code": { "type": "string" },
+                "createdAt": { "type": "string", "format": "date-time" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_string_ranges", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{ "id": "1", "code": "a10", "createdAt": "2024-01-01T10:00:00Z" }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "2", "code": "a9", "createdAt": "2024-01-01T12:00:00+05:00" }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "3", "code": "b1", "createdAt": "2024-03-01T00:00:00Z" }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "4", "createdAt": "2023-12-31T23:59:59Z" }"#).unwrap(),
+        ];
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+        let ids = |result: JsValue| {
+            let mut ids: Vec<String> = Array::from(&result)
+                .iter()
+                .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        // Strings compare lexicographically, missing values never match
+        let query_value = json_str_to_js_value(r#"{ "code": { "$gte": "a", "$lt": "b" } }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "2"]);
+
+        let query_value = json_str_to_js_value(r#"{ "code": { "$gt": "a10" } }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["2", "3"]);
+
+        // Dates compare as instants, whatever their offset
+        let query_value = json_str_to_js_value(r#"{ "createdAt": { "$lt": "2024-01-01T09:00:00Z" } }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["2", "4"]);
+
+        let query_value = json_str_to_js_value(r#"{ "createdAt": { "$gte": "2024-01-01T10:00:00Z", "$lte": "2024-02-01T00:00:00Z" } }"#).unwrap();
+        let result = db.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1"]);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_full_text_search() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string", "fullText": true },
+                "body": { "type": "string", "fullText": true },
+                "views": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("notes"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_full_text", schemas_obj).await.unwrap();
+
+        let notes = [
+            r#"{ "id": "1", "title": "Rust notes", "body": "Ownership and borrowing in Rust", "views": 10 }"#,
+            r#"{ "id": "2", "title": "Gardening", "body": "Tomatoes need sun", "views": 3 }"#,
+            r#"{ "id": "3", "title": "WebAssembly", "body": "Rust compiles to wasm", "views": 7 }"#,
+            r#"{ "id": "4", "title": "Rust", "body": "Fast and safe", "views": 1 }"#,
+        ];
+        for note in notes {
+            let create_op = Operation {
+                collection: "notes".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(note).unwrap(),
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        let ids = |result: JsValue| -> Vec<String> {
+            Array::from(&result)
+                .iter()
+                .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+                .collect()
+        };
+
+        // Matches come back most relevant first, case does not matter
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "RUST" } }"#).unwrap();
+        let result = db.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "4", "3"]);
+
+        // Every term is required with $operator: 'and', any of them otherwise
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust wasm", "$operator": "and" } }"#).unwrap();
+        let result = db.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["3"]);
+
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "wasm tomatoes" } }"#).unwrap();
+        let result = db.count_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // Other conditions still apply, and a sort replaces the relevance order
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" }, "views": { "$gt": 5 } }"#).unwrap();
+        let result = db.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "3"]);
+
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "views": 1 } }"#).unwrap();
+        let result = db.find_js("notes", query_value, options).await.unwrap();
+        assert_eq!(ids(result), vec!["4", "3", "1"]);
+
+        // Only the documents holding the terms are read
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" } }"#).unwrap();
+        let report = db.explain_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        let field = |name: &str| Reflect::get(&report, &JsValue::from_str(name)).unwrap();
+        assert_eq!(field("strategy").as_string().unwrap(), "fullText");
+        assert_eq!(field("index").as_string().unwrap(), "body,title");
+        assert_eq!(field("scanned").as_f64().unwrap(), 3.0);
+
+        // Updates and deletes are reflected in the index
+        let update_op = Operation {
+            collection: "notes".to_string(),
+            op_type: OpType::UPDATE,
+            data: json_str_to_js_value(r#"{ "id": "3", "title": "WebAssembly", "body": "Compiled to wasm", "views": 7 }"#).unwrap(),
+            indexes: vec![],
+        };
+        db.write(&update_op).await.unwrap();
+        let delete_op = Operation {
+            collection: "notes".to_string(),
+            op_type: OpType::DELETE,
+            data: JsValue::from_str("1"),
+            indexes: vec![],
+        };
+        db.write(&delete_op).await.unwrap();
+
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" } }"#).unwrap();
+        let result = db.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["4"]);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_indexdb_storage_vector_search() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "category": { "type": "string" },
+                "embedding": { "type": "vector", "dimensions": 3, "approximate": true }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("items"), &schema).unwrap();
+
+        let db = IndexDB::create("test_db_vector", schemas_obj).await.unwrap();
+
+        let items = [
+            r#"{ "id": "1", "category": "a", "embedding": [1, 0, 0] }"#,
+            r#"{ "id": "2", "category": "b", "embedding": [0.9, 0.1, 0] }"#,
+            r#"{ "id": "3", "category": "a", "embedding": [0, 1, 0] }"#,
+            r#"{ "id": "4", "category": "b", "embedding": [0, 0, 1] }"#,
+            r#"{ "id": "5", "category": "a", "embedding": [-1, 0, 0] }"#,
+        ];
+        for item in items {
+            let create_op = Operation {
+                collection: "items".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(item).unwrap(),
+                indexes: vec![],
+            };
+            db.write(&create_op).await.unwrap();
+        }
+
+        // Vectors must have the dimensions of their property
+        let invalid_op = Operation {
+            collection: "items".to_string(),
+            op_type: OpType::CREATE,
+            data: json_str_to_js_value(r#"{ "id": "6", "category": "a", "embedding": [1, 0] }"#).unwrap(),
+            indexes: vec![],
+        };
+        assert!(db.write(&invalid_op).await.is_err());
+
+        let ids = |result: JsValue| -> Vec<String> {
+            Array::from(&result)
+                .iter()
+                .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+                .collect()
+        };
+
+        // The nearest documents come back first, cosine distance by default
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2 } } }"#).unwrap();
+        let result = db.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "2"]);
+
+        // Neighbours are chosen among the documents matching the other conditions
+        let query_value = json_str_to_js_value(r#"{ "category": "a", "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2 } } }"#).unwrap();
+        let result = db.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "3"]);
+
+        // Equally distant documents are ordered by primary key
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [0, 0, 0.9], "$k": 3, "$metric": "euclidean" } } }"#).unwrap();
+        let result = db.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["4", "2", "1"]);
+
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [0, 0, 2], "$k": 1, "$metric": "dot" } } }"#).unwrap();
+        let result = db.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["4"]);
+
+        // A sort reorders the neighbours, counting stops at $k
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2 } } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "id": -1 } }"#).unwrap();
+        let result = db.find_js("items", query_value.clone(), options).await.unwrap();
+        assert_eq!(ids(result), vec!["2", "1"]);
+        let result = db.count_js("items", query_value.clone(), JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let report = db.explain_js("items", query_value, JsValue::undefined()).await.unwrap();
+        let field = |report: &JsValue, name: &str| Reflect::get(report, &JsValue::from_str(name)).unwrap();
+        assert_eq!(field(&report, "strategy").as_string().unwrap(), "vector");
+        assert_eq!(field(&report, "index").as_string().unwrap(), "embedding");
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 2.0);
+        assert!(field(&report, "scanned").as_f64().unwrap() <= 5.0);
+
+        // More neighbours than candidates rank every document, each read once
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 10 } } }"#).unwrap();
+        let report = db.explain_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(field(&report, "scanned").as_f64().unwrap(), 5.0);
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 5.0);
+
+        // Deleted documents leave the index
+        let delete_op = Operation {
+            collection: "items".to_string(),
+            op_type: OpType::DELETE,
+            data: JsValue::from_str("1"),
+            indexes: vec![],
+        };
+        db.write(&delete_op).await.unwrap();
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 1 } } }"#).unwrap();
+        let result = db.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["2"]);
+
+        // Clean up
+        db.close().await.unwrap();
+    }
+}
@@ -4,6 +4,12 @@ use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::wasm_bindgen;
 use crate::operation::{OpType, Operation};
 use crate::query::Query;
+use crate::query::options::QueryOptions;
+use crate::query::plan::{Execution, Explain, QueryPlan};
+use crate::query::predicate::{FieldPath, Predicate, Value};
+use crate::schema::{full_text_paths, vector_index_paths};
+use crate::storage::internals::fulltext::FullTextIndex;
+use crate::storage::internals::vector::{Hyperplanes, VectorIndex};
 use crate::storage::internals::base_storage::BaseStorage;
 use std::sync::RwLock;
 
@@ -40,6 +46,10 @@ pub struct InMemory {
     core: CoreStorage,
     base: BaseStorage,
     by_index: RwLock<HashMap<String, HashMap<String, JsValue>>>,
+    /// The full-text index of each collection with full-text indexed properties.
+    full_text: RwLock<HashMap<String, FullTextIndex>>,
+    /// The approximate index of each collection with approximately indexed vector properties.
+    vectors: RwLock<HashMap<String, VectorIndex>>,
 }
 
 impl Storage for InMemory {
@@ -83,6 +93,7 @@ impl Storage for InMemory {
                         }
                         
                         index.insert(pk_str.clone(), document.clone());
+                        self.index_document(&op.collection, &pk_str, Some(&document))?;
                         Ok(document)
                     }
                     OpType::UPDATE => {
@@ -93,6 +104,7 @@ impl Storage for InMemory {
                         }
                         
                         index.insert(pk_str.clone(), document.clone());
+                        self.index_document(&op.collection, &pk_str, Some(&document))?;
                         Ok(document)
                     }
                     _ => Err(JsValue::from_str("Unsupported operation type for this data"))
@@ -114,6 +126,7 @@ impl Storage for InMemory {
                 };
 
                 if index.remove(&pk_str).is_some() {
+                    self.index_document(&op.collection, &pk_str, None)?;
                     Ok(JsValue::from_str("Document deleted"))
                 } else {
                     Err(JsValue::from_str("Document with this primary key does not exist"))
@@ -123,22 +136,12 @@ impl Storage for InMemory {
         }
     }
 
-    async fn find(&self, collection_name: &str, query: Query) -> Result<JsValue, JsValue> {
-        let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
-        let normalized_query = query.parse()?;
+    async fn find(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue> {
+        let execution = self.execute(collection_name, &query, &options)?;
         let results = Array::new();
-        let primary_key = schema.primary_key.clone();
-        let index_name = format!("pk_{}_{}", collection_name, primary_key);
-
-        if let Some(index) = self.by_index.read().unwrap().get(&index_name) {
-            for (_pk, doc) in index.iter() {
-                let matches = self.core.document_matches_query(doc, &normalized_query)?;
-                if matches {
-                    results.push(doc);
-                }
-            }
+        for doc in execution.documents {
+            results.push(&doc);
         }
-
         Ok(results.into())
     }
 
@@ -170,31 +173,36 @@ impl Storage for InMemory {
         Err(JsValue::from_str("Document not found"))
     }
 
-    async fn count(&self, collection_name: &str, query: Query) -> Result<JsValue, JsValue> {
+    async fn count(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue> {
         let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
-        let normalized_query = query.parse()?;
-        let mut count = 0;
+        let predicate = query.compile()?;
+        let plan = QueryPlan::new(&predicate, &schema.primary_key);
 
-        let primary_key = schema.primary_key.clone();
-        let index_name = format!("pk_{}_{}", collection_name, primary_key);
-
-        if let Some(index) = self.by_index.read().unwrap().get(&index_name) {
-            for (_pk, doc) in index.iter() {
-                let matches = self.core.document_matches_query(doc, &normalized_query)?;
-                if matches {
-                    count += 1;
-                }
-            }
-        }
+        // Order does not change how many documents a page holds, so counting never sorts
+        let scan_limit = options.limit.map(|limit| limit + options.skip);
+        let (matched, _) = self.scan(collection_name, &predicate, &plan, scan_limit)?;
 
+        let count = matched.len().saturating_sub(options.skip);
         Ok(JsValue::from_f64(count as f64))
     }
 
+    async fn explain(&self, collection_name: &str, query: Query, options: QueryOptions) -> Result<JsValue, JsValue> {
+        let started_at = js_sys::Date::now();
+        let execution = self.execute(collection_name, &query, &options)?;
+        Explain::from_execution(query.get_query()?, &execution, started_at).to_js()
+    }
+
     async fn close(&self) -> Result<JsValue, JsValue> {
         // Clear all data from the storage
         self.by_index.write()
             .map_err(|_| JsValue::from_str("Failed to acquire write lock"))?
             .clear();
+        self.full_text.write()
+            .map_err(|_| JsValue::from_str("Failed to acquire write lock"))?
+            .clear();
+        self.vectors.write()
+            .map_err(|_| JsValue::from_str("Failed to acquire write lock"))?
+            .clear();
         
         Ok(JsValue::from_str("In-memory database closed"))
     }
@@ -206,6 +214,138 @@ impl Storage for InMemory {
 }
 
 
+impl InMemory {
+    /// Finds the documents matching a query, sorted and paginated.
+    fn execute(&self, collection_name: &str, query: &Query, options: &QueryOptions) -> Result<Execution, JsValue> {
+        let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let predicate = query.compile()?;
+        let plan = QueryPlan::new(&predicate, &schema.primary_key);
+
+        let (matched, scanned) = self.scan(collection_name, &predicate, &plan, options.scan_limit())?;
+        let matched_count = matched.len();
+        let sorted = self.core.sort_documents(matched, options, &schema.primary_key)?;
+        Ok(Execution {
+            documents: options.paginate(sorted),
+            plan,
+            scanned,
+            matched: matched_count,
+        })
+    }
+
+    /// Reads the candidates of the plan and returns those matching the predicate, along with
+    /// the number of documents read. The scan stops once `scan_limit` documents matched.
+    fn scan(
+        &self,
+        collection_name: &str,
+        predicate: &Predicate,
+        plan: &QueryPlan,
+        scan_limit: Option<usize>,
+    ) -> Result<(Vec<JsValue>, usize), JsValue> {
+        let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let index_name = format!("pk_{}_{}", collection_name, schema.primary_key);
+        let guard = self.by_index.read().map_err(|_| JsValue::from_str("Failed to acquire read lock"))?;
+        let mut scanned = 0;
+        let index = match guard.get(&index_name) {
+            Some(index) => index,
+            None => return Ok((Vec::new(), scanned)),
+        };
+
+        // Relevance order is kept when the search is not sorted, so only the keys are collected
+        let relevant = match plan {
+            QueryPlan::FullText(search) => self
+                .full_text
+                .read()
+                .map_err(|_| JsValue::from_str("Failed to acquire read lock"))?
+                .get(collection_name)
+                .map(|full_text| full_text.search(search))
+                .unwrap_or_default(),
+            QueryPlan::Nearest(search) if search.approximate => {
+                let buckets = Hyperplanes::new(search.vector.len()).buckets(&search.field.path, &search.vector);
+                self.vectors
+                    .read()
+                    .map_err(|_| JsValue::from_str("Failed to acquire read lock"))?
+                    .get(collection_name)
+                    .map(|vectors| vectors.candidates(&buckets))
+                    .unwrap_or_default()
+            }
+            _ => Vec::new(),
+        };
+        let candidates: Box<dyn Iterator<Item = &JsValue>> = match plan {
+            QueryPlan::PrimaryKey { keys, .. } => Box::new(keys.iter().filter_map(|key| {
+                let pk_str = match key {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                    _ => return None,
+                };
+                index.get(&pk_str)
+            })),
+            QueryPlan::FullText(_) => Box::new(relevant.iter().filter_map(|key| index.get(key))),
+            QueryPlan::Nearest(search) if search.approximate => Box::new(relevant.iter().filter_map(|key| index.get(key))),
+            QueryPlan::Nearest(_) | QueryPlan::Scan => Box::new(index.values()),
+        };
+        let QueryPlan::Nearest(search) = plan else {
+            let matched = self.collect_matches(candidates, predicate, scan_limit, &mut scanned)?;
+            return Ok((matched, scanned));
+        };
+
+        // Any match may be among the nearest, so every candidate is read
+        let mut matched = self.collect_matches(candidates, predicate, None, &mut scanned)?;
+        if search.approximate && matched.len() < search.k {
+            // Too few candidates shared a bucket with the searched vector, every document is ranked.
+            // The full scan reads the candidates again, which are only counted once.
+            scanned = 0;
+            matched = self.collect_matches(Box::new(index.values()), predicate, None, &mut scanned)?;
+        }
+        Ok((self.core.nearest(matched, search, &schema.primary_key)?, scanned))
+    }
+
+    /// Returns the candidates matching the predicate, counting those read into `scanned`.
+    fn collect_matches<'a>(
+        &self,
+        candidates: Box<dyn Iterator<Item = &'a JsValue> + 'a>,
+        predicate: &Predicate,
+        scan_limit: Option<usize>,
+        scanned: &mut usize,
+    ) -> Result<Vec<JsValue>, JsValue> {
+        let mut matched = Vec::new();
+        for doc in candidates {
+            if scan_limit.is_some_and(|limit| matched.len() >= limit) {
+                break;
+            }
+            *scanned += 1;
+            if self.core.matches(doc, predicate)? {
+                matched.push(doc.clone());
+            }
+        }
+        Ok(matched)
+    }
+
+    /// Keeps the full-text and vector indexes of a collection in sync with a written document,
+    /// `None` once the document is deleted.
+    fn index_document(&self, collection_name: &str, pk_str: &str, document: Option<&JsValue>) -> Result<(), JsValue> {
+        let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let fields: Vec<FieldPath> = full_text_paths(&schema.properties).iter().map(|path| FieldPath::new(path)).collect();
+        if !fields.is_empty() {
+            let mut guard = self.full_text.write().map_err(|_| JsValue::from_str("Failed to acquire write lock"))?;
+            let full_text = guard.entry(collection_name.to_string()).or_default();
+            match document {
+                Some(document) => full_text.insert(pk_str.to_string(), self.core.document_terms(document, &fields)?),
+                None => full_text.remove(pk_str),
+            }
+        }
+        let vector_paths = vector_index_paths(&schema.properties);
+        if !vector_paths.is_empty() {
+            let mut guard = self.vectors.write().map_err(|_| JsValue::from_str("Failed to acquire write lock"))?;
+            let vectors = guard.entry(collection_name.to_string()).or_default();
+            match document {
+                Some(document) => vectors.insert(pk_str.to_string(), self.core.vector_buckets(document, &vector_paths)?),
+                None => vectors.remove(pk_str),
+            }
+        }
+        Ok(())
+    }
+}
+
 #[wasm_bindgen]
 impl InMemory {
     
@@ -221,6 +361,8 @@ impl InMemory {
                 InMemory {
                     base,
                     by_index: RwLock::new(HashMap::new()),
+                    full_text: RwLock::new(HashMap::new()),
+                    vectors: RwLock::new(HashMap::new()),
                     core: CoreStorage {}
                 }
             ),
@@ -257,9 +399,17 @@ impl InMemory {
     }
 
     #[wasm_bindgen(js_name = "find")]
-    pub async fn find_js(&self, collection_name: &str, query: JsValue) -> Result<JsValue, JsValue> {
+    pub async fn find_js(&self, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+        let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
+        let options = QueryOptions::new(options, schema)?;
+        self.find(collection_name, Query::new(query, schema.clone())?, options).await
+    }
+
+    #[wasm_bindgen(js_name = "explain")]
+    pub async fn explain_js(&self, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
         let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
-        self.find(collection_name, Query::new(query, schema.clone())?).await
+        let options = QueryOptions::new(options, schema)?;
+        self.explain(collection_name, Query::new(query, schema.clone())?, options).await
     }
 
     #[wasm_bindgen(js_name = "findDocumentById")]
@@ -272,9 +422,10 @@ impl InMemory {
     }
 
     #[wasm_bindgen(js_name = "count")]
-    pub async fn count_js(&self, collection_name: &str, query: JsValue) -> Result<JsValue, JsValue> {
+    pub async fn count_js(&self, collection_name: &str, query: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
         let schema = self.base.schemas.get(collection_name).ok_or_else(|| JsValue::from_str("Collection not found"))?;
-        self.count(collection_name, Query::new(query, schema.clone())?).await
+        let options = QueryOptions::new(options, schema)?;
+        self.count(collection_name, Query::new(query, schema.clone())?, options).await
     }
 
     #[wasm_bindgen(js_name = "close")]
@@ -457,7 +608,7 @@ mod tests {
             "age": { "$gt": 30 }
         }"#).unwrap();
         
-        let result = inmem.find_js("demo", query_value).await.unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
         let result_array = Array::from(&result);
         
         assert_eq!(result_array.length(), 1);
@@ -514,7 +665,7 @@ mod tests {
             "status": "active"
         }"#).unwrap();
         
-        let result = inmem.count_js("demo", query_value).await.unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
         assert_eq!(result.as_f64().unwrap(), 2.0);
     }
 
@@ -571,12 +722,902 @@ mod tests {
         let empty_query = json_str_to_js_value("{}").unwrap();
         
         // Test find on empty collection
-        let posts_result = inmem.find_js("posts", empty_query.clone()).await.unwrap();
+        let posts_result = inmem.find_js("posts", empty_query.clone(), JsValue::undefined()).await.unwrap();
         let posts_array = Array::from(&posts_result);
         assert_eq!(posts_array.length(), 0);
         
         // Test count on empty collection
-        let count_result = inmem.count_js("posts", empty_query).await.unwrap();
+        let count_result = inmem.count_js("posts", empty_query, JsValue::undefined()).await.unwrap();
         assert_eq!(count_result.as_f64().unwrap(), 0.0);
     }
-}
\ No newline at end of file
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_negation_operators() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "age": { "type": "number" },
+                "country": { "type": "string" },
+                "status": { "type": "string" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "name": "Alice", "age": 30, "country": "ES", "status": "active"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "name": "Bob", "age": 16, "country": "ES", "status": "archived"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "name": "Charlie", "age": 17, "country": "FR", "status": "active"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "4", "name": "Dave", "age": 40, "country": "FR", "status": "deleted"
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // $nin excludes every listed value
+        let query_value = json_str_to_js_value(r#"{
+            "status": { "$nin": ["archived", "deleted"] }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // $ne combined with a regular condition
+        let query_value = json_str_to_js_value(r#"{
+            "country": { "$ne": "ES" },
+            "status": "active"
+        }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("name")).unwrap(),
+            JsValue::from_str("Charlie")
+        );
+
+        // $not negates the wrapped operator object
+        let query_value = json_str_to_js_value(r#"{
+            "age": { "$not": { "$lt": 18 } }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // $nor: not (age < 18 and country = ES)
+        let query_value = json_str_to_js_value(r#"{
+            "$nor": [
+                { "$and": [ { "age": { "$lt": 18 } }, { "country": "ES" } ] }
+            ]
+        }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 3);
+        for i in 0..result_array.length() {
+            assert_ne!(
+                Reflect::get(&result_array.get(i), &JsValue::from_str("name")).unwrap(),
+                JsValue::from_str("Bob")
+            );
+        }
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_string_operators() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "email": { "type": "string" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "name": "John", "email": "john@example.com"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "name": "Johanna", "email": "johanna@test.org"
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "name": "Mary", "email": "mary@example.com"
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // Prefix lookups are case sensitive unless the "i" option is given
+        let query_value = json_str_to_js_value(r#"{
+            "name": { "$startsWith": "jo" }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 0.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "name": { "$startsWith": "jo", "$options": "i" }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "email": { "$contains": "@EXAMPLE", "$options": "i" }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "email": { "$regex": "^[a-z]+@test\\.org$" }
+        }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("name")).unwrap(),
+            JsValue::from_str("Johanna")
+        );
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_nested_paths() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "city": { "type": "string" },
+                        "zip": { "type": "number" }
+                    }
+                },
+                "tags": {
+                    "type": "array",
+                    "items": [{
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" }
+                        }
+                    }]
+                }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "address": { "city": "Madrid", "zip": 28001 }, "tags": [{ "name": "admin" }, { "name": "dev" }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "address": { "city": "Lisbon", "zip": 1000 }, "tags": [{ "name": "dev" }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "tags": []
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        let query_value = json_str_to_js_value(r#"{
+            "address.city": "Madrid"
+        }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("id")).unwrap(),
+            JsValue::from_str("1")
+        );
+
+        let query_value = json_str_to_js_value(r#"{
+            "address.zip": { "$lt": 5000 }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Paths through arrays match when any element matches
+        let query_value = json_str_to_js_value(r#"{
+            "tags.name": "dev"
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags.0.name": "admin"
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Negations match when no element matches, including missing values
+        let query_value = json_str_to_js_value(r#"{
+            "tags.name": { "$ne": "admin" }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_array_operators() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "tags": {
+                    "type": "array",
+                    "items": [{ "type": "string" }]
+                },
+                "roles": {
+                    "type": "array",
+                    "items": [{
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "level": { "type": "number" }
+                        }
+                    }]
+                }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "tags": ["admin", "staff"], "roles": [{ "name": "owner", "level": 3 }, { "name": "viewer", "level": 1 }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "tags": ["staff"], "roles": [{ "name": "owner", "level": 1 }]
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3", "tags": [], "roles": []
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // A single value matches arrays that contain it
+        let query_value = json_str_to_js_value(r#"{
+            "tags": "staff"
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags": { "$all": ["staff", "admin"] }
+        }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        let result_array = Array::from(&result);
+        assert_eq!(result_array.length(), 1);
+        assert_eq!(
+            Reflect::get(&result_array.get(0), &JsValue::from_str("id")).unwrap(),
+            JsValue::from_str("1")
+        );
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags": { "$size": 0 }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        // Both conditions have to hold for the same element
+        let query_value = json_str_to_js_value(r#"{
+            "roles": { "$elemMatch": { "name": "owner", "level": { "$gte": 2 } } }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{
+            "tags": { "$nin": ["admin"] }
+        }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_null_and_missing_values() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "age": { "type": "number" },
+                "active": { "type": "boolean" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{
+                "id": "1", "name": "John", "age": 30, "active": false
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "2", "name": null, "age": null
+            }"#).unwrap(),
+            json_str_to_js_value(r#"{
+                "id": "3"
+            }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // Missing values behave like null, except for $exists
+        let query_value = json_str_to_js_value(r#"{ "name": null }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$ne": null } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$exists": true } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$exists": false } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "age": { "$gt": 10 } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$in": [null, "John"] } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 3.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$nin": [null] } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$ne": "John" } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value(r#"{ "name": { "$startsWith": "J" } }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+
+        let query_value = json_str_to_js_value(r#"{ "active": false }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 1.0);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_sort() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "name": { "type": "string" },
+                "createdAt": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{ "id": "1", "name": "Carol", "createdAt": 100 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "2", "name": "Alice", "createdAt": 200 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "3", "name": "Bob", "createdAt": 100 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "4", "name": "Bob", "createdAt": 100 }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // Newest first, then by name, with the primary key breaking the remaining tie
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "createdAt": -1, "name": 1 } }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await.unwrap();
+        let ids: Vec<String> = Array::from(&result)
+            .iter()
+            .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["2", "3", "4", "1"]);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "unknown": 1 } }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await;
+        assert!(result.is_err());
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_limit_and_skip() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "rank": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        for i in 0..10 {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(&format!(r#"{{ "id": "doc{}", "rank": {} }}"#, i, 10 - i)).unwrap(),
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // Without a sort the scan stops once the page is full
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "limit": 3 }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await.unwrap();
+        assert_eq!(Array::from(&result).length(), 3);
+
+        // Skip and limit apply after sorting
+        let query_value = json_str_to_js_value(r#"{ "rank": { "$gt": 2 } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "rank": 1 }, "skip": 2, "limit": 3 }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await.unwrap();
+        let ranks: Vec<f64> = Array::from(&result)
+            .iter()
+            .map(|doc| Reflect::get(&doc, &JsValue::from_str("rank")).unwrap().as_f64().unwrap())
+            .collect();
+        assert_eq!(ranks, vec![5.0, 6.0, 7.0]);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "skip": 8, "limit": 5 }"#).unwrap();
+        let result = inmem.count_js("demo", query_value, options).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "skip": 20 }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await.unwrap();
+        assert_eq!(Array::from(&result).length(), 0);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_resume_after_keyset() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "score": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{ "id": "a", "score": 10 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "b", "score": 20 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "c", "score": 20 }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "d", "score": 30 }"#).unwrap(),
+        ];
+
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // Resuming after ("b", 20) keeps its tie "c" thanks to the primary key
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "score": 1 }, "after": [20, "b"], "limit": 2 }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await.unwrap();
+        let ids: Vec<String> = Array::from(&result)
+            .iter()
+            .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["c", "d"]);
+
+        let query_value = json_str_to_js_value("{}").unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "score": 1 }, "after": [20] }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, options).await;
+        assert!(result.is_err());
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_explain() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "rank": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        for i in 0..10 {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(&format!(r#"{{ "id": "doc{}", "rank": {} }}"#, i, i)).unwrap(),
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+        let field = |report: &JsValue, name: &str| Reflect::get(report, &JsValue::from_str(name)).unwrap();
+
+        // Primary keys are looked up directly, missing keys are not scanned
+        let query_value = json_str_to_js_value(r#"{ "id": { "$in": ["doc1", "doc2", "missing"] }, "rank": { "$gt": 1 } }"#).unwrap();
+        let report = inmem.explain_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(field(&report, "strategy").as_string().unwrap(), "primaryKey");
+        assert_eq!(field(&report, "index").as_string().unwrap(), "id");
+        assert_eq!(field(&report, "scanned").as_f64().unwrap(), 2.0);
+        assert_eq!(field(&report, "matched").as_f64().unwrap(), 1.0);
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 1.0);
+        assert!(field(&report, "executionTimeMs").as_f64().unwrap() >= 0.0);
+
+        // Other queries scan the whole collection
+        let query_value = json_str_to_js_value(r#"{ "rank": { "$gte": 5 } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "rank": -1 }, "limit": 2 }"#).unwrap();
+        let report = inmem.explain_js("demo", query_value, options).await.unwrap();
+        assert_eq!(field(&report, "strategy").as_string().unwrap(), "scan");
+        assert!(field(&report, "index").is_null());
+        assert_eq!(field(&report, "scanned").as_f64().unwrap(), 10.0);
+        assert_eq!(field(&report, "matched").as_f64().unwrap(), 5.0);
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 2.0);
+        assert!(field(&report, "query").is_object());
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_string_and_date_ranges() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "code": { "type": "string" },
+                "createdAt": { "type": "string", "format": "date-time" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("demo"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = vec![
+            json_str_to_js_value(r#"{ "id": "1", "code": "a10", "createdAt": "2024-01-01T10:00:00Z" }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "2", "code": "a9", "createdAt": "2024-01-01T12:00:00+05:00" }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "3", "code": "b1", "createdAt": "2024-03-01T00:00:00Z" }"#).unwrap(),
+            json_str_to_js_value(r#"{ "id": "4", "createdAt": "2023-12-31T23:59:59Z" }"#).unwrap(),
+        ];
+        for item in items {
+            let create_op = Operation {
+                collection: "demo".to_string(),
+                op_type: OpType::CREATE,
+                data: item,
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+        let ids = |result: JsValue| {
+            let mut ids: Vec<String> = Array::from(&result)
+                .iter()
+                .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        // Strings compare lexicographically, missing values never match
+        let query_value = json_str_to_js_value(r#"{ "code": { "$gte": "a", "$lt": "b" } }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "2"]);
+
+        let query_value = json_str_to_js_value(r#"{ "code": { "$gt": "a10" } }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["2", "3"]);
+
+        // Dates compare as instants, whatever their offset
+        let query_value = json_str_to_js_value(r#"{ "createdAt": { "$lt": "2024-01-01T09:00:00Z" } }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["2", "4"]);
+
+        let query_value = json_str_to_js_value(r#"{ "createdAt": { "$gte": "2024-01-01T10:00:00Z", "$lte": "2024-02-01T00:00:00Z" } }"#).unwrap();
+        let result = inmem.find_js("demo", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1"]);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_full_text_search() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string", "fullText": true },
+                "body": { "type": "string", "fullText": true },
+                "views": { "type": "number" }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("notes"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let notes = [
+            r#"{ "id": "1", "title": "Rust notes", "body": "Ownership and borrowing in Rust", "views": 10 }"#,
+            r#"{ "id": "2", "title": "Gardening", "body": "Tomatoes need sun", "views": 3 }"#,
+            r#"{ "id": "3", "title": "WebAssembly", "body": "Rust compiles to wasm", "views": 7 }"#,
+            r#"{ "id": "4", "title": "Rust", "body": "Fast and safe", "views": 1 }"#,
+        ];
+        for note in notes {
+            let create_op = Operation {
+                collection: "notes".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(note).unwrap(),
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        let ids = |result: JsValue| -> Vec<String> {
+            Array::from(&result)
+                .iter()
+                .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+                .collect()
+        };
+
+        // Matches come back most relevant first, case does not matter
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "RUST" } }"#).unwrap();
+        let result = inmem.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "4", "3"]);
+
+        // Every term is required with $operator: 'and', any of them otherwise
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust wasm", "$operator": "and" } }"#).unwrap();
+        let result = inmem.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["3"]);
+
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "wasm tomatoes" } }"#).unwrap();
+        let result = inmem.count_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        // Other conditions still apply, and a sort replaces the relevance order
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" }, "views": { "$gt": 5 } }"#).unwrap();
+        let result = inmem.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "3"]);
+
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "views": 1 } }"#).unwrap();
+        let result = inmem.find_js("notes", query_value, options).await.unwrap();
+        assert_eq!(ids(result), vec!["4", "3", "1"]);
+
+        // Only the documents holding the terms are read
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" } }"#).unwrap();
+        let report = inmem.explain_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        let field = |name: &str| Reflect::get(&report, &JsValue::from_str(name)).unwrap();
+        assert_eq!(field("strategy").as_string().unwrap(), "fullText");
+        assert_eq!(field("index").as_string().unwrap(), "body,title");
+        assert_eq!(field("scanned").as_f64().unwrap(), 3.0);
+
+        // Updates and deletes are reflected in the index
+        let update_op = Operation {
+            collection: "notes".to_string(),
+            op_type: OpType::UPDATE,
+            data: json_str_to_js_value(r#"{ "id": "3", "title": "WebAssembly", "body": "Compiled to wasm", "views": 7 }"#).unwrap(),
+            indexes: vec![],
+        };
+        inmem.write(&update_op).await.unwrap();
+        let delete_op = Operation {
+            collection: "notes".to_string(),
+            op_type: OpType::DELETE,
+            data: JsValue::from_str("1"),
+            indexes: vec![],
+        };
+        inmem.write(&delete_op).await.unwrap();
+
+        let query_value = json_str_to_js_value(r#"{ "$text": { "$search": "rust" } }"#).unwrap();
+        let result = inmem.find_js("notes", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["4"]);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn test_inmemory_storage_vector_search() {
+        let schemas_obj = Object::new();
+        let schema_str = r#"{
+            "version": 1,
+            "primaryKey": "id",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "category": { "type": "string" },
+                "embedding": { "type": "vector", "dimensions": 3, "approximate": true }
+            }
+        }"#;
+        let schema = json_str_to_js_value(schema_str).unwrap();
+        Reflect::set(&schemas_obj, &JsValue::from_str("items"), &schema).unwrap();
+
+        let inmem = InMemory::create("test_db", schemas_obj).await.unwrap();
+
+        let items = [
+            r#"{ "id": "1", "category": "a", "embedding": [1, 0, 0] }"#,
+            r#"{ "id": "2", "category": "b", "embedding": [0.9, 0.1, 0] }"#,
+            r#"{ "id": "3", "category": "a", "embedding": [0, 1, 0] }"#,
+            r#"{ "id": "4", "category": "b", "embedding": [0, 0, 1] }"#,
+            r#"{ "id": "5", "category": "a", "embedding": [-1, 0, 0] }"#,
+        ];
+        for item in items {
+            let create_op = Operation {
+                collection: "items".to_string(),
+                op_type: OpType::CREATE,
+                data: json_str_to_js_value(item).unwrap(),
+                indexes: vec![],
+            };
+            inmem.write(&create_op).await.unwrap();
+        }
+
+        // Vectors must have the dimensions of their property
+        let invalid_op = Operation {
+            collection: "items".to_string(),
+            op_type: OpType::CREATE,
+            data: json_str_to_js_value(r#"{ "id": "6", "category": "a", "embedding": [1, 0] }"#).unwrap(),
+            indexes: vec![],
+        };
+        assert!(inmem.write(&invalid_op).await.is_err());
+
+        let ids = |result: JsValue| -> Vec<String> {
+            Array::from(&result)
+                .iter()
+                .map(|doc| Reflect::get(&doc, &JsValue::from_str("id")).unwrap().as_string().unwrap())
+                .collect()
+        };
+
+        // The nearest documents come back first, cosine distance by default
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2 } } }"#).unwrap();
+        let result = inmem.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "2"]);
+
+        // Neighbours are chosen among the documents matching the other conditions
+        let query_value = json_str_to_js_value(r#"{ "category": "a", "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2 } } }"#).unwrap();
+        let result = inmem.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["1", "3"]);
+
+        // Equally distant documents are ordered by primary key
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [0, 0, 0.9], "$k": 3, "$metric": "euclidean" } } }"#).unwrap();
+        let result = inmem.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["4", "2", "1"]);
+
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [0, 0, 2], "$k": 1, "$metric": "dot" } } }"#).unwrap();
+        let result = inmem.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["4"]);
+
+        // A sort reorders the neighbours, counting stops at $k
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 2 } } }"#).unwrap();
+        let options = json_str_to_js_value(r#"{ "sort": { "id": -1 } }"#).unwrap();
+        let result = inmem.find_js("items", query_value.clone(), options).await.unwrap();
+        assert_eq!(ids(result), vec!["2", "1"]);
+        let result = inmem.count_js("items", query_value.clone(), JsValue::undefined()).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 2.0);
+
+        let report = inmem.explain_js("items", query_value, JsValue::undefined()).await.unwrap();
+        let field = |report: &JsValue, name: &str| Reflect::get(report, &JsValue::from_str(name)).unwrap();
+        assert_eq!(field(&report, "strategy").as_string().unwrap(), "vector");
+        assert_eq!(field(&report, "index").as_string().unwrap(), "embedding");
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 2.0);
+        assert!(field(&report, "scanned").as_f64().unwrap() <= 5.0);
+
+        // More neighbours than candidates rank every document, each read once
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 10 } } }"#).unwrap();
+        let report = inmem.explain_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(field(&report, "scanned").as_f64().unwrap(), 5.0);
+        assert_eq!(field(&report, "returned").as_f64().unwrap(), 5.0);
+
+        // Deleted documents leave the index
+        let delete_op = Operation {
+            collection: "items".to_string(),
+            op_type: OpType::DELETE,
+            data: JsValue::from_str("1"),
+            indexes: vec![],
+        };
+        inmem.write(&delete_op).await.unwrap();
+        let query_value = json_str_to_js_value(r#"{ "embedding": { "$near": { "$vector": [1, 0, 0], "$k": 1 } } }"#).unwrap();
+        let result = inmem.find_js("items", query_value, JsValue::undefined()).await.unwrap();
+        assert_eq!(ids(result), vec!["2"]);
+    }
+}
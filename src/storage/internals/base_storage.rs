@@ -36,9 +36,10 @@ export class BaseStorage<Schemas extends SchemaTypeRecord> extends StorageIntern
 
     start(): Promise<void>;
     close(): Promise<void>;
-    count(colectionName: keyof Schemas, query: QueryType<Schemas[keyof Schemas]>): Promise<number>;
+    count(colectionName: keyof Schemas, query: QueryType<Schemas[keyof Schemas]>, options?: QueryOptions<Schemas[keyof Schemas]>): Promise<number>;
     findDocumentById(collectionName: keyof Schemas, id: string): Promise<Doc<Schemas[keyof Schemas]> | null>;
-    find(collectionName: keyof Schemas, query: QueryType<Schemas[keyof Schemas]>): Promise<Doc<Schemas[keyof Schemas]>[]>;
+    find(collectionName: keyof Schemas, query: QueryType<Schemas[keyof Schemas]>, options?: QueryOptions<Schemas[keyof Schemas]>): Promise<Doc<Schemas[keyof Schemas]>[]>;
+    explain(collectionName: keyof Schemas, query: QueryType<Schemas[keyof Schemas]>, options?: QueryOptions<Schemas[keyof Schemas]>): Promise<ExplainResult>;
     write(op: Operation<Schemas[keyof Schemas]>): Promise<Doc<Schemas[keyof Schemas]>>;
 
     getOption(name: string): string | boolean | number | undefined;
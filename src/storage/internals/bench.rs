@@ -0,0 +1,272 @@
+use js_sys::{Array, Object, Reflect, JSON};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::{console_log, wasm_bindgen_test};
+use crate::query::Query;
+use crate::query::predicate::Predicate;
+use crate::schema::Schema;
+use crate::storage::internals::core::CoreStorage;
+use crate::utils::build_regex;
+
+#[cfg(feature = "browser")]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+/// Documents matched per round.
+const DOCUMENTS: usize = 5000;
+
+/// Rounds run by each evaluator, after a warm-up round.
+const ROUNDS: usize = 10;
+
+/// The evaluator compiled predicates replaced, which walks the query object with `Object::keys`
+/// and `Reflect::get` again for every document. Kept as the reference of the benchmark only,
+/// without the array operators the benchmark does not use.
+struct ReflectedEvaluator;
+
+impl ReflectedEvaluator {
+    fn document_matches_query(&self, document: &JsValue, query: &JsValue) -> Result<bool, JsValue> {
+        if !query.is_object() {
+            return Err(JsValue::from_str("Query must be an object"));
+        }
+        let keys = Object::keys(&Object::from(query.clone()));
+        for i in 0..keys.length() {
+            let key = keys.get(i).as_string().unwrap_or_default();
+            let value = Reflect::get(query, &JsValue::from_str(&key))?;
+            let matches = if key == "$and" || key == "$or" || key == "$nor" {
+                if !Array::is_array(&value) {
+                    return Err(JsValue::from_str(&format!("{} must be an array", key)));
+                }
+                // $and stops at the first failing condition, $or and $nor at the first passing one
+                let stop_on = key != "$and";
+                let mut stopped = false;
+                for item in Array::from(&value).iter() {
+                    if self.document_matches_query(document, &item)? == stop_on {
+                        stopped = true;
+                        break;
+                    }
+                }
+                if key == "$or" { stopped } else { !stopped }
+            } else {
+                let doc_values = self.resolve_path(document, &key)?;
+                self.evaluate_condition(&doc_values, &value)?
+            };
+            if !matches {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn resolve_path(&self, document: &JsValue, path: &str) -> Result<Vec<JsValue>, JsValue> {
+        let mut current = vec![document.clone()];
+        for segment in path.split('.') {
+            let key = JsValue::from_str(segment);
+            let mut next = Vec::new();
+            for value in current {
+                if Array::is_array(&value) {
+                    let arr = Array::from(&value);
+                    if let Ok(index) = segment.parse::<u32>() {
+                        next.push(arr.get(index));
+                    } else {
+                        for item in arr.iter() {
+                            if item.is_object() {
+                                next.push(Reflect::get(&item, &key)?);
+                            }
+                        }
+                    }
+                } else if value.is_object() {
+                    next.push(Reflect::get(&value, &key)?);
+                } else {
+                    next.push(JsValue::undefined());
+                }
+            }
+            current = next;
+        }
+        if current.is_empty() {
+            current.push(JsValue::undefined());
+        }
+        Ok(current)
+    }
+
+    fn evaluate_condition(&self, doc_values: &[JsValue], condition: &JsValue) -> Result<bool, JsValue> {
+        let mut members = Vec::with_capacity(doc_values.len());
+        for doc_value in doc_values {
+            members.push(doc_value.clone());
+            if Array::is_array(doc_value) {
+                members.extend(Array::from(doc_value).iter());
+            }
+        }
+        if !condition.is_object() || Array::is_array(condition) {
+            return self.any_value(&members, |doc_value| self.values_equal(doc_value, condition));
+        }
+        let options = Reflect::get(condition, &JsValue::from_str("$options"))?
+            .as_string()
+            .unwrap_or_default();
+        let keys = Object::keys(&Object::from(condition.clone()));
+        for i in 0..keys.length() {
+            let key = keys.get(i).as_string().unwrap_or_default();
+            let value = Reflect::get(condition, &JsValue::from_str(&key))?;
+            let matches = match key.as_str() {
+                "$ne" => !self.any_value(&members, |doc_value| self.values_equal(doc_value, &value))?,
+                "$nin" => !self.any_value(&members, |doc_value| self.value_in(doc_value, &value))?,
+                "$not" => !self.evaluate_condition(doc_values, &value)?,
+                "$exists" => doc_values.iter().any(|doc_value| !doc_value.is_undefined()) == value.is_truthy(),
+                "$options" => true,
+                _ => self.any_value(&members, |doc_value| self.evaluate_operator(doc_value, &key, &value, &options))?,
+            };
+            if !matches {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn any_value<F>(&self, doc_values: &[JsValue], predicate: F) -> Result<bool, JsValue>
+    where
+        F: Fn(&JsValue) -> Result<bool, JsValue>,
+    {
+        for doc_value in doc_values {
+            if predicate(doc_value)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn evaluate_operator(&self, doc_value: &JsValue, operator: &str, value: &JsValue, options: &str) -> Result<bool, JsValue> {
+        let range = |cmp: fn(f64, f64) -> bool| -> Result<bool, JsValue> {
+            let bound = value.as_f64().ok_or_else(|| JsValue::from_str("Condition value is not a number"))?;
+            Ok(doc_value.as_f64().is_some_and(|number| cmp(number, bound)))
+        };
+        match operator {
+            "$gt" => range(|a, b| a > b),
+            "$gte" => range(|a, b| a >= b),
+            "$lt" => range(|a, b| a < b),
+            "$lte" => range(|a, b| a <= b),
+            "$in" => self.value_in(doc_value, value),
+            "$regex" => {
+                let pattern = value.as_string().ok_or_else(|| JsValue::from_str("$regex value must be a string"))?;
+                match doc_value.as_string() {
+                    Some(text) => Ok(build_regex(&pattern, options)?.test(&text)),
+                    None => Ok(false),
+                }
+            }
+            "$startsWith" | "$contains" => {
+                let needle = value
+                    .as_string()
+                    .ok_or_else(|| JsValue::from_str(&format!("{} value must be a string", operator)))?;
+                let Some(text) = doc_value.as_string() else {
+                    return Ok(false);
+                };
+                let (text, needle) = if options.contains('i') {
+                    (text.to_lowercase(), needle.to_lowercase())
+                } else {
+                    (text, needle)
+                };
+                Ok(if operator == "$startsWith" { text.starts_with(&needle) } else { text.contains(&needle) })
+            }
+            _ => Err(JsValue::from_str(&format!("Unsupported operator: {}", operator))),
+        }
+    }
+
+    fn value_in(&self, doc_value: &JsValue, list: &JsValue) -> Result<bool, JsValue> {
+        if !Array::is_array(list) {
+            return Err(JsValue::from_str("$in and $nin values must be arrays"));
+        }
+        self.any_value(&Array::from(list).to_vec(), |item| self.values_equal(doc_value, item))
+    }
+
+    fn values_equal(&self, doc_value: &JsValue, cond_value: &JsValue) -> Result<bool, JsValue> {
+        if Array::is_array(doc_value) || Array::is_array(cond_value) {
+            if !Array::is_array(doc_value) || !Array::is_array(cond_value) {
+                return Ok(false);
+            }
+            let (doc_arr, cond_arr) = (Array::from(doc_value), Array::from(cond_value));
+            if doc_arr.length() != cond_arr.length() {
+                return Ok(false);
+            }
+            for i in 0..doc_arr.length() {
+                if !self.values_equal(&doc_arr.get(i), &cond_arr.get(i))? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        } else if cond_value.is_null() {
+            Ok(doc_value.is_null() || doc_value.is_undefined())
+        } else if let (Some(a), Some(b)) = (doc_value.as_string(), cond_value.as_string()) {
+            Ok(a == b)
+        } else if let (Some(a), Some(b)) = (doc_value.as_f64(), cond_value.as_f64()) {
+            Ok(a == b)
+        } else if let (Some(a), Some(b)) = (doc_value.as_bool(), cond_value.as_bool()) {
+            Ok(a == b)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// Runs a warm-up round then `ROUNDS` rounds of `round`, returning the median duration of a
+/// round in milliseconds along with the matches counted by the last one.
+fn measure(mut round: impl FnMut() -> usize) -> (f64, usize) {
+    let mut matches = round();
+    let mut durations = Vec::with_capacity(ROUNDS);
+    for _ in 0..ROUNDS {
+        let start = js_sys::Date::now();
+        matches = round();
+        durations.push(js_sys::Date::now() - start);
+    }
+    durations.sort_by(f64::total_cmp);
+    (durations[ROUNDS / 2], matches)
+}
+
+#[wasm_bindgen_test]
+fn bench_compiled_query() {
+    let schema = Schema::create(JSON::parse(r#"{
+        "version": 1,
+        "primaryKey": "id",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "name": { "type": "string" },
+            "age": { "type": "number" },
+            "tags": { "type": "array", "items": [{ "type": "string" }] }
+        }
+    }"#).unwrap()).unwrap();
+    let documents: Vec<JsValue> = (0..DOCUMENTS)
+        .map(|i| JSON::parse(&format!(
+            r#"{{ "id": "doc{}", "name": "name{}", "age": {}, "tags": ["t{}", "shared"] }}"#,
+            i, i, i % 90, i % 7
+        )).unwrap())
+        .collect();
+    let query = Query::new(JSON::parse(r#"{
+        "$or": [
+            { "age": { "$gte": 30, "$lt": 40 } },
+            { "name": { "$startsWith": "NAME1", "$options": "i" }, "tags": { "$in": ["t3"] } }
+        ],
+        "tags": { "$nin": ["t5"] }
+    }"#).unwrap(), schema.clone()).unwrap();
+    let parsed = query.parse().unwrap();
+    let core = CoreStorage {};
+
+    // Each round compiles the query once, like a find does, then evaluates every document
+    let (compiled_ms, compiled) = measure(|| {
+        let predicate = Predicate::compile(&parsed, &schema.properties).unwrap();
+        documents.iter().filter(|doc| core.matches(doc, &predicate).unwrap()).count()
+    });
+    let (reflected_ms, reflected) = measure(|| {
+        documents
+            .iter()
+            .filter(|doc| ReflectedEvaluator.document_matches_query(doc, &parsed).unwrap())
+            .count()
+    });
+
+    console_log!(
+        "Matching {} documents: compiled predicate {}ms, reflected query {}ms (median of {} rounds)",
+        DOCUMENTS, compiled_ms, reflected_ms, ROUNDS
+    );
+    assert_eq!(compiled, reflected);
+    assert!(compiled > 0);
+    assert!(
+        compiled_ms < reflected_ms,
+        "compiled predicates took {}ms, reflected queries {}ms",
+        compiled_ms, reflected_ms
+    );
+}
@@ -1,157 +1,513 @@
-use js_sys::{Array, Object, Reflect};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use js_sys::{Array, Object, Reflect, JSON};
 use wasm_bindgen::JsValue;
+use crate::query::aggregate::{AccumulatorKind, Aggregation};
+use crate::query::options::QueryOptions;
+use crate::query::predicate::{Condition, ElemMatch, FieldPath, NearSearch, Operator, Predicate, TextPattern, Value};
+use crate::query::search::Search;
+use crate::storage::internals::fulltext::{fuzzy_score, tokenize, DocumentTerms};
+use crate::storage::internals::vector::{to_vector, Hyperplanes};
+use crate::utils::{to_bytes, to_iso_date};
 
+/// Query evaluation shared by every storage implementation.
+///
+/// Missing fields resolve to `undefined`. Against missing and `null` values:
+///
+/// - `field: null` matches both, `field: value` matches neither. Other values compare strictly,
+///   without type coercion.
+/// - `$ne: null` matches neither, `$ne: value` matches both.
+/// - `$in` matches both only if it lists `null`, `$nin` the opposite.
+/// - `$gt`, `$gte`, `$lt` and `$lte` only match values of their bound's type, `$regex`,
+///   `$startsWith` and `$contains` only strings, `$size`, `$all` and `$elemMatch` only arrays.
+///   None of them match either.
+/// - Range bounds compare numbers numerically and strings lexicographically, except strings of
+///   `date` and `date-time` properties which compare as dates.
+/// - `$exists: true` matches `null` but not missing values, `$exists: false` the opposite.
+/// - `$not` matches whenever its inner condition does not.
+/// - `$text` matches when the full-text indexed values hold any of the searched terms, or every
+///   one of them with `$operator: 'and'`.
+/// - `$near` matches when the property holds a vector of its dimensions. Storages then keep the
+///   `$k` matches nearest to the searched vector, see `nearest`.
 #[derive(Debug, Clone)]
 pub struct CoreStorage {
 
 }
 
+/// Running state of an accumulator within a group.
+#[derive(Debug, Clone, Default)]
+struct AccumulatorState {
+    count: usize,
+    sum: f64,
+    numbers: usize,
+    best: Option<JsValue>,
+}
+
 impl CoreStorage {
 
-    pub(crate) fn document_matches_query(&self, document: &JsValue, query: &JsValue) -> Result<bool, JsValue> {
-        // Ensure query is an object
-        if !query.is_object() {
-            return Err(JsValue::from_str("Query must be an object"));
+    /// Evaluates a compiled query against a document.
+    pub(crate) fn matches(&self, document: &JsValue, predicate: &Predicate) -> Result<bool, JsValue> {
+        match predicate {
+            // $and stops at the first failing predicate, $or and $nor at the first passing one
+            Predicate::And(predicates) => {
+                for predicate in predicates {
+                    if !self.matches(document, predicate)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Predicate::Or(predicates) => self.matches_any(document, predicates),
+            Predicate::Nor(predicates) => Ok(!self.matches_any(document, predicates)?),
+            Predicate::Field(path, condition) => {
+                // Nested values are addressed with dot paths
+                let doc_values = self.resolve_path(document, path)?;
+                self.evaluate_condition(&doc_values, condition)
+            }
+            Predicate::Text(search) => Ok(search.matches(&self.document_terms(document, &search.fields)?)),
+            Predicate::Near(search) => Ok(self.vector_at(document, &search.field, search.vector.len())?.is_some()),
         }
+    }
 
-        let keys = Object::keys(&Object::from(query.clone()));
+    /// Keeps the `k` documents nearest to the searched vector, nearest first, ties broken by the
+    /// primary key. Documents must match the search, so that they hold a vector.
+    pub(crate) fn nearest(&self, documents: Vec<JsValue>, search: &NearSearch, primary_key: &str) -> Result<Vec<JsValue>, JsValue> {
+        let mut ranked = Vec::with_capacity(documents.len());
+        for document in documents {
+            let Some(vector) = self.vector_at(&document, &search.field, search.vector.len())? else {
+                continue;
+            };
+            let key = Reflect::get(&document, &JsValue::from_str(primary_key))?;
+            ranked.push((search.metric.distance(&search.vector, &vector), key, document));
+        }
+        ranked.sort_by(|(a, a_key, _), (b, b_key, _)| {
+            a.partial_cmp(b)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| self.compare_primary_keys(a_key, b_key))
+        });
+        Ok(ranked.into_iter().take(search.k).map(|(_, _, document)| document).collect())
+    }
 
-        for i in 0..keys.length() {
-            let key = keys.get(i).as_string().unwrap_or_default();
-            let value = Reflect::get(query, &JsValue::from_str(&key))
-                .map_err(|e| JsValue::from(format!("Failed to get the query value, err {:?}", e)))?;
+    /// Hashes the vectors of a document's approximately indexed properties into their buckets.
+    pub(crate) fn vector_buckets(&self, document: &JsValue, paths: &[(String, usize)]) -> Result<Vec<String>, JsValue> {
+        let mut buckets = Vec::new();
+        for (path, dimensions) in paths {
+            if let Some(vector) = self.vector_at(document, &FieldPath::new(path), *dimensions)? {
+                buckets.extend(Hyperplanes::new(*dimensions).buckets(path, &vector));
+            }
+        }
+        Ok(buckets)
+    }
 
-            if key == "$and" {
-                // $and operator: all conditions must be true
-                if !Array::is_array(&value) {
-                    return Err(JsValue::from_str("$and must be an array"));
-                }
-                let arr = Array::from(&value);
-                for j in 0..arr.length() {
-                    let item = arr.get(j);
-                    let matches = self.document_matches_query(document, &item)?;
-                    if !matches {
-                        return Ok(false);
-                    }
-                }
+    /// Reads the vector a path resolves to, `None` unless it has the expected dimensions.
+    fn vector_at(&self, document: &JsValue, path: &FieldPath, dimensions: usize) -> Result<Option<Vec<f64>>, JsValue> {
+        Ok(self
+            .resolve_path(document, path)?
+            .first()
+            .and_then(|value| to_vector(value, dimensions)))
+    }
+
+    /// Tokenizes the string values of a document's full-text indexed properties.
+    pub(crate) fn document_terms(&self, document: &JsValue, fields: &[FieldPath]) -> Result<DocumentTerms, JsValue> {
+        let mut texts = Vec::new();
+        for field in fields {
+            let doc_values = self.resolve_path(document, field)?;
+            texts.extend(self.expand_arrays(&doc_values).iter().filter_map(JsValue::as_string));
+        }
+        Ok(DocumentTerms::new(texts.iter().map(String::as_str)))
+    }
+
+    fn matches_any(&self, document: &JsValue, predicates: &[Predicate]) -> Result<bool, JsValue> {
+        for predicate in predicates {
+            if self.matches(document, predicate)? {
                 return Ok(true);
-            } else if key == "$or" {
-                // $or operator: at least one condition must be true
-                if !Array::is_array(&value) {
-                    return Err(JsValue::from_str("$or must be an array"));
-                }
-                let arr = Array::from(&value);
-                for j in 0..arr.length() {
-                    let item = arr.get(j);
-                    let matches = self.document_matches_query(document, &item)?;
-                    if matches {
-                        return Ok(true);
-                    }
-                }
-                return Ok(false);
-            } else {
-                // Attribute condition
-                let doc_value = Reflect::get(document, &JsValue::from_str(&key))
-                    .map_err(|e| JsValue::from(format!("Failed to get the document key, err {:?}", e)))?;
-
-                let matches = self.evaluate_condition(&doc_value, &value)?;
-                if !matches {
-                    return Ok(false);
-                }
             }
         }
-        Ok(true)
+        Ok(false)
     }
 
-    fn evaluate_condition(&self, doc_value: &JsValue, condition: &JsValue) -> Result<bool, JsValue> {
-        if condition.is_object() && !Array::is_array(condition) {
-            // Condition is an object with operators
-            let keys = Object::keys(&Object::from(condition.clone()));
-            for i in 0..keys.length() {
-                let key = keys.get(i).as_string().unwrap_or_default();
-                let value = Reflect::get(condition, &JsValue::from_str(&key))?;
-                match key.as_str() {
-                    "$gt" => {
-                        let res = self.compare_values(doc_value, &value, |a:f64, b:f64| a > b)?;
-                        if !res {
-                            return Ok(false);
-                        }
+    /// Sorts documents by the sort criteria of the options.
+    ///
+    /// The sort is stable and ties are broken by the primary key, so the order does not depend
+    /// on the order in which the storage yields documents. When the options resume after a
+    /// keyset, only documents ordered strictly after it are kept.
+    pub(crate) fn sort_documents(&self, documents: Vec<JsValue>, options: &QueryOptions, primary_key: &str) -> Result<Vec<JsValue>, JsValue> {
+        if !options.is_ordered() {
+            return Ok(documents);
+        }
+        let mut keyed = Vec::with_capacity(documents.len());
+        for document in documents {
+            let keyset = self.keyset(&document, options, primary_key)?;
+            keyed.push((keyset, document));
+        }
+        if let Some(after) = &options.after {
+            keyed.retain(|(keyset, _)| self.compare_keysets(options, keyset, after) == Ordering::Greater);
+        }
+        keyed.sort_by(|(a, _), (b, _)| self.compare_keysets(options, a, b));
+        Ok(keyed.into_iter().map(|(_, document)| document).collect())
+    }
+
+    /// Returns the sort values of a document followed by its primary key.
+    pub(crate) fn keyset(&self, document: &JsValue, options: &QueryOptions, primary_key: &str) -> Result<Vec<JsValue>, JsValue> {
+        let mut keyset = Vec::with_capacity(options.sort.len() + 1);
+        for sort_key in &options.sort {
+            keyset.push(self.first_value(document, &sort_key.path)?);
+        }
+        keyset.push(Reflect::get(document, &JsValue::from_str(primary_key))?);
+        Ok(keyset)
+    }
+
+    fn compare_keysets(&self, options: &QueryOptions, a: &[JsValue], b: &[JsValue]) -> Ordering {
+        for (index, sort_key) in options.sort.iter().enumerate() {
+            let ordering = sort_key.compare(&a[index], &b[index]);
+            let ordering = if sort_key.descending { ordering.reverse() } else { ordering };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        let pk_index = options.sort.len();
+        self.compare_primary_keys(&a[pk_index], &b[pk_index])
+    }
+
+    /// Groups documents and computes the accumulators of each group.
+    ///
+    /// Missing and `null` groupBy values form the same group. `$sum`, `$avg`, `$min` and `$max`
+    /// ignore values that do not have the type of their property; accumulators
+    /// without any value yield `null`, except `$sum` which yields 0. Rows are ordered by their
+    /// groupBy values.
+    pub(crate) fn aggregate(&self, documents: &[JsValue], aggregation: &Aggregation) -> Result<JsValue, JsValue> {
+        let mut groups: Vec<(Vec<JsValue>, Vec<AccumulatorState>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for document in documents {
+            let mut keys = Vec::with_capacity(aggregation.group_by.len());
+            for group_key in &aggregation.group_by {
+                let mut value = self.first_value(document, &group_key.path)?;
+                // Dates group by the instant they hold, whatever offset they were written with
+                if group_key.date {
+                    if let Some(iso) = to_iso_date(&value) {
+                        value = JsValue::from_str(&iso);
                     }
-                    "$gte" => {
-                        let res = self.compare_values(doc_value, &value, |a:f64, b:f64| a >= b)?;
-                        if !res {
-                            return Ok(false);
+                }
+                keys.push(if value.is_undefined() { JsValue::NULL } else { value });
+            }
+            let id = JSON::stringify(&keys.iter().collect::<Array>())?
+                .as_string()
+                .unwrap_or_default();
+            let position = match positions.get(&id) {
+                Some(position) => *position,
+                None => {
+                    let states = vec![AccumulatorState::default(); aggregation.accumulators.len()];
+                    groups.push((keys, states));
+                    positions.insert(id, groups.len() - 1);
+                    groups.len() - 1
+                }
+            };
+            let states = &mut groups[position].1;
+            for (accumulator, state) in aggregation.accumulators.iter().zip(states.iter_mut()) {
+                state.count += 1;
+                let field = match &accumulator.field {
+                    Some(field) => field,
+                    None => continue,
+                };
+                let value = self.first_value(document, &field.path)?;
+                match accumulator.kind {
+                    AccumulatorKind::Sum | AccumulatorKind::Avg => {
+                        if let Some(number) = value.as_f64() {
+                            state.sum += number;
+                            state.numbers += 1;
                         }
                     }
-                    "$lt" => {
-                        let res = self.compare_values(doc_value, &value, |a:f64, b:f64| a < b)?;
-                        if !res {
-                            return Ok(false);
+                    AccumulatorKind::Min | AccumulatorKind::Max => {
+                        if !field.matches_type(&value) {
+                            continue;
                         }
-                    }
-                    "$lte" => {
-                        let res = self.compare_values(doc_value, &value, |a:f64, b:f64| a <= b)?;
-                        if !res {
-                            return Ok(false);
+                        let replace = match &state.best {
+                            None => true,
+                            Some(best) => {
+                                let ordering = field.compare(&value, best);
+                                if accumulator.kind == AccumulatorKind::Min {
+                                    ordering == Ordering::Less
+                                } else {
+                                    ordering == Ordering::Greater
+                                }
+                            }
+                        };
+                        if replace {
+                            state.best = Some(value);
                         }
                     }
-                    "$in" => {
-                        if !Array::is_array(&value) {
-                            return Err(JsValue::from_str("$in value must be an array"));
-                        }
-                        let arr = Array::from(&value);
-                        let mut found = false;
-                        for j in 0..arr.length() {
-                            let item = arr.get(j);
-                            if self.values_equal(doc_value, &item)? {
-                                found = true;
-                                break;
+                    AccumulatorKind::Count => {}
+                }
+            }
+        }
+
+        groups.sort_by(|(a, _), (b, _)| {
+            aggregation.group_by
+                .iter()
+                .enumerate()
+                .map(|(index, group_key)| group_key.compare(&a[index], &b[index]))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let rows = Array::new();
+        for (keys, states) in groups {
+            let row = Object::new();
+            for (group_key, value) in aggregation.group_by.iter().zip(keys) {
+                Reflect::set(&row, &JsValue::from_str(&group_key.path), &value)?;
+            }
+            for (accumulator, state) in aggregation.accumulators.iter().zip(states) {
+                let result = match accumulator.kind {
+                    AccumulatorKind::Count => JsValue::from_f64(state.count as f64),
+                    AccumulatorKind::Sum => JsValue::from_f64(state.sum),
+                    AccumulatorKind::Avg if state.numbers > 0 => JsValue::from_f64(state.sum / state.numbers as f64),
+                    AccumulatorKind::Avg => JsValue::NULL,
+                    AccumulatorKind::Min | AccumulatorKind::Max => state.best.unwrap_or(JsValue::NULL),
+                };
+                Reflect::set(&row, &JsValue::from_str(&accumulator.name), &result)?;
+            }
+            rows.push(&row);
+        }
+        Ok(rows.into())
+    }
+
+    /// Scores documents against a fuzzy search and returns the hits as `{ doc, score }`, best
+    /// first.
+    ///
+    /// Each searched term scores its closest term among the searched properties, 1 when found
+    /// as is. A document scores the average over the searched terms and is a hit when at least
+    /// one of them is close enough. Ties are broken by the primary key.
+    pub(crate) fn search(&self, documents: &[JsValue], search: &Search, primary_key: &str) -> Result<JsValue, JsValue> {
+        let mut hits = Vec::new();
+        for document in documents {
+            let mut terms = HashSet::new();
+            for field in &search.fields {
+                let doc_values = self.resolve_path(document, field)?;
+                for text in self.expand_arrays(&doc_values).iter().filter_map(JsValue::as_string) {
+                    terms.extend(tokenize(&text));
+                }
+            }
+            let mut total = 0.0;
+            let mut matched = false;
+            for searched in &search.terms {
+                let best = terms
+                    .iter()
+                    .filter_map(|term| fuzzy_score(searched, term, search.max_distance))
+                    .fold(None, |best: Option<f64>, score| Some(best.map_or(score, |best| best.max(score))));
+                if let Some(best) = best {
+                    total += best;
+                    matched = true;
+                }
+            }
+            if matched {
+                let key = Reflect::get(document, &JsValue::from_str(primary_key))?;
+                hits.push((total / search.terms.len() as f64, key, document));
+            }
+        }
+        hits.sort_by(|(a, a_key, _), (b, b_key, _)| {
+            b.partial_cmp(a)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| self.compare_primary_keys(a_key, b_key))
+        });
+
+        let results = Array::new();
+        for (score, _, document) in hits.into_iter().take(search.limit.unwrap_or(usize::MAX)) {
+            let hit = Object::new();
+            Reflect::set(&hit, &JsValue::from_str("doc"), document)?;
+            Reflect::set(&hit, &JsValue::from_str("score"), &JsValue::from_f64(score))?;
+            results.push(&hit);
+        }
+        Ok(results.into())
+    }
+
+    fn first_value(&self, document: &JsValue, path: &str) -> Result<JsValue, JsValue> {
+        Ok(self.resolve_path(document, &FieldPath::new(path))?
+            .into_iter()
+            .next()
+            .unwrap_or(JsValue::undefined()))
+    }
+
+    fn compare_primary_keys(&self, a: &JsValue, b: &JsValue) -> Ordering {
+        match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => a.as_string().cmp(&b.as_string()),
+        }
+    }
+
+    /// Resolves a dot-separated path against a document.
+    ///
+    /// Arrays met along the way are traversed element by element (or addressed directly with a
+    /// numeric segment), so a single path can yield several values. A missing path yields a
+    /// single `undefined`.
+    fn resolve_path(&self, document: &JsValue, path: &FieldPath) -> Result<Vec<JsValue>, JsValue> {
+        let mut current = vec![document.clone()];
+        for segment in &path.segments {
+            let mut next = Vec::new();
+            for value in current {
+                if Array::is_array(&value) {
+                    let arr = Array::from(&value);
+                    if let Some(index) = segment.index {
+                        next.push(arr.get(index));
+                    } else {
+                        for item in arr.iter() {
+                            if item.is_object() {
+                                next.push(Reflect::get(&item, &segment.key)?);
                             }
                         }
-                        if !found {
-                            return Ok(false);
+                    }
+                } else if value.is_object() {
+                    next.push(Reflect::get(&value, &segment.key)?);
+                } else {
+                    next.push(JsValue::undefined());
+                }
+            }
+            current = next;
+        }
+        if current.is_empty() {
+            current.push(JsValue::undefined());
+        }
+        Ok(current)
+    }
+
+    fn evaluate_condition(&self, doc_values: &[JsValue], condition: &Condition) -> Result<bool, JsValue> {
+        match condition {
+            Condition::Equals(value) => Ok(self
+                .expand_arrays(doc_values)
+                .iter()
+                .any(|doc_value| self.values_equal(doc_value, value))),
+            Condition::Operators(operators) => self.evaluate_operators(doc_values, operators),
+        }
+    }
+
+    /// Evaluates operators against every value a path resolved to.
+    ///
+    /// Positive operators match when any value satisfies them, negations ($ne, $nin, $not)
+    /// match when no value satisfies their positive form. Array values take part both whole
+    /// and element by element, so a scalar condition checks array membership, while
+    /// $size, $all and $elemMatch look at the arrays themselves.
+    fn evaluate_operators(&self, doc_values: &[JsValue], operators: &[Operator]) -> Result<bool, JsValue> {
+        let members = self.expand_arrays(doc_values);
+        for operator in operators {
+            let matches = match operator {
+                Operator::Ne(value) => !members.iter().any(|doc_value| self.values_equal(doc_value, value)),
+                Operator::Nin(list) => !members.iter().any(|doc_value| self.value_in(doc_value, list)),
+                Operator::Not(inner) => !self.evaluate_operators(doc_values, inner)?,
+                Operator::Exists(expected) => {
+                    doc_values.iter().any(|doc_value| !doc_value.is_undefined()) == *expected
+                }
+                Operator::Size(_) | Operator::All(_) | Operator::ElemMatch(_) => {
+                    let mut matched = false;
+                    for doc_value in doc_values {
+                        if self.evaluate_array_operator(doc_value, operator)? {
+                            matched = true;
+                            break;
                         }
                     }
-                    _ => {
-                        return Err(JsValue::from_str(&format!("Unsupported operator: {}", key)));
+                    matched
+                }
+                _ => members.iter().any(|doc_value| self.evaluate_operator(doc_value, operator)),
+            };
+            if !matches {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Adds the elements of every array value next to the array itself.
+    fn expand_arrays(&self, doc_values: &[JsValue]) -> Vec<JsValue> {
+        let mut members = Vec::with_capacity(doc_values.len());
+        for doc_value in doc_values {
+            members.push(doc_value.clone());
+            if Array::is_array(doc_value) {
+                members.extend(Array::from(doc_value).iter());
+            }
+        }
+        members
+    }
+
+    fn evaluate_array_operator(&self, doc_value: &JsValue, operator: &Operator) -> Result<bool, JsValue> {
+        if !Array::is_array(doc_value) {
+            return Ok(false);
+        }
+        let arr = Array::from(doc_value);
+        match operator {
+            Operator::Size(size) => Ok(arr.length() as f64 == *size),
+            Operator::All(expected) => {
+                let members = arr.to_vec();
+                Ok(expected
+                    .iter()
+                    .all(|value| members.iter().any(|member| self.values_equal(member, value))))
+            }
+            Operator::ElemMatch(ElemMatch::Document(predicate)) => {
+                for element in arr.iter() {
+                    if element.is_object() && self.matches(&element, predicate)? {
+                        return Ok(true);
                     }
                 }
+                Ok(false)
             }
-            Ok(true)
-        } else {
-            // Direct value comparison
-            self.values_equal(doc_value, condition)
+            Operator::ElemMatch(ElemMatch::Value(operators)) => {
+                for element in arr.iter() {
+                    if self.evaluate_operators(&[element], operators)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            _ => Ok(false),
         }
     }
 
-    fn compare_values<F>(
-        &self,
-        doc_value: &JsValue,
-        cond_value: &JsValue,
-        cmp: F,
-    ) -> Result<bool, JsValue>
-    where
-        F: Fn(f64, f64) -> bool,
-    {
-        let doc_num = doc_value
-            .as_f64()
-            .ok_or_else(|| JsValue::from_str("Document value is not a number"))?;
-        let cond_num = cond_value
-            .as_f64()
-            .ok_or_else(|| JsValue::from_str("Condition value is not a number"))?;
-        Ok(cmp(doc_num, cond_num))
+    fn evaluate_operator(&self, doc_value: &JsValue, operator: &Operator) -> bool {
+        // Missing, null and values of another type never satisfy an operator
+        match operator {
+            Operator::Gt(bound) => bound.compare(doc_value) == Some(Ordering::Greater),
+            Operator::Gte(bound) => bound.compare(doc_value).is_some_and(|ordering| ordering != Ordering::Less),
+            Operator::Lt(bound) => bound.compare(doc_value) == Some(Ordering::Less),
+            Operator::Lte(bound) => bound.compare(doc_value).is_some_and(|ordering| ordering != Ordering::Greater),
+            Operator::In(list) => self.value_in(doc_value, list),
+            Operator::Regex(regex) => doc_value.as_string().is_some_and(|text| regex.test(&text)),
+            Operator::StartsWith(pattern) => self
+                .prepare_text(doc_value, pattern)
+                .is_some_and(|text| text.starts_with(&pattern.needle)),
+            Operator::Contains(pattern) => self
+                .prepare_text(doc_value, pattern)
+                .is_some_and(|text| text.contains(&pattern.needle)),
+            _ => false,
+        }
     }
 
-    fn values_equal(&self, doc_value: &JsValue, cond_value: &JsValue) -> Result<bool, JsValue> {
-        if doc_value.is_string() && cond_value.is_string() {
-            Ok(doc_value.as_string() == cond_value.as_string())
-        } else if doc_value.as_f64().is_some() {
-            Ok(doc_value.as_f64() == cond_value.as_f64())
-        } else if doc_value.is_truthy() || cond_value.is_falsy() {
-            Ok(doc_value.as_bool() == cond_value.as_bool())
-        } else {
-            Ok(false)
+    fn prepare_text(&self, doc_value: &JsValue, pattern: &TextPattern) -> Option<String> {
+        let text = doc_value.as_string()?;
+        Some(if pattern.ignore_case { text.to_lowercase() } else { text })
+    }
+
+    fn value_in(&self, doc_value: &JsValue, list: &[Value]) -> bool {
+        list.iter().any(|value| self.values_equal(doc_value, value))
+    }
+
+    fn values_equal(&self, doc_value: &JsValue, value: &Value) -> bool {
+        match value {
+            // Arrays are only equal to arrays with the same elements in the same order
+            Value::Array(items) => {
+                if !Array::is_array(doc_value) {
+                    return false;
+                }
+                let arr = Array::from(doc_value);
+                arr.length() as usize == items.len()
+                    && items
+                        .iter()
+                        .enumerate()
+                        .all(|(i, item)| self.values_equal(&arr.get(i as u32), item))
+            }
+            _ if Array::is_array(doc_value) => false,
+            Value::Null => doc_value.is_null() || doc_value.is_undefined(),
+            Value::String(string) => doc_value.as_string().is_some_and(|doc_string| &doc_string == string),
+            Value::Number(number) => doc_value.as_f64().is_some_and(|doc_number| doc_number == *number),
+            Value::Bool(boolean) => doc_value.as_bool() == Some(*boolean),
+            Value::Bytes(bytes) => to_bytes(doc_value).is_some_and(|doc_bytes| &doc_bytes == bytes),
         }
     }
 
-}
\ No newline at end of file
+}
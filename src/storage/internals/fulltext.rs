@@ -0,0 +1,305 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::JsValue;
+use crate::query::predicate::TextSearch;
+
+/// Splits text into lowercase terms, at every character that is not alphanumeric.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+/// Optimal string alignment distance between two terms: the number of insertions, deletions,
+/// substitutions and transpositions of adjacent characters turning one into the other, with
+/// no substring edited twice.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// The trigrams of a term, padded so that its first characters weigh as much as the others.
+pub fn trigrams(term: &str) -> HashSet<String> {
+    let padded: Vec<char> = format!("  {} ", term).chars().collect();
+    padded.windows(3).map(|window| window.iter().collect()).collect()
+}
+
+/// Share of trigrams two terms have in common, from 0 to 1.
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let shared = a.intersection(&b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Scores how close a document term is to a searched term, from 0 to 1, `None` when they are
+/// further apart than the edits the searched term tolerates.
+///
+/// Terms of up to 2 characters have to match exactly and terms of up to 5 tolerate a single
+/// edit, longer terms tolerate `max_distance` edits. Close terms score the average of their
+/// edit and trigram similarities, so that typos in short terms weigh more than in long ones.
+pub fn fuzzy_score(searched: &str, term: &str, max_distance: usize) -> Option<f64> {
+    if searched == term {
+        return Some(1.0);
+    }
+    let (searched_length, term_length) = (searched.chars().count(), term.chars().count());
+    let allowed = match searched_length {
+        0..=2 => 0,
+        3..=5 => max_distance.min(1),
+        _ => max_distance,
+    };
+    if searched_length.abs_diff(term_length) > allowed {
+        return None;
+    }
+    let distance = edit_distance(searched, term);
+    if distance > allowed {
+        return None;
+    }
+    let edit_similarity = 1.0 - distance as f64 / searched_length.max(term_length) as f64;
+    Some((edit_similarity + trigram_similarity(searched, term)) / 2.0)
+}
+
+/// The terms found in the full-text indexed values of a document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentTerms {
+    /// How many times each term occurs.
+    pub(crate) frequencies: HashMap<String, usize>,
+    /// Total number of terms.
+    pub(crate) length: usize,
+}
+
+impl DocumentTerms {
+    pub fn new<'a>(texts: impl IntoIterator<Item = &'a str>) -> DocumentTerms {
+        let mut terms = DocumentTerms::default();
+        for text in texts {
+            for term in tokenize(text) {
+                *terms.frequencies.entry(term).or_insert(0) += 1;
+                terms.length += 1;
+            }
+        }
+        terms
+    }
+
+    pub(crate) fn contains(&self, term: &str) -> bool {
+        self.frequencies.contains_key(term)
+    }
+
+    /// Converts the terms into the record `IndexDB` persists for the document with this primary key.
+    ///
+    /// The distinct terms are listed under `terms`, which backs a multi-entry index.
+    pub(crate) fn to_record(&self, key: &JsValue) -> Result<JsValue, JsValue> {
+        let mut distinct: Vec<&String> = self.frequencies.keys().collect();
+        distinct.sort();
+        let frequencies = Object::new();
+        for (term, count) in &self.frequencies {
+            Reflect::set(&frequencies, &JsValue::from_str(term), &JsValue::from_f64(*count as f64))?;
+        }
+        let record = Object::new();
+        Reflect::set(&record, &JsValue::from_str("key"), key)?;
+        Reflect::set(&record, &JsValue::from_str("terms"), &distinct.into_iter().map(|term| JsValue::from_str(term)).collect::<Array>())?;
+        Reflect::set(&record, &JsValue::from_str("frequencies"), &frequencies)?;
+        Reflect::set(&record, &JsValue::from_str("length"), &JsValue::from_f64(self.length as f64))?;
+        Ok(record.into())
+    }
+
+    /// Reads a record written by `to_record`, returning the primary key along with the terms.
+    pub(crate) fn from_record(record: &JsValue) -> Result<(JsValue, DocumentTerms), JsValue> {
+        let key = Reflect::get(record, &JsValue::from_str("key"))?;
+        let frequencies = Reflect::get(record, &JsValue::from_str("frequencies"))?;
+        if !frequencies.is_object() {
+            return Err(JsValue::from_str("Invalid full-text record"));
+        }
+        let mut terms = DocumentTerms::default();
+        for term in Object::keys(&Object::from(frequencies.clone())).iter() {
+            let count = Reflect::get(&frequencies, &term)?.as_f64().unwrap_or(0.0) as usize;
+            terms.frequencies.insert(term.as_string().unwrap_or_default(), count);
+        }
+        terms.length = Reflect::get(record, &JsValue::from_str("length"))?
+            .as_f64()
+            .unwrap_or(0.0) as usize;
+        Ok((key, terms))
+    }
+}
+
+/// Orders the documents matching a search by relevance, most relevant first.
+///
+/// `candidates` must hold every document containing at least one of the searched terms, out of
+/// the `total` documents of the collection, since document frequencies are counted from them.
+/// Each searched term a document holds adds its frequency, relative to the document length,
+/// weighted by how rare the term is. Ties keep the order of the candidates.
+pub fn rank<K>(search: &TextSearch, candidates: Vec<(K, &DocumentTerms)>, total: usize) -> Vec<K> {
+    let mut document_frequencies: HashMap<&str, usize> = HashMap::new();
+    for (_, terms) in &candidates {
+        for term in &search.terms {
+            if terms.contains(term) {
+                *document_frequencies.entry(term.as_str()).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut scored: Vec<(K, f64)> = candidates
+        .into_iter()
+        .filter(|(_, terms)| search.matches(terms))
+        .map(|(key, terms)| {
+            let score = search
+                .terms
+                .iter()
+                .filter_map(|term| {
+                    let frequency = *terms.frequencies.get(term)?;
+                    let documents = *document_frequencies.get(term.as_str())?;
+                    let idf = (1.0 + total.max(documents) as f64 / documents as f64).ln();
+                    Some(frequency as f64 / terms.length.max(1) as f64 * idf)
+                })
+                .sum();
+            (key, score)
+        })
+        .collect();
+    scored.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+    scored.into_iter().map(|(key, _)| key).collect()
+}
+
+/// An inverted index over the full-text indexed properties of a collection.
+///
+/// Every document of the collection is indexed, including those without any term, so that the
+/// index knows the size of the collection when weighting terms.
+#[derive(Debug, Default)]
+pub struct FullTextIndex {
+    /// The terms of each document, by primary key.
+    documents: HashMap<String, DocumentTerms>,
+    /// The primary keys of the documents holding each term.
+    postings: HashMap<String, HashSet<String>>,
+}
+
+impl FullTextIndex {
+    /// Indexes a document, replacing its previous terms.
+    pub fn insert(&mut self, key: String, terms: DocumentTerms) {
+        self.remove(&key);
+        for term in terms.frequencies.keys() {
+            self.postings.entry(term.clone()).or_default().insert(key.clone());
+        }
+        self.documents.insert(key, terms);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(terms) = self.documents.remove(key) else {
+            return;
+        };
+        for term in terms.frequencies.keys() {
+            if let Some(keys) = self.postings.get_mut(term) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+    }
+
+    /// Returns the primary keys of the documents matching the search, most relevant first.
+    pub fn search(&self, search: &TextSearch) -> Vec<String> {
+        let mut keys: Vec<&String> = search
+            .terms
+            .iter()
+            .filter_map(|term| self.postings.get(term))
+            .flatten()
+            .collect::<HashSet<&String>>()
+            .into_iter()
+            .collect();
+        // Postings are unordered, sorting keeps ties stable from one search to the next
+        keys.sort();
+        let candidates = keys
+            .into_iter()
+            .map(|key| (key.clone(), &self.documents[key]))
+            .collect();
+        rank(search, candidates, self.documents.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::predicate::TextMode;
+
+    fn search(terms: &[&str], mode: TextMode) -> TextSearch {
+        TextSearch {
+            terms: terms.iter().map(|term| term.to_string()).collect(),
+            mode,
+            fields: vec![],
+        }
+    }
+
+    fn index() -> FullTextIndex {
+        let mut index = FullTextIndex::default();
+        index.insert("1".to_string(), DocumentTerms::new(["Rust and WebAssembly", "Notes on wasm-bindgen"]));
+        index.insert("2".to_string(), DocumentTerms::new(["Rust, rust, RUST!"]));
+        index.insert("3".to_string(), DocumentTerms::new(["Gardening"]));
+        index
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Hello, World! wasm-bindgen_2 Ünïcode"), vec!["hello", "world", "wasm", "bindgen", "2", "ünïcode"]);
+        assert!(tokenize(" ,;- ").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("john", "john"), 0);
+        assert_eq!(edit_distance("jonh", "john"), 1);
+        assert_eq!(edit_distance("jon", "john"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        // Optimal string alignment does not edit a transposed pair again
+        assert_eq!(edit_distance("ca", "abc"), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("john", "john", 2), Some(1.0));
+        let typo = fuzzy_score("jonh", "john", 2).unwrap();
+        assert!(typo > 0.0 && typo < 1.0);
+        // Short terms tolerate fewer edits
+        assert_eq!(fuzzy_score("jo", "jon", 2), None);
+        assert_eq!(fuzzy_score("jonh", "jane", 2), None);
+        assert!(fuzzy_score("johnathan", "jonathon", 2).is_some());
+        assert_eq!(fuzzy_score("johnathan", "jonathon", 0), None);
+    }
+
+    #[test]
+    fn test_full_text_index_search() {
+        let index = index();
+        assert_eq!(index.search(&search(&["rust"], TextMode::Any)), vec!["2", "1"]);
+        assert_eq!(index.search(&search(&["rust", "wasm"], TextMode::All)), vec!["1"]);
+        assert_eq!(index.search(&search(&["wasm", "gardening"], TextMode::Any)).len(), 2);
+        assert!(index.search(&search(&["python"], TextMode::Any)).is_empty());
+    }
+
+    #[test]
+    fn test_full_text_index_remove() {
+        let mut index = index();
+        index.remove("2");
+        index.insert("1".to_string(), DocumentTerms::new(["Gardening notes"]));
+        assert!(index.search(&search(&["rust"], TextMode::Any)).is_empty());
+        assert_eq!(index.search(&search(&["gardening"], TextMode::Any)), vec!["3", "1"]);
+        assert!(!index.postings.contains_key("rust"));
+    }
+}
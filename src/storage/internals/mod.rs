@@ -1,2 +1,6 @@
 pub mod base_storage;
 pub mod core;
+pub mod fulltext;
+pub mod vector;
+#[cfg(feature = "bench")]
+mod bench;
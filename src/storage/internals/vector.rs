@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+use js_sys::Array;
+use wasm_bindgen::JsValue;
+
+/// Number of hash tables of the approximate index, a vector is a candidate when it shares a
+/// bucket with the searched one in any of them.
+const TABLES: usize = 8;
+
+/// Number of hyperplanes of each table, and so of bits of its buckets.
+const BITS: usize = 8;
+
+/// Seed of the hyperplanes, which must not change once buckets are persisted.
+const SEED: u64 = 0x5eed_2024_ac1d_b00c;
+
+/// How `$near` measures the distance between two vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    /// One minus the cosine of their angle, 1 when either vector is zero.
+    Cosine,
+    /// The opposite of their dot product.
+    Dot,
+    /// The straight-line distance between them.
+    Euclidean,
+}
+
+impl Metric {
+    pub fn parse(metric: &str) -> Option<Metric> {
+        match metric {
+            "cosine" => Some(Metric::Cosine),
+            "dot" => Some(Metric::Dot),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
+        }
+    }
+
+    /// Distance between two vectors of the same dimensions, the smaller the closer.
+    pub fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            Metric::Cosine => {
+                let norms = (dot(a, a) * dot(b, b)).sqrt();
+                if norms == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot(a, b) / norms
+                }
+            }
+            Metric::Dot => -dot(a, b),
+            Metric::Euclidean => a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt(),
+        }
+    }
+}
+
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Reads a vector of `dimensions` finite numbers, `None` for any other value.
+pub fn to_vector(value: &JsValue, dimensions: usize) -> Option<Vec<f64>> {
+    if !Array::is_array(value) {
+        return None;
+    }
+    let array = Array::from(value);
+    if array.length() as usize != dimensions {
+        return None;
+    }
+    array
+        .iter()
+        .map(|component| component.as_f64().filter(|component| component.is_finite()))
+        .collect()
+}
+
+/// Random hyperplanes hashing vectors into buckets, so that vectors pointing in close
+/// directions likely share a bucket.
+///
+/// Hyperplanes only depend on the dimensions, so buckets computed in an earlier session stay
+/// valid. Since buckets group vectors by direction, they suit cosine distance best.
+#[derive(Debug, Clone)]
+pub struct Hyperplanes {
+    planes: Vec<Vec<f64>>,
+}
+
+impl Hyperplanes {
+    pub fn new(dimensions: usize) -> Hyperplanes {
+        let mut state = SEED ^ dimensions as u64;
+        Hyperplanes {
+            planes: (0..TABLES * BITS)
+                .map(|_| (0..dimensions).map(|_| gaussian(&mut state)).collect())
+                .collect(),
+        }
+    }
+
+    /// Returns the bucket of a vector in each table, prefixed with the path of its property
+    /// so that the vectors of several properties can share an index.
+    pub fn buckets(&self, path: &str, vector: &[f64]) -> Vec<String> {
+        self.planes
+            .chunks(BITS)
+            .enumerate()
+            .map(|(table, planes)| {
+                let bits = planes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, plane)| dot(plane, vector) >= 0.0)
+                    .fold(0u32, |bits, (bit, _)| bits | 1 << bit);
+                format!("{}:{}:{:02x}", path, table, bits)
+            })
+            .collect()
+    }
+}
+
+/// Draws a normally distributed number, advancing a SplitMix64 state.
+fn gaussian(state: &mut u64) -> f64 {
+    let mut uniform = || {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // 53 random bits, in (0, 1] so that the logarithm below is finite
+        ((z >> 11) + 1) as f64 / (1u64 << 53) as f64
+    };
+    let (u, v) = (uniform(), uniform());
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+/// An approximate index over the vector properties of a collection, mapping buckets to the
+/// primary keys of the documents hashed into them.
+#[derive(Debug, Default)]
+pub struct VectorIndex {
+    /// The buckets of each document, by primary key.
+    documents: HashMap<String, Vec<String>>,
+    /// The primary keys of the documents in each bucket.
+    buckets: HashMap<String, HashSet<String>>,
+}
+
+impl VectorIndex {
+    /// Indexes a document, replacing its previous buckets.
+    pub fn insert(&mut self, key: String, buckets: Vec<String>) {
+        self.remove(&key);
+        for bucket in &buckets {
+            self.buckets.entry(bucket.clone()).or_default().insert(key.clone());
+        }
+        self.documents.insert(key, buckets);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(buckets) = self.documents.remove(key) else {
+            return;
+        };
+        for bucket in buckets {
+            if let Some(keys) = self.buckets.get_mut(&bucket) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.buckets.remove(&bucket);
+                }
+            }
+        }
+    }
+
+    /// Returns the primary keys of the documents sharing any of the buckets, sorted.
+    pub fn candidates(&self, buckets: &[String]) -> Vec<String> {
+        let mut keys: Vec<String> = buckets
+            .iter()
+            .filter_map(|bucket| self.buckets.get(bucket))
+            .flatten()
+            .cloned()
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_distance() {
+        let (a, b) = ([1.0, 0.0], [0.0, 2.0]);
+        assert_eq!(Metric::Cosine.distance(&a, &b), 1.0);
+        assert_eq!(Metric::Cosine.distance(&a, &[3.0, 0.0]), 0.0);
+        assert_eq!(Metric::Cosine.distance(&a, &[0.0, 0.0]), 1.0);
+        assert_eq!(Metric::Dot.distance(&[1.0, 2.0], &[3.0, 4.0]), -11.0);
+        assert_eq!(Metric::Euclidean.distance(&[0.0, 0.0], &[3.0, 4.0]), 5.0);
+        assert_eq!(Metric::parse("manhattan"), None);
+    }
+
+    #[test]
+    fn test_hyperplanes_buckets() {
+        let hyperplanes = Hyperplanes::new(4);
+        let buckets = hyperplanes.buckets("embedding", &[0.3, -1.2, 0.5, 2.0]);
+        assert_eq!(buckets.len(), TABLES);
+        assert!(buckets.iter().all(|bucket| bucket.starts_with("embedding:")));
+        // Buckets only depend on the direction, and on nothing else than the dimensions
+        assert_eq!(Hyperplanes::new(4).buckets("embedding", &[0.6, -2.4, 1.0, 4.0]), buckets);
+        let opposite = hyperplanes.buckets("embedding", &[-0.3, 1.2, -0.5, -2.0]);
+        assert!(opposite.iter().all(|bucket| !buckets.contains(bucket)));
+    }
+
+    #[test]
+    fn test_vector_index_candidates() {
+        let mut index = VectorIndex::default();
+        index.insert("1".to_string(), vec!["a".to_string(), "b".to_string()]);
+        index.insert("2".to_string(), vec!["b".to_string(), "c".to_string()]);
+        index.insert("3".to_string(), vec!["d".to_string()]);
+        assert_eq!(index.candidates(&["b".to_string(), "d".to_string()]), vec!["1", "2", "3"]);
+
+        index.insert("1".to_string(), vec!["d".to_string()]);
+        index.remove("3");
+        assert_eq!(index.candidates(&["a".to_string()]), Vec::<String>::new());
+        assert_eq!(index.candidates(&["d".to_string()]), vec!["1"]);
+        assert!(!index.buckets.contains_key("a"));
+    }
+}
@@ -2,10 +2,10 @@ use std::collections::HashMap;
 
 use base::StorageExternal;
 use js_sys::Reflect;
-use serde_wasm_bindgen::to_value;
+use serde::Serialize;
 use wasm_bindgen::{JsCast, JsValue};
 
-use crate::{error::RIDBError, operation::{OpType, Operation}, plugin::BasePlugin, schema::{property_type::PropertyType, Schema}};
+use crate::{error::RIDBError, operation::{OpType, Operation}, plugin::BasePlugin, schema::{apply_defaults, has_type, map_typed, property_type::PropertyType, Schema}, utils::{parse_date, to_bytes, to_iso_date}};
 
 pub mod internals;
 pub mod base;
@@ -74,7 +74,14 @@ impl Storage {
             .map(|migration| migration)
     }
 
-    pub(crate) fn call(&self, collection_name: &str,hook_type: HookType, mut doc: JsValue) -> Result<JsValue, JsValue> {
+    pub(crate) fn call(&self, collection_name: &str,hook_type: HookType, doc: JsValue) -> Result<JsValue, JsValue> {
+        self.call_with_fields(collection_name, hook_type, doc, &JsValue::undefined())
+    }
+
+    /// Runs the plugin hooks, telling them which top-level fields the caller needs.
+    ///
+    /// `fields` is an array of field names, or `undefined` when the whole document is needed.
+    pub(crate) fn call_with_fields(&self, collection_name: &str, hook_type: HookType, mut doc: JsValue, fields: &JsValue) -> Result<JsValue, JsValue> {
         let plugins = &self.plugins;
         for plugin in plugins {
             let hook = match hook_type {
@@ -84,13 +91,38 @@ impl Storage {
             doc = self.compute_hook(
                 collection_name, 
                 doc.clone(), 
-                &hook
+                &hook,
+                fields
             )?;
         }
+        if let HookType::Recover = hook_type {
+            doc = self.rehydrate_dates(collection_name, doc)?;
+        }
         Ok(doc)
     }
 
-    fn compute_hook(&self, collection_name: &str, doc: JsValue, hook: &JsValue) -> Result<JsValue, JsValue> {
+    /// Turns the ISO strings stored for date-type properties back into `Date` objects, in a
+    /// single document or an array of documents.
+    fn rehydrate_dates(&self, collection_name: &str, doc: JsValue) -> Result<JsValue, JsValue> {
+        let schema = self.get_schema(collection_name)?;
+        if !has_type(&schema.properties, PropertyType::Date) {
+            return Ok(doc);
+        }
+        let to_date = |value: &JsValue| {
+            parse_date(&value.as_string()?).map(|time| js_sys::Date::new(&JsValue::from_f64(time)).into())
+        };
+        if js_sys::Array::is_array(&doc) {
+            js_sys::Array::from(&doc)
+                .iter()
+                .map(|document| map_typed(&schema.properties, &document, PropertyType::Date, &to_date))
+                .collect::<Result<js_sys::Array, JsValue>>()
+                .map(JsValue::from)
+        } else {
+            map_typed(&schema.properties, &doc, PropertyType::Date, &to_date)
+        }
+    }
+
+    fn compute_hook(&self, collection_name: &str, doc: JsValue, hook: &JsValue, fields: &JsValue) -> Result<JsValue, JsValue> {
         let schema = self.get_schema(collection_name)?;
         let migration = self.get_migration(collection_name)?;
         if !hook.is_function() {
@@ -98,11 +130,10 @@ impl Storage {
         }
         let hook_fn = hook.dyn_ref::<js_sys::Function>()
             .ok_or_else(|| JsValue::from(RIDBError::error("Hook is not a function")))?;
-        hook_fn.call3(
+        hook_fn.apply(
             &JsValue::NULL,
-            &to_value(&schema)?,
-            &migration,
-            &doc
+            // Plain objects rather than maps, which plugins read the schema back from
+            &js_sys::Array::of4(&schema.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?, migration, &doc, fields)
         ).map_err(|e| JsValue::from(RIDBError::error(&format!("Error executing plugin hook: {:?}", e))))
     }
 
@@ -145,67 +176,61 @@ impl Storage {
 
         if primary_key_type == PropertyType::String && !doc_property.is_string() {
             Err(JsValue::from(RIDBError::from("Unexpected primary key should be a string")))
-        } else if primary_key_type == PropertyType::Number && !doc_property.is_bigint() {
+        } else if primary_key_type.is_numeric() && !doc_property.is_bigint() {
             Err(JsValue::from(RIDBError::from("Unexpected primary key should be number")))
         } else {
             Ok(document)
         }
     }
 
-    /// Checks if a value is of the correct type based on the property type.
-    ///
-    /// # Arguments
-    ///
-    /// * `value` - The value to check.
-    /// * `prop_type` - The expected property type.
-    ///
-    /// # Returns
-    ///
-    /// * `bool` - `true` if the value is of the correct type, otherwise `false`.
-    pub fn is_type_correct(&self, value: &JsValue, prop_type: PropertyType) -> bool {
-        match prop_type {
-            PropertyType::String => value.is_string(),
-            PropertyType::Number => value.as_f64().is_some(),
-            PropertyType::Object => value.is_object(),
-            PropertyType::Array => value.is_array(),
-            PropertyType::Boolean => value.is_falsy() || value.is_truthy(),
-            _ => false,
-        }
-    }
-
     pub fn validate_schema(&self, collection_name: &str, document_without_pk: JsValue) -> Result<JsValue, JsValue> {
         let document = self.ensure_primary_key(collection_name, document_without_pk)?;
-        let schema = self.get_schema(collection_name)?;
-        let properties = schema.properties.clone();
-        let required = schema.required.clone().unwrap_or(Vec::new());
-        let encrypted = schema.encrypted.clone().unwrap_or(Vec::new());
-
-        for (key, prop) in properties {
-            let value = Reflect::get(&document, &JsValue::from_str(&key))?;
-            if value.is_undefined() {
-                if required.contains(&key) && !encrypted.contains(&key) {
-                    return Err(JsValue::from(RIDBError::error(
-                        &format!("Field {} is required", key),
-                    )));
-                }
-            } else {
-                if !self.is_type_correct(&value, prop.property_type) {
-                    return Err(JsValue::from(RIDBError::error(
-                        &format!("Field {} should match type {:?}", key, prop.property_type),
-                    )));
-                }
-            }
-        }
+        self.get_schema(collection_name)?.validate_schema(document.clone())?;
         Ok(document)
     }
 
 
-    pub(crate) async fn write(&self, collection_name: &str, document_without_pk: JsValue) -> Result<JsValue, JsValue> {
+    /// Returns a copy of a document holding the defaults of the properties it lacks, along with
+    /// whether the document is created rather than updated. Defaults are only generated for
+    /// created documents.
+    ///
+    /// Runs before the create hooks, which may move the values of encrypted properties away.
+    pub(crate) async fn with_defaults(&self, collection_name: &str, document: JsValue) -> Result<(JsValue, bool), JsValue> {
+        let schema = self.get_schema(collection_name)?;
+        let pk = Reflect::get(&document, &JsValue::from_str(schema.primary_key.as_str()))
+            .map_err(|e| JsValue::from(RIDBError::from(e)))?;
+        // Documents without a primary key are created, with a generated one or none at all
+        let creating = pk.is_undefined()
+            || pk.is_null()
+            || self.find_document_by_id(collection_name, pk).await?.is_null();
+        let document = apply_defaults(&schema.properties, &document, creating)
+            .map_err(|e| JsValue::from(RIDBError::from(e)))?;
+        Ok((document, creating))
+    }
+
+    pub(crate) async fn write(&self, collection_name: &str, document_without_pk: JsValue, creating: bool) -> Result<JsValue, JsValue> {
         // Move all the preparation logic before the async operation
         let document = {
             let schema = self.get_schema(collection_name)?;
             let primary_key = schema.primary_key.clone();
             let indexes = schema.indexes.clone();
+
+            // Dates are stored as ISO strings, whether they were given as such or as `Date` objects
+            let document_without_pk = if has_type(&schema.properties, PropertyType::Date) {
+                map_typed(&schema.properties, &document_without_pk, PropertyType::Date, &|value| {
+                    to_iso_date(value).map(JsValue::from)
+                })?
+            } else {
+                document_without_pk
+            };
+            // Bytes are stored as copies, so that later changes to the given buffers do not leak in
+            let document_without_pk = if has_type(&schema.properties, PropertyType::Bytes) {
+                map_typed(&schema.properties, &document_without_pk, PropertyType::Bytes, &|value| {
+                    to_bytes(value).map(|bytes| js_sys::Uint8Array::from(bytes.as_slice()).into())
+                })?
+            } else {
+                document_without_pk
+            };
             
             let document = self.validate_schema(collection_name, document_without_pk)?;
             
@@ -221,13 +246,7 @@ impl Storage {
                 }
             };
 
-            let pk = Reflect::get(&document, &JsValue::from_str(primary_key.as_str()))
-                .map_err(|e| JsValue::from(RIDBError::from(e)))?;
-
-            // Find existing document
-            let existing = self.find_document_by_id(collection_name, pk).await?;
-            
-            let op_type = if existing.is_null() { OpType::CREATE } else { OpType::UPDATE };
+            let op_type = if creating { OpType::CREATE } else { OpType::UPDATE };
 
             Operation {
                 collection: collection_name.to_string(),
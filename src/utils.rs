@@ -1,6 +1,6 @@
 
 use serde_wasm_bindgen::from_value;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 
 pub fn extract_property<T>(js_value: &JsValue, key: &str) -> Result<T, JsValue>
     where
@@ -15,3 +15,57 @@ pub fn extract_property<T>(js_value: &JsValue, key: &str) -> Result<T, JsValue>
     from_value(prop).map_err(|err| JsValue::from(err.to_string()))
 }
 
+
+/// Builds a JavaScript `RegExp`, reporting invalid patterns or flags as an error instead of throwing.
+pub fn build_regex(pattern: &str, flags: &str) -> Result<js_sys::RegExp, JsValue> {
+    let constructor = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("RegExp"))?;
+    let args = js_sys::Array::of2(&JsValue::from_str(pattern), &JsValue::from_str(flags));
+    js_sys::Reflect::construct(&constructor.into(), &args)
+        .map(|regex| regex.into())
+        .map_err(|_| JsValue::from_str(&format!("Invalid regular expression: /{}/{}", pattern, flags)))
+}
+
+/// Parses a `date` or `date-time` string into milliseconds since the epoch, `None` when it is not a valid date.
+pub fn parse_date(value: &str) -> Option<f64> {
+    let time = js_sys::Date::parse(value);
+    if time.is_nan() { None } else { Some(time) }
+}
+
+/// Whether a value holds bytes, as a `Uint8Array` or an `ArrayBuffer`.
+pub fn is_bytes(value: &JsValue) -> bool {
+    value.is_instance_of::<js_sys::Uint8Array>() || value.is_instance_of::<js_sys::ArrayBuffer>()
+}
+
+/// Copies the bytes of a `Uint8Array` or an `ArrayBuffer`, `None` for any other value.
+pub fn to_bytes(value: &JsValue) -> Option<Vec<u8>> {
+    if let Some(array) = value.dyn_ref::<js_sys::Uint8Array>() {
+        Some(array.to_vec())
+    } else {
+        value
+            .dyn_ref::<js_sys::ArrayBuffer>()
+            .map(|buffer| js_sys::Uint8Array::new(buffer).to_vec())
+    }
+}
+
+/// Converts a `Date` or a date string into the ISO 8601 string dates are stored as, such as
+/// `2024-01-31T00:00:00.000Z`, which sorts like the instants it holds. `None` for any other value.
+pub fn to_iso_date(value: &JsValue) -> Option<String> {
+    let time = match value.dyn_ref::<js_sys::Date>() {
+        Some(date) => date.get_time(),
+        None => parse_date(&value.as_string()?)?,
+    };
+    if time.is_nan() {
+        return None;
+    }
+    js_sys::Date::new(&JsValue::from_f64(time)).to_iso_string().as_string()
+}
+
+/// Generates a random (version 4) UUID, as in `0b6c4ba9-2c2f-4d55-9b1a-4c2e8f7d6a13`.
+pub fn generate_uuid() -> Result<String, JsValue> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| JsValue::from(e.to_string()))?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]))
+}
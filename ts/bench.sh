@@ -0,0 +1,8 @@
+#!/usr/bin/env bash
+set -eo pipefail  # Exit on any error, including within pipes
+
+# Change to parent directory, add error handling
+cd .. || { echo "Failed to change directory"; exit 1; }
+
+# Benchmarks run optimized, their timings are kept in bench_output.txt
+wasm-pack --log-level error test --release --node -- --features bench,node bench_ 2>&1 | tee bench_output.txt
@@ -40,11 +40,18 @@ export {
     RIDBModule,
     InternalsRecord,
     ExtractType,
+    PropertyValue,
+    PropertyTypeName,
     Doc,
+    CreateDoc,
     Operation,
     Hook,
     BasePluginOptions,
     SchemaType,
+    JSONSchema,
+    JSONSchemaOptions,
+    SchemaViolation,
+    ValidationError,
     EnumerateUpTo,
     EnumerateFrom1To,
     IsVersionGreaterThan0,
@@ -55,8 +62,34 @@ export {
     MigrationsParameter,
     Operators,
     InOperator,
+    NInOperator,
+    NotEqualOperator,
+    ExistsOperator,
+    StringOperators,
+    FieldOperators,
+    NotOperator,
     OperatorOrType,
+    ArrayOperators,
+    ElementType,
+    NestedPathQuery,
     LogicalOperators,
+    TextOperator,
+    NearOperator,
+    SortDirection,
+    QueryOptions,
+    FindPageOptions,
+    Page,
+    Select,
+    FindOptions,
+    FindByIdOptions,
+    AggregateField,
+    Accumulator,
+    AggregateOptions,
+    AggregateResult,
+    ExplainResult,
+    SearchField,
+    SearchOptions,
+    SearchHit,
     QueryType,
     SchemaTypeRecord
 } from "ridb-rust";
@@ -287,4 +320,8 @@ export const SchemaFieldType = {
     boolean: 'boolean' as const,
     array: 'array' as const,
     object: 'object' as const,
+    vector: 'vector' as const,
+    integer: 'integer' as const,
+    date: 'date' as const,
+    bytes: 'bytes' as const,
 };
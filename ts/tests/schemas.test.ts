@@ -1,6 +1,6 @@
 import { describe, it, expect } from 'vitest';
 import { v4 as uuidv4 } from 'uuid';
-import { SchemaFieldType, RIDB } from '..';
+import { SchemaFieldType, RIDB, ValidationError } from '..';
 import { StoragesType } from './shared';
 
 
@@ -344,7 +344,7 @@ export default (platform: string, storages: StoragesType[]) => {
                     await expect(async () => db.start({
                         storageType: storage,
                         password: "test"
-                    })).to.rejects.toThrowError("Serialization Error: invalid value: string \"....\", expected an PropertyType (String, Number, Boolean, Object or Array)")
+                    })).to.rejects.toThrowError("Serialization Error: invalid value: string \"....\", expected an PropertyType (String, Number, Boolean, Object, Array, Vector, Integer, Date or Bytes)")
                 })
                 it("Should throw an error when the minLength is lower than 0", async () => {
                     const db = new RIDB(
@@ -538,7 +538,1329 @@ export default (platform: string, storages: StoragesType[]) => {
 
                     expect(created.__version).to.eq(1);
                 })
+                it("Should be able to find documents using negation operators", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        age: {
+                                            type: SchemaFieldType.number,
+                                        },
+                                        status: {
+                                            type: SchemaFieldType.string,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", age: 30, status: "active" })
+                    await db.collections.demo.create({ id: "2", age: 16, status: "archived" })
+                    await db.collections.demo.create({ id: "3", age: 40, status: "deleted" })
+
+                    const active = await db.collections.demo.find({
+                        status: {
+                            $nin: ["archived", "deleted"]
+                        }
+                    })
+                    expect(active.length).to.eq(1)
+                    expect(active[0].id).to.eq("1")
+
+                    const adults = await db.collections.demo.count({
+                        age: {
+                            $not: {
+                                $lt: 18
+                            }
+                        },
+                        $nor: [
+                            { status: "deleted" }
+                        ]
+                    })
+                    expect(adults).to.eq(1)
+
+                    const notArchived = await db.collections.demo.count({
+                        status: {
+                            $ne: "archived"
+                        }
+                    })
+                    expect(notArchived).to.eq(2)
+                });
+                it("Should be able to find documents using string matching operators", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", name: "John" })
+                    await db.collections.demo.create({ id: "2", name: "Johanna" })
+                    await db.collections.demo.create({ id: "3", name: "Mary" })
+
+                    const prefixed = await db.collections.demo.count({
+                        name: {
+                            $startsWith: "jo",
+                            $options: "i"
+                        }
+                    })
+                    expect(prefixed).to.eq(2)
+
+                    const found = await db.collections.demo.find({
+                        name: {
+                            $regex: "^j.*n$",
+                            $options: "i"
+                        }
+                    })
+                    expect(found.length).to.eq(1)
+                    expect(found[0].id).to.eq("1")
+
+                    await expect(
+                        db.collections.demo.find({
+                            name: {
+                                $contains: "oh",
+                                $options: "g"
+                            }
+                        })
+                    ).rejects.toThrow()
+                });
+                it("Should be able to find documents using nested dot paths", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        address: {
+                                            type: SchemaFieldType.object,
+                                            properties: {
+                                                city: {
+                                                    type: SchemaFieldType.string
+                                                }
+                                            }
+                                        },
+                                        tags: {
+                                            type: SchemaFieldType.array,
+                                            items: [{
+                                                type: SchemaFieldType.object,
+                                                properties: {
+                                                    name: {
+                                                        type: SchemaFieldType.string
+                                                    }
+                                                }
+                                            }]
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", address: { city: "Madrid" }, tags: [{ name: "admin" }, { name: "dev" }] })
+                    await db.collections.demo.create({ id: "2", address: { city: "Lisbon" }, tags: [{ name: "dev" }] })
+
+                    const inMadrid = await db.collections.demo.find({
+                        "address.city": "Madrid"
+                    })
+                    expect(inMadrid.length).to.eq(1)
+                    expect(inMadrid[0].id).to.eq("1")
+
+                    const devs = await db.collections.demo.count({
+                        "tags.name": "dev"
+                    })
+                    expect(devs).to.eq(2)
+
+                    await expect(
+                        db.collections.demo.find({
+                            "address.country": "Spain"
+                        })
+                    ).rejects.toThrow()
+                });
+                it("Should be able to find documents using array operators", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        tags: {
+                                            type: SchemaFieldType.array,
+                                            items: [{
+                                                type: SchemaFieldType.string
+                                            }]
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", tags: ["admin", "staff"] })
+                    await db.collections.demo.create({ id: "2", tags: ["staff"] })
+                    await db.collections.demo.create({ id: "3", tags: [] })
+
+                    expect(await db.collections.demo.count({ tags: "staff" })).to.eq(2)
+                    expect(await db.collections.demo.count({ tags: { $all: ["admin", "staff"] } })).to.eq(1)
+                    expect(await db.collections.demo.count({ tags: { $size: 0 } })).to.eq(1)
+                    expect(await db.collections.demo.count({ tags: { $elemMatch: { $startsWith: "adm" } } })).to.eq(1)
+                });
+                it("Should treat missing fields consistently and support $exists", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        nickname: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        active: {
+                                            type: SchemaFieldType.boolean,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", nickname: "Jo", active: false })
+                    await db.collections.demo.create({ id: "2" })
+
+                    expect(await db.collections.demo.count({ nickname: { $exists: true } })).to.eq(1)
+                    expect(await db.collections.demo.count({ nickname: { $exists: false } })).to.eq(1)
+                    expect(await db.collections.demo.count({ nickname: null })).to.eq(1)
+                    expect(await db.collections.demo.count({ nickname: { $ne: "Jo" } })).to.eq(1)
+                    expect(await db.collections.demo.count({ active: false })).to.eq(1)
+                });
+                it("Should be able to sort documents with multiple keys", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        createdAt: {
+                                            type: SchemaFieldType.number,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", name: "Carol", createdAt: 100 })
+                    await db.collections.demo.create({ id: "2", name: "Alice", createdAt: 200 })
+                    await db.collections.demo.create({ id: "3", name: "Bob", createdAt: 100 })
+
+                    const sorted = await db.collections.demo.find({}, {
+                        sort: { createdAt: -1, name: 1 }
+                    })
+                    expect(sorted.map((doc) => doc.id)).to.deep.eq(["2", "3", "1"])
+
+                    const byName = await db.collections.demo.find({}, {
+                        sort: { name: 1 }
+                    })
+                    expect(byName.map((doc) => doc.id)).to.deep.eq(["2", "3", "1"])
+                });
+                it("Should be able to paginate results with limit and skip", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        rank: {
+                                            type: SchemaFieldType.number,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    for (let i = 1; i <= 5; i++) {
+                        await db.collections.demo.create({ id: `${i}`, rank: i })
+                    }
+
+                    const page = await db.collections.demo.find({}, {
+                        sort: { rank: -1 },
+                        skip: 1,
+                        limit: 2
+                    })
+                    expect(page.map((doc) => doc.rank)).to.deep.eq([4, 3])
+
+                    expect(await db.collections.demo.count({}, { skip: 4, limit: 2 })).to.eq(1)
+                    expect(await db.collections.demo.find({}, { limit: 0 })).to.have.length(0)
+                });
+                it("Should be able to read pages with a cursor", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        score: {
+                                            type: SchemaFieldType.number,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    for (let i = 1; i <= 5; i++) {
+                        await db.collections.demo.create({ id: `${i}`, score: i % 2 })
+                    }
+
+                    const first = await db.collections.demo.findPage({}, {
+                        sort: { score: 1 },
+                        pageSize: 2
+                    })
+                    expect(first.docs.map((doc) => doc.id)).to.deep.eq(["2", "4"])
+                    expect(first.nextCursor).to.be.a("string")
+
+                    // Documents written before the cursor position do not shift later pages
+                    await db.collections.demo.create({ id: "0", score: 0 })
+
+                    const second = await db.collections.demo.findPage({}, {
+                        sort: { score: 1 },
+                        pageSize: 2,
+                        after: first.nextCursor
+                    })
+                    expect(second.docs.map((doc) => doc.id)).to.deep.eq(["1", "3"])
+
+                    const last = await db.collections.demo.findPage({}, {
+                        sort: { score: 1 },
+                        pageSize: 2,
+                        after: second.nextCursor
+                    })
+                    expect(last.docs.map((doc) => doc.id)).to.deep.eq(["5"])
+                    expect(last.nextCursor).to.eq(null)
+
+                    await expect(
+                        db.collections.demo.findPage({}, { pageSize: 2, after: "garbage" })
+                    ).rejects.toThrow()
+                });
+                it("Should be able to select the fields of returned documents", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    encrypted: ['secret'],
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        secret: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        address: {
+                                            type: SchemaFieldType.object,
+                                            properties: {
+                                                city: {
+                                                    type: SchemaFieldType.string
+                                                },
+                                                zip: {
+                                                    type: SchemaFieldType.string
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({
+                        id: "1",
+                        name: "John",
+                        secret: "hidden",
+                        address: { city: "Madrid", zip: "28001" }
+                    })
+
+                    const [included] = await db.collections.demo.find({}, {
+                        select: { name: 1, "address.city": 1 }
+                    })
+                    expect(included).to.deep.eq({
+                        id: "1",
+                        __version: 0,
+                        name: "John",
+                        address: { city: "Madrid" }
+                    })
+
+                    const [excluded] = await db.collections.demo.find({}, {
+                        select: { secret: 0, address: 0 }
+                    })
+                    expect(excluded).to.deep.eq({ id: "1", __version: 0, name: "John" })
+
+                    const withSecret = await db.collections.demo.findById("1", {
+                        select: { secret: 1 }
+                    })
+                    expect(withSecret).to.deep.eq({ id: "1", __version: 0, secret: "hidden" })
+
+                    await expect(
+                        db.collections.demo.find({}, { select: { unknown: 1 } as any })
+                    ).rejects.toThrow()
+                });
+                it("Should migrate encrypted documents when selecting unencrypted fields", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 1,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    encrypted: ['secret'],
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        secret: {
+                                            type: SchemaFieldType.string,
+                                        }
+                                    }
+                                }
+                            } as const,
+                            migrations: {
+                                demo: {
+                                    1: function (doc) {
+                                        return { ...doc, name: `${doc.name} (${doc.secret})` }
+                                    }
+                                }
+                            }
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({
+                        id: "1",
+                        name: "John",
+                        secret: "hidden",
+                        __version: 0
+                    })
+
+                    const [migrated] = await db.collections.demo.find({}, {
+                        select: { name: 1 }
+                    })
+                    expect(migrated).to.deep.eq({ id: "1", __version: 1, name: "John (hidden)" })
+                });
+                it("Should be able to aggregate documents by group", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        category: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        price: {
+                                            type: SchemaFieldType.number,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", category: "fruit", price: 2 })
+                    await db.collections.demo.create({ id: "2", category: "fruit", price: 4 })
+                    await db.collections.demo.create({ id: "3", category: "bread", price: 3 })
+
+                    const rows = await db.collections.demo.aggregate({ price: { $gt: 1 } }, {
+                        groupBy: "category",
+                        accumulators: {
+                            count: { $count: true },
+                            total: { $sum: "price" },
+                            average: { $avg: "price" },
+                            cheapest: { $min: "price" },
+                            priciest: { $max: "price" }
+                        }
+                    })
+                    expect(rows).to.deep.eq([
+                        { category: "bread", count: 1, total: 3, average: 3, cheapest: 3, priciest: 3 },
+                        { category: "fruit", count: 2, total: 6, average: 3, cheapest: 2, priciest: 4 }
+                    ])
+
+                    const [all] = await db.collections.demo.aggregate({}, {
+                        accumulators: { count: { $count: true } }
+                    })
+                    expect(all).to.deep.eq({ count: 3 })
+
+                    await expect(
+                        db.collections.demo.aggregate({}, {
+                            accumulators: { total: { $sum: "category" } }
+                        })
+                    ).rejects.toThrow()
+                });
+                it("Should be able to explain how a query is answered", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        age: {
+                                            type: SchemaFieldType.number,
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    await db.collections.demo.create({ id: "1", age: 20 })
+                    await db.collections.demo.create({ id: "2", age: 30 })
+                    await db.collections.demo.create({ id: "3", age: 40 })
+
+                    const lookup = await db.collections.demo.explain({ id: "2" })
+                    expect(lookup.strategy).to.eq("primaryKey")
+                    expect(lookup.index).to.eq("id")
+                    expect(lookup.scanned).to.eq(1)
+                    expect(lookup.matched).to.eq(1)
+                    expect(lookup.returned).to.eq(1)
+
+                    const scan = await db.collections.demo.explain({ age: { $gt: 25 } }, { limit: 1, sort: { age: 1 } })
+                    expect(scan.strategy).to.eq("scan")
+                    expect(scan.index).to.eq(null)
+                    expect(scan.scanned).to.eq(3)
+                    expect(scan.matched).to.eq(2)
+                    expect(scan.returned).to.eq(1)
+                    expect(scan.executionTimeMs).to.be.at.least(0)
+                });
+                it("Should be able to compare strings and dates with range operators", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                        },
+                                        createdAt: {
+                                            type: SchemaFieldType.string,
+                                            format: 'date-time'
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    await db.collections.demo.create({ id: "1", name: "alice", createdAt: "2024-01-01T00:00:00Z" })
+                    await db.collections.demo.create({ id: "2", name: "bob", createdAt: "2024-06-01T12:00:00+02:00" })
+                    await db.collections.demo.create({ id: "3", name: "carol", createdAt: "2025-01-01T00:00:00Z" })
+
+                    const names = await db.collections.demo.find({ name: { $gte: "b", $lt: "c" } })
+                    expect(names.map((doc) => doc.id)).to.deep.eq(["2"])
+
+                    const dates = await db.collections.demo.find({ createdAt: { $gt: "2024-06-01T09:00:00Z" } })
+                    expect(dates.map((doc) => doc.id).sort()).to.deep.eq(["2", "3"])
+
+                    await expect(
+                        db.collections.demo.find({ createdAt: { $gt: "yesterday" } })
+                    ).rejects.toThrow()
+                    await expect(
+                        db.collections.demo.create({ id: "4", name: "dave", createdAt: "not a date" })
+                    ).rejects.toThrow()
+                });
+                it("Should be able to search full-text indexed properties", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        title: {
+                                            type: SchemaFieldType.string,
+                                            fullText: true
+                                        },
+                                        body: {
+                                            type: SchemaFieldType.string,
+                                            fullText: true
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    await db.collections.demo.create({ id: "1", title: "Rust notes", body: "Ownership and borrowing in Rust" })
+                    await db.collections.demo.create({ id: "2", title: "Gardening", body: "Tomatoes need sun" })
+                    await db.collections.demo.create({ id: "3", title: "WebAssembly", body: "Rust compiles to wasm" })
+
+                    const any = await db.collections.demo.find({ $text: { $search: "rust" } })
+                    expect(any.map((doc) => doc.id)).to.deep.eq(["1", "3"])
+
+                    const all = await db.collections.demo.find({ $text: { $search: "rust wasm", $operator: "and" } })
+                    expect(all.map((doc) => doc.id)).to.deep.eq(["3"])
+
+                    await db.collections.demo.delete("1")
+                    const remaining = await db.collections.demo.find({ $text: { $search: "rust" } })
+                    expect(remaining.map((doc) => doc.id)).to.deep.eq(["3"])
+
+                    const explain = await db.collections.demo.explain({ $text: { $search: "tomatoes" } })
+                    expect(explain.strategy).to.eq("fullText")
+                    expect(explain.returned).to.eq(1)
+
+                    await expect(
+                        db.collections.demo.find({ $or: [{ $text: { $search: "rust" } }] } as any)
+                    ).rejects.toThrow()
+                });
+                it("Should be able to search documents with typos", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string
+                                        },
+                                        age: {
+                                            type: SchemaFieldType.number
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    await db.collections.demo.create({ id: "1", name: "John Smith", age: 30 })
+                    await db.collections.demo.create({ id: "2", name: "Jane Doe", age: 25 })
+                    await db.collections.demo.create({ id: "3", name: "Johnny Walker", age: 40 })
+
+                    const hits = await db.collections.demo.search("jonh", { fields: ["name"] })
+                    expect(hits.map((hit) => hit.doc.id)).to.deep.eq(["1"])
+                    expect(hits[0].score).to.be.greaterThan(0).and.lessThan(1)
+
+                    const exact = await db.collections.demo.search("john smith")
+                    expect(exact[0].doc.id).to.eq("1")
+                    expect(exact[0].score).to.eq(1)
+
+                    const limited = await db.collections.demo.search("jane john", { limit: 1 })
+                    expect(limited).to.have.length(1)
+
+                    const strict = await db.collections.demo.search("jonh", { maxDistance: 0 })
+                    expect(strict).to.have.length(0)
+
+                    await expect(
+                        db.collections.demo.search("jonh", { fields: ["age"] } as any)
+                    ).rejects.toThrow()
+                });
+                it("Should be able to find the nearest vectors", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        category: {
+                                            type: SchemaFieldType.string
+                                        },
+                                        embedding: {
+                                            type: SchemaFieldType.vector,
+                                            dimensions: 3,
+                                            approximate: true
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    await db.collections.demo.create({ id: "1", category: "a", embedding: [1, 0, 0] })
+                    await db.collections.demo.create({ id: "2", category: "b", embedding: [0.9, 0.1, 0] })
+                    await db.collections.demo.create({ id: "3", category: "a", embedding: [0, 1, 0] })
+
+                    const nearest = await db.collections.demo.find({
+                        embedding: { $near: { $vector: [1, 0, 0], $k: 2 } }
+                    })
+                    expect(nearest.map((doc) => doc.id)).to.deep.eq(["1", "2"])
+
+                    const filtered = await db.collections.demo.find({
+                        category: "a",
+                        embedding: { $near: { $vector: [0, 1, 0], $k: 1, $metric: "euclidean" } }
+                    })
+                    expect(filtered.map((doc) => doc.id)).to.deep.eq(["3"])
+
+                    await expect(
+                        db.collections.demo.create({ id: "4", category: "a", embedding: [1, 0] })
+                    ).rejects.toThrow()
+                    await expect(
+                        db.collections.demo.find({ embedding: { $near: { $vector: [1, 0], $k: 1 } } })
+                    ).rejects.toThrow()
+                });
+                it("Should enforce length and item constraints on write", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                            minLength: 2,
+                                            maxLength: 10
+                                        },
+                                        tags: {
+                                            type: SchemaFieldType.array,
+                                            items: [{ type: SchemaFieldType.string }],
+                                            maxItems: 2
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    const created = await db.collections.demo.create({ id: "1", name: "alice", tags: ["a", "b"] })
+                    expect(created.name).to.eq("alice")
+
+                    await expect(
+                        db.collections.demo.create({ id: "2", name: "a".repeat(11) })
+                    ).rejects.toThrow(/name should be at most 10 characters long/)
+                    await expect(
+                        db.collections.demo.create({ id: "3", name: "a" })
+                    ).rejects.toThrow(/name should be at least 2 characters long/)
+                    await expect(
+                        db.collections.demo.update({ id: "1", name: "alice", tags: ["a", "b", "c"] })
+                    ).rejects.toThrow(/tags should have at most 2 items/)
+                    await expect(
+                        db.collections.demo.create({ id: "x".repeat(61), name: "bob" })
+                    ).rejects.toThrow(/id should be at most 60 characters long/)
+                });
+                it("Should validate nested properties and array items on write", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        address: {
+                                            type: SchemaFieldType.object,
+                                            required: ['city'],
+                                            properties: {
+                                                city: {
+                                                    type: SchemaFieldType.string
+                                                }
+                                            }
+                                        },
+                                        scores: {
+                                            type: SchemaFieldType.array,
+                                            items: [{ type: SchemaFieldType.number }]
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    const created = await db.collections.demo.create({ id: "1", address: { city: "Paris" }, scores: [1, 2] } as any)
+                    expect(created.id).to.eq("1")
+
+                    await expect(
+                        db.collections.demo.create({ id: "2", address: {} } as any)
+                    ).rejects.toThrow(/\/address\/city is required/)
+                    await expect(
+                        db.collections.demo.create({ id: "3", scores: [1, "2"] } as any)
+                    ).rejects.toThrow(/\/scores\/1 should match type Number/)
+                });
+                it("Should apply default values to created documents", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60,
+                                            default: { $generate: 'uuid' }
+                                        },
+                                        status: {
+                                            type: SchemaFieldType.string,
+                                            default: 'draft'
+                                        },
+                                        createdAt: {
+                                            type: SchemaFieldType.number,
+                                            default: { $generate: 'now' }
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    const before = Date.now()
+                    const input = {}
+                    const created = await db.collections.demo.create(input)
+                    expect(input).to.deep.eq({})
+                    expect(created.id).to.match(/^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/)
+                    expect(created.status).to.eq("draft")
+                    expect(created.createdAt).to.be.at.least(before)
+
+                    const published = await db.collections.demo.create({ id: "1", status: "published" })
+                    expect(published.status).to.eq("published")
+
+                    const found = await db.collections.demo.findById(created.id)
+                    expect(found.status).to.eq("draft")
+
+                    // Generated defaults only fill created documents, static ones any write
+                    const updated = await db.collections.demo.create({ id: created.id })
+                    expect(updated.createdAt).to.eq(undefined)
+                    expect(updated.status).to.eq("draft")
+                });
+                it("Should apply default values to encrypted properties", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    encrypted: ['secret'],
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        secret: {
+                                            type: SchemaFieldType.string,
+                                            default: 'hidden'
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    const created = await db.collections.demo.create({ id: "1" })
+                    expect(created.secret).to.eq("hidden")
+
+                    const found = await db.collections.demo.findById("1")
+                    expect(found.secret).to.eq("hidden")
+                });
+                it("Should enforce enum, numeric and pattern constraints on write", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        status: {
+                                            type: SchemaFieldType.string,
+                                            enum: ['draft', 'published']
+                                        },
+                                        age: {
+                                            type: SchemaFieldType.integer,
+                                            minimum: 0
+                                        },
+                                        email: {
+                                            type: SchemaFieldType.string,
+                                            pattern: '^[^@\\s]+@[^@\\s]+$'
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    const created = await db.collections.demo.create({ id: "1", status: "draft", age: 30, email: "jane@example.com" })
+                    expect(created.status).to.eq("draft")
+
+                    await expect(
+                        db.collections.demo.create({ id: "2", status: "archived" as any, age: 30, email: "jane@example.com" })
+                    ).rejects.toThrow(/status should be one of "draft", "published"/)
+                    await expect(
+                        db.collections.demo.create({ id: "3", status: "draft", age: 30.5, email: "jane@example.com" })
+                    ).rejects.toThrow(/age should match type Integer/)
+                    await expect(
+                        db.collections.demo.create({ id: "4", status: "draft", age: -1, email: "jane@example.com" })
+                    ).rejects.toThrow(/age should be greater than or equal to 0/)
+                    await expect(
+                        db.collections.demo.create({ id: "5", status: "draft", age: 30, email: "jane" })
+                    ).rejects.toThrow(/email should match pattern/)
+                });
+                it("Should store date properties and read them back as dates", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        createdAt: {
+                                            type: SchemaFieldType.date
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    const created = await db.collections.demo.create({ id: "1", createdAt: new Date("2024-01-15T10:00:00Z") })
+                    expect(created.createdAt).to.be.instanceOf(Date)
+                    expect(created.createdAt.toISOString()).to.eq("2024-01-15T10:00:00.000Z")
+                    await db.collections.demo.create({ id: "2", createdAt: "2024-03-01" as any })
+                    await db.collections.demo.create({ id: "3", createdAt: new Date("2023-12-31T23:59:59Z") })
+
+                    const found = await db.collections.demo.findById("2")
+                    expect(found.createdAt).to.be.instanceOf(Date)
+                    expect(found.createdAt.getTime()).to.eq(Date.UTC(2024, 2, 1))
+
+                    const inRange = await db.collections.demo.find({
+                        createdAt: { $gte: new Date("2024-01-01T00:00:00Z"), $lt: new Date("2024-02-01T00:00:00Z") }
+                    })
+                    expect(inRange.map((doc) => doc.id)).to.deep.eq(["1"])
+
+                    const sorted = await db.collections.demo.find({}, { sort: { createdAt: -1 } } as any)
+                    expect(sorted.map((doc) => doc.id)).to.deep.eq(["2", "1", "3"])
+
+                    await expect(
+                        db.collections.demo.create({ id: "4", createdAt: "someday" as any })
+                    ).rejects.toThrow(/createdAt should be a valid date/)
+                });
+
+                it("Should store bytes properties and find them by equality", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    encrypted: ['signature'],
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        publicKey: {
+                                            type: SchemaFieldType.bytes
+                                        },
+                                        signature: {
+                                            type: SchemaFieldType.bytes
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage,
+                        password: "test"
+                    })
+
+                    const publicKey = new Uint8Array([1, 2, 3, 255])
+                    await db.collections.demo.create({ id: "1", publicKey, signature: new Uint8Array([9, 8, 7]) })
+                    await db.collections.demo.create({ id: "2", publicKey: new Uint8Array([4, 5]).buffer as any, signature: new Uint8Array([0]) })
+                    // Stored bytes are copies of the given ones
+                    publicKey[0] = 0
+
+                    const found = await db.collections.demo.findById("1")
+                    expect(found.publicKey).to.be.instanceOf(Uint8Array)
+                    expect(Array.from(found.publicKey)).to.deep.eq([1, 2, 3, 255])
+                    expect(found.signature).to.be.instanceOf(Uint8Array)
+                    expect(Array.from(found.signature)).to.deep.eq([9, 8, 7])
+
+                    const byKey = await db.collections.demo.find({ publicKey: new Uint8Array([4, 5]) })
+                    expect(byKey.map((doc) => doc.id)).to.deep.eq(["2"])
+                    expect(Array.from(byKey[0].publicKey)).to.deep.eq([4, 5])
+
+                    const byKeys = await db.collections.demo.find({ publicKey: { $in: [new Uint8Array([1, 2, 3, 255]), new Uint8Array([6])] } })
+                    expect(byKeys.map((doc) => doc.id)).to.deep.eq(["1"])
+
+                    await expect(
+                        db.collections.demo.create({ id: "3", publicKey: [1, 2, 3] as any, signature: new Uint8Array([0]) })
+                    ).rejects.toThrow(/publicKey should match type Bytes/)
+                })
+
+                it("Should accept nullable and union property types", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string,
+                                            maxLength: 60
+                                        },
+                                        nickname: {
+                                            type: [SchemaFieldType.string, 'null']
+                                        },
+                                        score: {
+                                            type: [SchemaFieldType.number, SchemaFieldType.string]
+                                        },
+                                        deletedAt: {
+                                            type: SchemaFieldType.date,
+                                            nullable: true
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    await db.collections.demo.create({ id: "1", nickname: null, score: 10, deletedAt: null })
+                    await db.collections.demo.create({ id: "2", nickname: "jo", score: "A+", deletedAt: new Date("2024-01-01T00:00:00Z") })
+
+                    const found = await db.collections.demo.findById("1")
+                    expect(found.nickname).to.eq(null)
+                    expect(found.deletedAt).to.eq(null)
+                    expect(found.score).to.eq(10)
+
+                    const byScore = await db.collections.demo.find({ score: "A+" })
+                    expect(byScore.map((doc) => doc.id)).to.deep.eq(["2"])
+                    expect(byScore[0].deletedAt).to.be.instanceOf(Date)
+                    const withoutNickname = await db.collections.demo.find({ nickname: null })
+                    expect(withoutNickname.map((doc) => doc.id)).to.deep.eq(["1"])
+
+                    await expect(
+                        db.collections.demo.create({ id: "3", nickname: null, score: true as any, deletedAt: null })
+                    ).rejects.toThrow(/score should match type Number or String/)
+                    await expect(
+                        db.collections.demo.create({ id: "4", nickname: null, score: null as any, deletedAt: null })
+                    ).rejects.toThrow(/score should match type Number or String/)
+                })
+
+                it("Should convert schemas from and to JSON Schema", async () => {
+                    const { Schema } = await RIDB.load()
+                    const schema = Schema.fromJSONSchema({
+                        $schema: "https://json-schema.org/draft/2020-12/schema",
+                        title: "User",
+                        type: "object",
+                        required: ["id"],
+                        properties: {
+                            id: { type: "string", maxLength: 36 },
+                            email: { type: "string", pattern: "^[^@]+@[^@]+$" },
+                            age: { type: ["integer", "null"], minimum: 0 },
+                            tags: { type: "array", items: { type: "string" } }
+                        }
+                    }, { primaryKey: "id", version: 2 })
+                    expect(schema.primaryKey).to.eq("id")
+                    expect(schema.version).to.eq(2)
+
+                    expect(schema.toJSONSchema()).to.deep.eq({
+                        $schema: "https://json-schema.org/draft/2020-12/schema",
+                        type: "object",
+                        required: ["id"],
+                        properties: {
+                            id: { type: "string", maxLength: 36 },
+                            email: { type: "string", pattern: "^[^@]+@[^@]+$" },
+                            age: { type: ["integer", "null"], minimum: 0 },
+                            tags: { type: "array", items: { type: "string" } }
+                        }
+                    })
+
+                    expect(
+                        () => Schema.fromJSONSchema({ type: "object", properties: { id: { type: "string", oneOf: [] } } })
+                    ).toThrow(/Unsupported JSON Schema keyword oneOf at \/properties\/id/)
+                    expect(
+                        () => Schema.fromJSONSchema({ type: "object", properties: { key: { type: "string" } } })
+                    ).toThrow(/Primary key id is not a property of the JSON Schema/)
+                })
+
+                it("Should report every schema violation of a document", async () => {
+                    const db = new RIDB(
+                        {
+                            dbName: "test" + uuidv4(),
+                            schemas: {
+                                demo: {
+                                    version: 0,
+                                    primaryKey: 'id',
+                                    type: SchemaFieldType.object,
+                                    required: ['id', 'name'],
+                                    properties: {
+                                        id: {
+                                            type: SchemaFieldType.string
+                                        },
+                                        name: {
+                                            type: SchemaFieldType.string,
+                                            minLength: 2
+                                        },
+                                        age: {
+                                            type: SchemaFieldType.integer,
+                                            minimum: 0
+                                        },
+                                        address: {
+                                            type: SchemaFieldType.object,
+                                            required: ['city'],
+                                            properties: {
+                                                city: {
+                                                    type: SchemaFieldType.string
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            } as const
+                        }
+                    )
+                    await db.start({
+                        storageType: storage
+                    })
+
+                    let error: ValidationError | undefined
+                    try {
+                        await db.collections.demo.create({ id: "1", name: "J", age: -1, address: {} } as any)
+                    } catch (e) {
+                        error = e as ValidationError
+                    }
+                    expect(error).to.be.instanceOf(Error)
+                    expect(error!.code).to.eq("ValidationError")
+                    expect(error!.message).to.eq(
+                        "Validation Error: Field /address/city is required; Field age should be greater than or equal to 0; Field name should be at least 2 characters long"
+                    )
+                    expect(error!.violations).to.deep.eq([
+                        { path: "/address/city", keyword: "required", expected: "String", actual: "Undefined", message: "is required" },
+                        { path: "/age", keyword: "minimum", expected: 0, actual: -1, message: "should be greater than or equal to 0" },
+                        { path: "/name", keyword: "minLength", expected: 2, actual: 1, message: "should be at least 2 characters long" }
+                    ])
+                })
             })
         })
     });
-}
\ No newline at end of file
+}